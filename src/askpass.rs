@@ -87,7 +87,7 @@ pub fn handle() -> Result<()> {
 fn find_askpass_source(config: &SshConfigFile, alias: &str) -> Option<String> {
     // Per-host source
     for entry in config.host_entries() {
-        if entry.alias == alias {
+        if entry.alias == alias && !entry.disabled {
             if let Some(ref source) = entry.askpass {
                 return Some(source.clone());
             }
@@ -182,9 +182,17 @@ pub fn keychain_has_password(alias: &str) -> bool {
     retrieve_from_keychain(alias).is_ok()
 }
 
-/// Retrieve a password from the OS keychain. Public for keychain migration on alias rename.
-pub fn retrieve_keychain_password(alias: &str) -> Result<String> {
-    retrieve_from_keychain(alias)
+/// Move a keychain password to a new alias after a rename.
+/// Returns true if a password was moved.
+pub fn rename_keychain_entry(old_alias: &str, new_alias: &str) -> bool {
+    let Ok(password) = retrieve_from_keychain(old_alias) else {
+        return false;
+    };
+    if store_in_keychain(new_alias, &password).is_err() {
+        return false;
+    }
+    let _ = remove_from_keychain(old_alias);
+    true
 }
 
 /// Store a password in the OS keychain.
@@ -798,6 +806,7 @@ mod tests {
             elements: SshConfigFile::parse_content(content),
             path: std::path::PathBuf::from("/tmp/test_askpass_config"),
             crlf: false,
+            index: Default::default(),
         }
     }
 
@@ -27,6 +27,8 @@ pub enum AppEvent {
     },
     SyncProgress { provider: String, message: String },
     UpdateAvailable { version: String },
+    /// A watched config or Include file changed on disk.
+    ConfigChanged { path: std::path::PathBuf },
     PollError,
 }
 
@@ -65,6 +67,7 @@ impl EventHandler {
                 match event::poll(timeout) {
                     Ok(true) => {
                         if let Ok(evt) = event::read() {
+                            #[allow(clippy::collapsible_match)]
                             match evt {
                                 CrosstermEvent::Key(key)
                                     if key.kind == KeyEventKind::Press =>
@@ -134,7 +137,8 @@ impl EventHandler {
                 | AppEvent::SyncPartial { .. }
                 | AppEvent::SyncError { .. }
                 | AppEvent::SyncProgress { .. }
-                | AppEvent::UpdateAvailable { .. } => preserved.push(event),
+                | AppEvent::UpdateAvailable { .. }
+                | AppEvent::ConfigChanged { .. } => preserved.push(event),
                 _ => {}
             }
         }
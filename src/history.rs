@@ -72,6 +72,26 @@ impl ConnectionHistory {
         let _ = self.save();
     }
 
+    /// Move a host's history to its new alias after a rename. Merges with any
+    /// history the new alias already had. Returns true if there was any to move.
+    pub fn rename(&mut self, old_alias: &str, new_alias: &str) -> bool {
+        let Some(old) = self.entries.remove(old_alias) else {
+            return false;
+        };
+        let entry = self
+            .entries
+            .entry(new_alias.to_string())
+            .or_insert(HistoryEntry {
+                alias: new_alias.to_string(),
+                last_connected: 0,
+                count: 0,
+            });
+        entry.last_connected = entry.last_connected.max(old.last_connected);
+        entry.count += old.count;
+        let _ = self.save();
+        true
+    }
+
     /// Last connected timestamp for a host (0 if never connected).
     pub fn last_connected(&self, alias: &str) -> u64 {
         self.entries.get(alias).map_or(0, |e| e.last_connected)
@@ -148,6 +168,33 @@ mod tests {
         assert_eq!(history.frecency_score("unknown"), 0.0);
     }
 
+    #[test]
+    fn test_rename_moves_and_merges_history() {
+        let dir = std::env::temp_dir().join(format!("purple_history_rename_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let mut history = ConnectionHistory {
+            entries: HashMap::new(),
+            path: dir.join("history.tsv"),
+        };
+        for (alias, last_connected, count) in [("old", 200, 3), ("new", 100, 1)] {
+            history.entries.insert(
+                alias.to_string(),
+                HistoryEntry {
+                    alias: alias.to_string(),
+                    last_connected,
+                    count,
+                },
+            );
+        }
+        assert!(history.rename("old", "new"));
+        assert!(!history.entries.contains_key("old"));
+        let merged = &history.entries["new"];
+        assert_eq!((merged.last_connected, merged.count), (200, 4));
+        assert_eq!(fs::read_to_string(dir.join("history.tsv")).unwrap(), "new\t200\t4");
+        assert!(!history.rename("missing", "other"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_format_time_ago_zero() {
         assert_eq!(ConnectionHistory::format_time_ago(0), "");
@@ -237,11 +237,11 @@ fn add_entries(
         // Write group header before the first actually-imported host
         if let Some(group_name) = group.filter(|_| !header_written) {
             if !config.elements.is_empty() && !config.last_element_has_trailing_blank() {
-                config.elements.push(
+                config.push_element(
                     crate::ssh_config::model::ConfigElement::GlobalLine(String::new()),
                 );
             }
-            config.elements.push(
+            config.push_element(
                 crate::ssh_config::model::ConfigElement::GlobalLine(format!("# {}", group_name)),
             );
             header_written = true;
@@ -250,9 +250,7 @@ fn add_entries(
         if group.is_some() && imported == 0 {
             // Push first host directly after group comment (no blank separator between them)
             let block = SshConfigFile::entry_to_block(entry);
-            config
-                .elements
-                .push(crate::ssh_config::model::ConfigElement::HostBlock(block));
+            config.push_element(crate::ssh_config::model::ConfigElement::HostBlock(block));
         } else {
             config.add_host(entry);
         }
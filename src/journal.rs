@@ -0,0 +1,247 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::ssh_config::model::{ConfigElement, SshConfigFile};
+
+/// Undo steps kept. Each holds a full copy of the config, so the oldest are
+/// dropped past this to keep memory bounded on large configs.
+const MAX_DEPTH: usize = 100;
+
+/// A recorded config mutation.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// Short description shown in the status bar and history list (e.g. "delete web").
+    pub label: String,
+    /// Unix timestamp of the original change.
+    pub at: u64,
+    /// Config state on the other side of the change: before it while the entry
+    /// sits on the undo stack, after it while it sits on the redo stack.
+    snapshot: Vec<ConfigElement>,
+}
+
+/// Undo/redo journal for config mutations made from the TUI.
+/// Every successful write records the state it replaced. Undo and redo swap
+/// snapshots and write them back, so Include files are restored too.
+#[derive(Debug, Default)]
+pub struct Journal {
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Record a written change. `before` is the config state prior to it.
+    /// Clears the redo stack and drops the oldest step past `MAX_DEPTH`.
+    pub fn record(&mut self, label: impl Into<String>, before: Vec<ConfigElement>) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.undo.push(JournalEntry {
+            label: label.into(),
+            at,
+            snapshot: before,
+        });
+        if self.undo.len() > MAX_DEPTH {
+            self.undo.drain(..self.undo.len() - MAX_DEPTH);
+        }
+        self.redo.clear();
+    }
+
+    /// Forget everything (the config changed outside purple).
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    /// Undoable operations, most recent first.
+    pub fn undo_entries(&self) -> impl Iterator<Item = &JournalEntry> {
+        self.undo.iter().rev()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Undo the last `steps` operations and write the result in one go.
+    /// Returns the labels of the undone operations, most recent first.
+    /// On write failure the config and journal are left as they were.
+    pub fn undo(&mut self, config: &mut SshConfigFile, steps: usize) -> Result<Vec<String>> {
+        Self::travel(&mut self.undo, &mut self.redo, config, steps)
+    }
+
+    /// Redo the last `steps` undone operations. See `undo`.
+    pub fn redo(&mut self, config: &mut SshConfigFile, steps: usize) -> Result<Vec<String>> {
+        Self::travel(&mut self.redo, &mut self.undo, config, steps)
+    }
+
+    fn travel(
+        from: &mut Vec<JournalEntry>,
+        to: &mut Vec<JournalEntry>,
+        config: &mut SshConfigFile,
+        steps: usize,
+    ) -> Result<Vec<String>> {
+        let steps = steps.min(from.len());
+        if steps == 0 {
+            return Ok(Vec::new());
+        }
+        let labels = Self::shift(from, to, config, steps);
+        if let Err(e) = config.write() {
+            Self::shift(to, from, config, steps);
+            return Err(e);
+        }
+        Ok(labels)
+    }
+
+    /// Move `steps` entries from one stack to the other, swapping each entry's
+    /// snapshot with the config's current elements.
+    fn shift(
+        from: &mut Vec<JournalEntry>,
+        to: &mut Vec<JournalEntry>,
+        config: &mut SshConfigFile,
+        steps: usize,
+    ) -> Vec<String> {
+        let mut labels = Vec::with_capacity(steps);
+        for _ in 0..steps {
+            let Some(mut entry) = from.pop() else {
+                break;
+            };
+            std::mem::swap(&mut entry.snapshot, &mut config.elements);
+            config.invalidate_index();
+            labels.push(entry.label.clone());
+            to.push(entry);
+        }
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssh_config::model::HostEntry;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_config(name: &str, content: &str) -> (PathBuf, SshConfigFile) {
+        let dir = std::env::temp_dir().join(format!("purple_journal_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config");
+        fs::write(&path, content).unwrap();
+        let config = SshConfigFile::parse(&path).unwrap();
+        (dir, config)
+    }
+
+    fn entry(alias: &str) -> HostEntry {
+        HostEntry {
+            alias: alias.to_string(),
+            hostname: format!("{}.example.com", alias),
+            ..Default::default()
+        }
+    }
+
+    fn aliases(config: &SshConfigFile) -> Vec<String> {
+        config.host_entries().into_iter().map(|h| h.alias).collect()
+    }
+
+    /// Apply a change, write it and record it, like the TUI does.
+    fn change(journal: &mut Journal, config: &mut SshConfigFile, label: &str, f: impl FnOnce(&mut SshConfigFile)) {
+        let before = config.clone();
+        f(config);
+        config.write().unwrap();
+        journal.record(label, before.elements);
+    }
+
+    #[test]
+    fn undo_and_redo_walk_the_history() {
+        let (dir, mut config) = temp_config("walk", "Host a\n  HostName a.example.com\n");
+        let mut journal = Journal::default();
+        change(&mut journal, &mut config, "add b", |c| c.add_host(&entry("b")));
+        change(&mut journal, &mut config, "add c", |c| c.add_host(&entry("c")));
+        change(&mut journal, &mut config, "delete a", |c| c.delete_host("a"));
+        assert_eq!(aliases(&config), vec!["b", "c"]);
+
+        assert_eq!(journal.undo(&mut config, 1).unwrap(), vec!["delete a"]);
+        assert_eq!(aliases(&config), vec!["a", "b", "c"]);
+        assert_eq!(journal.undo(&mut config, 1).unwrap(), vec!["add c"]);
+        assert_eq!(aliases(&config), vec!["a", "b"]);
+        assert_eq!((journal.undo_len(), journal.redo_len()), (1, 2));
+
+        assert_eq!(journal.redo(&mut config, 2).unwrap(), vec!["add c", "delete a"]);
+        assert_eq!(aliases(&config), vec!["b", "c"]);
+
+        // What's on disk matches the in-memory state
+        let on_disk = SshConfigFile::parse(&config.path).unwrap();
+        assert_eq!(aliases(&on_disk), vec!["b", "c"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn undo_to_a_chosen_point() {
+        let (dir, mut config) = temp_config("point", "");
+        let mut journal = Journal::default();
+        for alias in ["a", "b", "c", "d"] {
+            change(&mut journal, &mut config, &format!("add {}", alias), |c| {
+                c.add_host(&entry(alias))
+            });
+        }
+        let labels: Vec<&str> = journal.undo_entries().map(|e| e.label.as_str()).collect();
+        assert_eq!(labels, vec!["add d", "add c", "add b", "add a"]);
+
+        // Roll back to just before "add b" (third entry)
+        let undone = journal.undo(&mut config, 3).unwrap();
+        assert_eq!(undone, vec!["add d", "add c", "add b"]);
+        assert_eq!(aliases(&config), vec!["a"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn new_change_clears_redo() {
+        let (dir, mut config) = temp_config("redo", "");
+        let mut journal = Journal::default();
+        change(&mut journal, &mut config, "add a", |c| c.add_host(&entry("a")));
+        journal.undo(&mut config, 1).unwrap();
+        assert_eq!(journal.redo_len(), 1);
+        change(&mut journal, &mut config, "add b", |c| c.add_host(&entry("b")));
+        assert_eq!(journal.redo_len(), 0);
+        assert!(journal.redo(&mut config, 1).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn history_depth_is_capped() {
+        let mut journal = Journal::default();
+        for i in 0..MAX_DEPTH + 5 {
+            journal.record(format!("change {}", i), Vec::new());
+        }
+        assert_eq!(journal.undo_len(), MAX_DEPTH);
+        assert_eq!(journal.undo_entries().next().unwrap().label, format!("change {}", MAX_DEPTH + 4));
+        assert_eq!(journal.undo_entries().last().unwrap().label, "change 5");
+    }
+
+    #[test]
+    fn nothing_to_undo_is_a_no_op() {
+        let (dir, mut config) = temp_config("empty", "Host a\n");
+        let mut journal = Journal::default();
+        assert!(journal.undo(&mut config, 1).unwrap().is_empty());
+        assert_eq!(aliases(&config), vec!["a"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn failed_write_leaves_state_untouched() {
+        let (dir, mut config) = temp_config("fail", "");
+        let mut journal = Journal::default();
+        change(&mut journal, &mut config, "add a", |c| c.add_host(&entry("a")));
+        // Point the config below a regular file so the write fails
+        config.path = dir.join("config").join("nested");
+        assert!(journal.undo(&mut config, 1).is_err());
+        assert_eq!(aliases(&config), vec!["a"]);
+        assert_eq!((journal.undo_len(), journal.redo_len()), (1, 0));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
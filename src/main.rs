@@ -7,21 +7,25 @@ mod handler;
 mod fs_util;
 mod history;
 mod import;
+mod journal;
 mod ping;
 mod preferences;
 mod providers;
 mod quick_add;
+mod saved_search;
 mod ssh_config;
 mod ssh_keys;
 mod tui;
 mod tunnel;
 mod ui;
 mod update;
+mod watcher;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::{CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{Shell, generate};
 
 use app::App;
@@ -50,6 +54,14 @@ struct Cli {
     #[arg(short, long)]
     list: bool,
 
+    /// Only list hosts matching a search query (e.g. 'user:root -tag:prod')
+    #[arg(long, value_name = "QUERY", requires = "list")]
+    filter: Option<String>,
+
+    /// Only list members of a saved search (see Ctrl+S in search)
+    #[arg(long, value_name = "NAME", requires = "list", conflicts_with = "filter")]
+    search: Option<String>,
+
     /// Path to SSH config file
     #[arg(long, default_value = "~/.ssh/config")]
     config: String,
@@ -76,6 +88,48 @@ enum Commands {
         /// Path to identity file (SSH key)
         #[arg(short, long)]
         key: Option<String>,
+
+        /// Fill in the rest from a host template
+        #[arg(short, long)]
+        template: Option<String>,
+
+        /// Place the host under this group header (created if missing)
+        #[arg(short, long)]
+        group: Option<String>,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Rename a host and update ProxyJump/ProxyCommand references, history and keychain
+    Rename {
+        /// Current alias
+        old: String,
+
+        /// New alias
+        new: String,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Park a host: comment its block out so ssh skips it
+    Disable {
+        /// Host alias
+        alias: String,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Bring back a host parked with `purple disable`
+    Enable {
+        /// Host alias
+        alias: String,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
     },
     /// Import hosts from a file or known_hosts
     Import {
@@ -89,6 +143,10 @@ enum Commands {
         /// Group label for imported hosts
         #[arg(short, long)]
         group: Option<String>,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
     },
     /// Sync hosts from cloud providers (DigitalOcean, Vultr, Linode, Hetzner, UpCloud, Proxmox VE)
     Sync {
@@ -106,6 +164,29 @@ enum Commands {
         /// Replace local tags with provider tags instead of merging
         #[arg(long)]
         reset_tags: bool,
+
+        /// Show the changes as a diff and ask before writing (with --dry-run: only show)
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Show the effective SSH config for a host (like ssh -G, with sources)
+    Show {
+        /// Host alias
+        alias: String,
+
+        /// Only print this directive (e.g. User, IdentityFile)
+        key: Option<String>,
+    },
+    /// Check the SSH config for mistakes (exits non-zero when problems are found)
+    Lint {
+        /// Output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// List, compare and restore SSH config backups
+    Backup {
+        #[command(subcommand)]
+        command: BackupCommands,
     },
     /// Manage cloud provider configurations
     Provider {
@@ -126,6 +207,12 @@ enum Commands {
     Update,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
 #[derive(Subcommand)]
 enum ProviderCommands {
     /// Add or update a provider configuration
@@ -172,6 +259,10 @@ enum ProviderCommands {
         /// Disable automatic sync on startup
         #[arg(long, conflicts_with = "auto_sync")]
         no_auto_sync: bool,
+
+        /// Host template for new hosts from this provider
+        #[arg(long)]
+        template: Option<String>,
     },
     /// List configured providers
     List,
@@ -196,6 +287,10 @@ enum TunnelCommands {
 
         /// Forward spec: L:port:host:port (local), R:port:host:port (remote) or D:port (SOCKS)
         forward: String,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
     },
     /// Remove a tunnel from a host
     Remove {
@@ -204,6 +299,10 @@ enum TunnelCommands {
 
         /// Forward spec: L:port:host:port (local), R:port:host:port (remote) or D:port (SOCKS)
         forward: String,
+
+        /// Show the changes as a diff and ask before writing
+        #[arg(long)]
+        diff: bool,
     },
     /// Start a tunnel (foreground, Ctrl+C to stop)
     Start {
@@ -212,6 +311,22 @@ enum TunnelCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum BackupCommands {
+    /// List backups of the SSH config, newest first
+    List,
+    /// Show what changed since a backup (host summary and line diff)
+    Diff {
+        /// Backup id (or a unique prefix) from `purple backup list`
+        id: String,
+    },
+    /// Restore a backup (the current config is backed up first)
+    Restore {
+        /// Backup id (or a unique prefix) from `purple backup list`
+        id: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum PasswordCommands {
     /// Store a password in the OS keychain for a host
@@ -278,38 +393,84 @@ fn main() -> Result<()> {
         return handle_password_command(command);
     }
 
+    SshConfigFile::set_backup_retention(preferences::load_backup_retention());
     let config_path = resolve_config_path(&cli.config)?;
+
+    // Backups work even when the current config doesn't parse
+    if let Some(Commands::Backup { command }) = cli.command {
+        return handle_backup_command(&config_path, command);
+    }
+
     let config = SshConfigFile::parse(&config_path)?;
 
     // Handle subcommands that need SSH config
     match cli.command {
-        Some(Commands::Add { target, alias, key }) => {
-            return handle_quick_add(config, &target, alias.as_deref(), key.as_deref());
+        Some(Commands::Add {
+            target,
+            alias,
+            key,
+            template,
+            group,
+            diff,
+        }) => {
+            return handle_quick_add(
+                config,
+                &target,
+                alias.as_deref(),
+                key.as_deref(),
+                template.as_deref(),
+                group.as_deref(),
+                diff,
+            );
+        }
+        Some(Commands::Rename { old, new, diff }) => {
+            return handle_rename(config, &old, &new, diff);
+        }
+        Some(Commands::Disable { alias, diff }) => {
+            return handle_set_disabled(config, &alias, true, diff);
+        }
+        Some(Commands::Enable { alias, diff }) => {
+            return handle_set_disabled(config, &alias, false, diff);
         }
         Some(Commands::Import {
             file,
             known_hosts,
             group,
+            diff,
         }) => {
-            return handle_import(config, file.as_deref(), known_hosts, group.as_deref());
+            return handle_import(config, file.as_deref(), known_hosts, group.as_deref(), diff);
         }
         Some(Commands::Sync {
             provider,
             dry_run,
             remove,
             reset_tags,
+            diff,
         }) => {
-            return handle_sync(config, provider.as_deref(), dry_run, remove, reset_tags);
+            return handle_sync(config, provider.as_deref(), dry_run, remove, reset_tags, diff);
         }
         Some(Commands::Tunnel { command }) => {
             return handle_tunnel_command(config, command);
         }
-        Some(Commands::Provider { .. }) | Some(Commands::Update) | Some(Commands::Password { .. }) => unreachable!(),
+        Some(Commands::Show { alias, key }) => {
+            return handle_show(&config, &alias, key.as_deref());
+        }
+        Some(Commands::Lint { format }) => {
+            return handle_lint(&config, format);
+        }
+        Some(Commands::Provider { .. })
+        | Some(Commands::Update)
+        | Some(Commands::Password { .. })
+        | Some(Commands::Backup { .. }) => unreachable!(),
         None => {}
     }
 
     // Direct connect mode (--connect)
     if let Some(alias) = cli.connect {
+        if config.host_entries().iter().any(|h| h.alias == alias && h.disabled) {
+            eprintln!("{} is disabled. Run 'purple enable {}' first.", alias, alias);
+            std::process::exit(1);
+        }
         let askpass = config.host_entries().iter()
             .find(|h| h.alias == alias)
             .and_then(|h| h.askpass.clone())
@@ -327,23 +488,64 @@ fn main() -> Result<()> {
 
     // List mode
     if cli.list {
-        let entries = config.host_entries();
-        if entries.is_empty() {
+        let mut entries = config.host_entries();
+        let saved = cli.search.as_deref().map(|name| {
+            let searches = saved_search::SavedSearches::load();
+            match searches.get(name) {
+                Some(search) => search.query.clone(),
+                None => {
+                    let names: Vec<&str> =
+                        searches.entries.iter().map(|s| s.name.as_str()).collect();
+                    if names.is_empty() {
+                        eprintln!("No saved search '{}'. Save one with Ctrl+S while searching.", name);
+                    } else {
+                        eprintln!("No saved search '{}'. Saved: {}.", name, names.join(", "));
+                    }
+                    std::process::exit(1);
+                }
+            }
+        });
+        if let Some(ref filter) = cli.filter.clone().or(saved) {
+            let query = match ssh_config::query::Query::parse(filter) {
+                Ok(query) => query,
+                Err(e) => {
+                    eprintln!("Invalid filter: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            entries.retain(|host| query.matches(host, &config_path));
+        } else if entries.is_empty() {
             println!("No hosts configured. Run 'purple' to add some!");
-        } else {
-            for host in &entries {
-                let user = if host.user.is_empty() {
-                    String::new()
-                } else {
-                    format!("{}@", host.user)
-                };
-                let port = if host.port == 22 {
-                    String::new()
-                } else {
-                    format!(":{}", host.port)
-                };
-                println!("{:<20} {}{}{}", host.alias, user, host.hostname, port);
+        }
+        let host_groups: HashMap<String, String> = config
+            .groups()
+            .into_iter()
+            .flat_map(|g| {
+                let name = g.name;
+                g.hosts.into_iter().map(move |alias| (alias, name.clone()))
+            })
+            .collect();
+        let mut current_group: Option<&str> = None;
+        for (i, host) in entries.iter().enumerate() {
+            let group = host_groups.get(&host.alias).map(String::as_str);
+            if let Some(name) = group.filter(|_| group != current_group) {
+                if i > 0 {
+                    println!();
+                }
+                println!("── {}", name);
             }
+            current_group = group;
+            let user = if host.user.is_empty() {
+                String::new()
+            } else {
+                format!("{}@", host.user)
+            };
+            let port = if host.port == 22 {
+                String::new()
+            } else {
+                format!(":{}", host.port)
+            };
+            println!("{:<20} {}{}{}", host.alias, user, host.hostname, port);
         }
         return Ok(());
     }
@@ -391,6 +593,7 @@ fn apply_saved_sort(app: &mut App) {
     app.sort_mode = saved;
     app.group_by_provider = group;
     app.view_mode = preferences::load_view_mode();
+    app.confirm_writes = preferences::load_confirm_writes();
     if saved != app::SortMode::Original || group {
         app.apply_sort();
     }
@@ -420,7 +623,10 @@ fn run_tui(mut app: App) -> Result<()> {
     terminal.enter()?;
     let events = EventHandler::new(250);
     let events_tx = events.sender();
-    let mut last_config_check = std::time::Instant::now();
+    app.reload.watcher = Some(watcher::ConfigWatcher::spawn(
+        watcher::WatchSet::for_config(&app.config),
+        events_tx.clone(),
+    ));
 
     // Auto-sync configured providers on startup (skipped when auto_sync=false)
     for section in app.provider_config.configured_providers().to_vec() {
@@ -444,17 +650,15 @@ fn run_tui(mut app: App) -> Result<()> {
             AppEvent::Key(key) => handler::handle_key_event(&mut app, key, &events_tx)?,
             AppEvent::Tick => {
                 app.tick_status();
-                // Throttle config file stat() to every 4 seconds
-                if last_config_check.elapsed() >= std::time::Duration::from_secs(4) {
-                    app.check_config_changed();
-                    last_config_check = std::time::Instant::now();
-                }
+                // Apply changes held back while a form was open
+                app.apply_config_changes();
                 // Poll active tunnels for exit
                 let exited = app.poll_tunnels();
                 for (_alias, msg, is_error) in exited {
                     app.set_status(msg, is_error);
                 }
             }
+            AppEvent::ConfigChanged { path } => app.config_changed(path),
             AppEvent::PingResult { alias, reachable } => {
                 let status = if reachable {
                     app::PingStatus::Reachable
@@ -585,7 +789,6 @@ fn run_tui(mut app: App) -> Result<()> {
             askpass::cleanup_marker(&alias);
             terminal.enter()?;
             events.resume();
-            last_config_check = std::time::Instant::now();
             // Reload in case config changed externally
             app.config = SshConfigFile::parse(&app.reload.config_path)?;
             app.reload_hosts();
@@ -608,7 +811,27 @@ fn handle_quick_add(
     target: &str,
     alias: Option<&str>,
     key: Option<&str>,
+    template: Option<&str>,
+    group: Option<&str>,
+    diff: bool,
 ) -> Result<()> {
+    let templates = ssh_config::template::load_all(&config);
+    let template = match template {
+        Some(name) => match ssh_config::template::find(&templates, name) {
+            Some(t) => Some(t),
+            None => {
+                let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+                if names.is_empty() {
+                    eprintln!("No template '{}'. No templates defined yet.", name);
+                } else {
+                    eprintln!("No template '{}'. Available: {}", name, names.join(", "));
+                }
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
     let parsed = quick_add::parse_target(target).map_err(|e| anyhow::anyhow!(e))?;
 
     let alias_str = alias
@@ -664,27 +887,121 @@ fn handle_quick_add(
         std::process::exit(1);
     }
 
-    let entry = HostEntry {
+    let mut entry = HostEntry {
         alias: alias_str.clone(),
         hostname: parsed.hostname,
         user: parsed.user,
         port: parsed.port,
-        identity_file: key_val,
+        identity_files: if key_val.is_empty() { Vec::new() } else { vec![key_val] },
         ..Default::default()
     };
+    if let Some(template) = template {
+        template.fill(&mut entry);
+    }
 
-    config.add_host(&entry);
+    let before = config.clone();
+    match group {
+        Some(group) => config.add_host_to_group(&entry, group),
+        None => config.add_host(&entry),
+    }
+    if let Some(template) = template {
+        config.apply_template(&alias_str, template);
+    }
+    if diff && !confirm_diff(&before, &config) {
+        return Ok(());
+    }
     config.write()?;
     println!("Welcome aboard, {}!", alias_str);
     Ok(())
 }
 
+fn handle_rename(mut config: SshConfigFile, old: &str, new: &str, diff: bool) -> Result<()> {
+    if !config.has_host(old) {
+        eprintln!("No host '{}' found.", old);
+        std::process::exit(1);
+    }
+    if config.host_entries().iter().any(|h| h.alias == old && h.disabled) {
+        eprintln!("'{}' is disabled. Run 'purple enable {}' first.", old, old);
+        std::process::exit(1);
+    }
+    if new == old {
+        eprintln!("'{}' already goes by that name.", old);
+        std::process::exit(1);
+    }
+    if config.has_host(new) {
+        eprintln!("'{}' already exists. Aliases are like fingerprints — unique.", new);
+        std::process::exit(1);
+    }
+    if new.is_empty() || new.contains(char::is_whitespace) || new.chars().any(|c| c.is_control()) {
+        eprintln!("Alias can't be empty or contain whitespace or control characters.");
+        std::process::exit(1);
+    }
+    if new.contains('#') || ssh_config::model::is_host_pattern(new) {
+        eprintln!("Alias can't contain '#' or pattern characters.");
+        std::process::exit(1);
+    }
+
+    let askpass = config
+        .host_entries()
+        .into_iter()
+        .find(|h| h.alias == old)
+        .and_then(|h| h.askpass)
+        .or_else(preferences::load_askpass_default);
+    let before = config.clone();
+    let references = config.rename_host(old, new).unwrap_or_default();
+    if diff && !confirm_diff(&before, &config) {
+        return Ok(());
+    }
+    config.write()?;
+    println!("Renamed {} to {}.", old, new);
+    for block in &references {
+        println!("  Updated reference in {}.", block);
+    }
+    if history::ConnectionHistory::load().rename(old, new) {
+        println!("  Moved connection history.");
+    }
+    if askpass.as_deref() == Some("keychain") && askpass::rename_keychain_entry(old, new) {
+        println!("  Moved keychain password.");
+    }
+    Ok(())
+}
+
+fn handle_set_disabled(mut config: SshConfigFile, alias: &str, disable: bool, diff: bool) -> Result<()> {
+    let Some(host) = config.host_entries().into_iter().find(|h| h.alias == alias) else {
+        eprintln!("No host '{}' found.", alias);
+        std::process::exit(1);
+    };
+    if host.disabled == disable {
+        let state = if disable { "disabled" } else { "enabled" };
+        println!("{} is already {}.", alias, state);
+        return Ok(());
+    }
+    let before = config.clone();
+    if disable {
+        config.disable_host(alias);
+    } else {
+        config.enable_host(alias);
+    }
+    if diff && !confirm_diff(&before, &config) {
+        return Ok(());
+    }
+    config.write()?;
+    if disable {
+        println!("Disabled {}. ssh skips it until 'purple enable {}'.", alias, alias);
+    } else {
+        println!("Enabled {}.", alias);
+    }
+    Ok(())
+}
+
 fn handle_import(
     mut config: SshConfigFile,
     file: Option<&str>,
     known_hosts: bool,
     group: Option<&str>,
+    diff: bool,
 ) -> Result<()> {
+    let before = config.clone();
     let result = if known_hosts {
         import::import_from_known_hosts(&mut config, group)
     } else if let Some(path) = file {
@@ -698,6 +1015,9 @@ fn handle_import(
     match result {
         Ok((imported, skipped, parse_failures, read_errors)) => {
             if imported > 0 {
+                if diff && !confirm_diff(&before, &config) {
+                    return Ok(());
+                }
                 config.write()?;
             }
             println!(
@@ -736,6 +1056,7 @@ fn handle_sync(
     dry_run: bool,
     remove: bool,
     reset_tags: bool,
+    diff: bool,
 ) -> Result<()> {
     let provider_config = providers::config::ProviderConfig::load();
     let sections: Vec<&providers::config::ProviderSection> = if let Some(name) = provider_name {
@@ -768,6 +1089,7 @@ fn handle_sync(
     let mut any_changes = false;
     let mut any_failures = false;
     let mut any_hard_failures = false;
+    let before = config.clone();
 
     for section in &sections {
         let provider = match providers::get_provider_with_config(&section.provider, section) {
@@ -834,8 +1156,9 @@ fn handle_sync(
             println!("{} servers found.", hosts.len());
         }
         let effective_remove = remove && !suppress_remove;
+        // --dry-run --diff applies the changes in memory only, so they can be shown
         let result = providers::sync::sync_provider_with_options(
-            &mut config, &*provider, &hosts, section, effective_remove, dry_run, reset_tags,
+            &mut config, &*provider, &hosts, section, effective_remove, dry_run && !diff, reset_tags,
         );
         let prefix = if dry_run { "  Would have: " } else { "  " };
         println!(
@@ -845,15 +1168,21 @@ fn handle_sync(
         if result.removed > 0 {
             println!("  Removed {}.", result.removed);
         }
+        for alias in &result.kept {
+            println!("  Kept {}: other hosts jump through it.", alias);
+        }
         if result.added > 0 || result.updated > 0 || result.removed > 0 {
             any_changes = true;
         }
     }
 
+    if any_changes && diff && (dry_run || any_hard_failures) {
+        print_diff(&ssh_config::diff::config_diff(&before, &config));
+    }
     if any_changes && !dry_run {
         if any_hard_failures {
             eprintln!("! Skipping config write due to sync failures. Fix the errors and re-run.");
-        } else {
+        } else if !diff || confirm_diff(&before, &config) {
             config.write()?;
         }
     }
@@ -879,6 +1208,7 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
             verify_tls,
             auto_sync,
             no_auto_sync,
+            mut template,
         } => {
             let p = match providers::get_provider(&provider) {
                 Some(p) => p,
@@ -933,6 +1263,9 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
                 if key.is_none() && !existing.identity_file.is_empty() {
                     key = Some(existing.identity_file.clone());
                 }
+                if template.is_none() && !existing.template.is_empty() {
+                    template = Some(existing.template.clone());
+                }
                 // Preserve verify_tls=false unless the user explicitly overrides it either way
                 if !no_verify_tls && !verify_tls && !existing.verify_tls {
                     no_verify_tls = true;
@@ -976,6 +1309,7 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
 
             let user = user.unwrap_or_else(|| "root".to_string());
             let identity_file = key.unwrap_or_default();
+            let template = template.unwrap_or_default();
 
             // Reject control characters in all fields (prevents INI injection)
             let url_value = url.clone().unwrap_or_default();
@@ -985,6 +1319,7 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
                 (&alias_prefix, "Alias prefix"),
                 (&user, "User"),
                 (&identity_file, "Identity file"),
+                (&template, "Template"),
             ] {
                 if value.chars().any(|c| c.is_control()) {
                     eprintln!("{} contains control characters.", name);
@@ -1016,6 +1351,7 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
                 url: url.unwrap_or_default(),
                 verify_tls: !no_verify_tls,
                 auto_sync: resolved_auto_sync,
+                template,
             };
 
             let mut config = providers::config::ProviderConfig::load();
@@ -1058,6 +1394,191 @@ fn handle_provider_command(command: ProviderCommands) -> Result<()> {
     }
 }
 
+fn handle_show(config: &SshConfigFile, alias: &str, key: Option<&str>) -> Result<()> {
+    if !config.has_host(alias) {
+        eprintln!("No host '{}' found.", alias);
+        std::process::exit(1);
+    }
+    let resolved = config.resolve_host(alias);
+    let values = match key {
+        Some(key) => {
+            let values = resolved.get_all(key);
+            if values.is_empty() {
+                eprintln!("{} is not set for {}.", key, alias);
+                std::process::exit(1);
+            }
+            values
+        }
+        None => resolved.values.iter().collect(),
+    };
+    let key_width = values.iter().map(|v| v.key.len()).max().unwrap_or(0);
+    let value_width = values
+        .iter()
+        .map(|v| v.value.chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(40);
+    for v in &values {
+        let source = match &v.source {
+            ssh_config::resolve::ValueSource::Block { header, file, .. } => {
+                format!("{} ({})", header, file.display())
+            }
+            ssh_config::resolve::ValueSource::Default => "default".to_string(),
+        };
+        println!(
+            "{:<kw$}  {:<vw$}  # {}",
+            v.key,
+            v.value,
+            source,
+            kw = key_width,
+            vw = value_width
+        );
+    }
+    for header in &resolved.unevaluated {
+        println!("# skipped: {} (not evaluated)", header);
+    }
+    Ok(())
+}
+
+fn handle_lint(config: &SshConfigFile, format: OutputFormat) -> Result<()> {
+    let findings = config.lint();
+    match format {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+        }
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!(
+                    "{}: {}: {}",
+                    finding.file.display(),
+                    finding.block,
+                    finding.message
+                );
+            }
+            if findings.is_empty() {
+                println!("No problems found.");
+            } else {
+                let noun = if findings.len() == 1 { "problem" } else { "problems" };
+                eprintln!("{} {} found.", findings.len(), noun);
+            }
+        }
+    }
+    if !findings.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Print a pending config change (--diff).
+fn print_diff(lines: &[String]) {
+    for line in lines {
+        println!("{}", line);
+    }
+}
+
+/// Show what `after` would write and ask before writing (--diff).
+/// Anything but y/yes, including EOF, declines.
+fn confirm_diff(before: &SshConfigFile, after: &SshConfigFile) -> bool {
+    let lines = ssh_config::diff::config_diff(before, after);
+    if lines.is_empty() {
+        println!("No changes to write.");
+        return false;
+    }
+    print_diff(&lines);
+    print!("\nWrite these changes? [y/N] ");
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    let confirmed = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !confirmed {
+        println!("Nothing written.");
+    }
+    confirmed
+}
+
+fn handle_backup_command(config_path: &std::path::Path, command: BackupCommands) -> Result<()> {
+    use ssh_config::{backup, diff};
+
+    // Include files are only known when the config parses
+    let includes = SshConfigFile::parse(config_path)
+        .map(|config| config.included_paths())
+        .unwrap_or_default();
+    let find = |id: &str| match backup::find(config_path, &includes, id) {
+        Some(b) => b,
+        None => {
+            eprintln!("No backup '{}' found. Run 'purple backup list' to see them.", id);
+            std::process::exit(1);
+        }
+    };
+    match command {
+        BackupCommands::List => {
+            let backups = backup::list(config_path, &includes);
+            if backups.is_empty() {
+                println!("No backups of {} yet.", config_path.display());
+                return Ok(());
+            }
+            println!("{:<15} {:<24} {:<9} HOSTS", "ID", "CREATED", "AGE");
+            for b in &backups {
+                let mut changes = backup::compare(b)
+                    .map(|c| c.changes.summary())
+                    .unwrap_or_else(|_| "unreadable".to_string());
+                if b.file != config_path {
+                    changes.push_str(&format!("  ({})", b.file.display()));
+                }
+                println!(
+                    "{:<15} {:<24} {:<9} {}",
+                    b.id,
+                    backup::format_utc(b.created),
+                    history::ConnectionHistory::format_time_ago(b.created),
+                    changes
+                );
+            }
+            println!(
+                "\nKeeping the last {}. Change with backup_retention in ~/.purple/preferences.",
+                SshConfigFile::backup_retention()
+            );
+        }
+        BackupCommands::Diff { id } => {
+            let b = find(&id);
+            let comparison = backup::compare(&b)?;
+            println!("Changes since backup {} ({}):", b.id, backup::format_utc(b.created));
+            let changes = &comparison.changes;
+            if changes.is_empty() {
+                println!("  No host changes.");
+            }
+            for alias in &changes.added {
+                println!("  + {}", alias);
+            }
+            for alias in &changes.removed {
+                println!("  - {}", alias);
+            }
+            for alias in &changes.changed {
+                println!("  ~ {}", alias);
+            }
+            let lines = diff::unified(&comparison.diff, 3);
+            if !lines.is_empty() {
+                println!();
+                for line in &lines {
+                    println!("{}", line);
+                }
+            }
+        }
+        BackupCommands::Restore { id } => {
+            let b = find(&id);
+            backup::restore(&b, config_path)?;
+            println!(
+                "Restored backup {} ({}) to {}. The previous version was backed up first.",
+                b.id,
+                backup::format_utc(b.created),
+                b.file.display()
+            );
+        }
+    }
+    Ok(())
+}
+
 fn handle_tunnel_command(mut config: SshConfigFile, command: TunnelCommands) -> Result<()> {
     match command {
         TunnelCommands::List { alias } => {
@@ -1096,15 +1617,15 @@ fn handle_tunnel_command(mut config: SshConfigFile, command: TunnelCommands) ->
             }
             Ok(())
         }
-        TunnelCommands::Add { alias, forward } => {
+        TunnelCommands::Add {
+            alias,
+            forward,
+            diff,
+        } => {
             if !config.has_host(&alias) {
                 eprintln!("No host '{}' found.", alias);
                 std::process::exit(1);
             }
-            if config.is_included_host(&alias) {
-                eprintln!("Host '{}' is from an included file and cannot be modified.", alias);
-                std::process::exit(1);
-            }
             let rule = tunnel::TunnelRule::from_cli_spec(&forward).unwrap_or_else(|e| {
                 eprintln!("{}", e);
                 std::process::exit(1);
@@ -1116,7 +1637,11 @@ fn handle_tunnel_command(mut config: SshConfigFile, command: TunnelCommands) ->
                 eprintln!("Forward {} already exists on {}.", forward, alias);
                 std::process::exit(1);
             }
+            let before = config.clone();
             config.add_forward(&alias, key, &value);
+            if diff && !confirm_diff(&before, &config) {
+                return Ok(());
+            }
             if let Err(e) = config.write() {
                 eprintln!("Failed to save config: {}", e);
                 std::process::exit(1);
@@ -1124,26 +1649,30 @@ fn handle_tunnel_command(mut config: SshConfigFile, command: TunnelCommands) ->
             println!("Added {} to {}.", forward, alias);
             Ok(())
         }
-        TunnelCommands::Remove { alias, forward } => {
+        TunnelCommands::Remove {
+            alias,
+            forward,
+            diff,
+        } => {
             if !config.has_host(&alias) {
                 eprintln!("No host '{}' found.", alias);
                 std::process::exit(1);
             }
-            if config.is_included_host(&alias) {
-                eprintln!("Host '{}' is from an included file and cannot be modified.", alias);
-                std::process::exit(1);
-            }
             let rule = tunnel::TunnelRule::from_cli_spec(&forward).unwrap_or_else(|e| {
                 eprintln!("{}", e);
                 std::process::exit(1);
             });
             let key = rule.tunnel_type.directive_key();
             let value = rule.to_directive_value();
+            let before = config.clone();
             let removed = config.remove_forward(&alias, key, &value);
             if !removed {
                 eprintln!("No matching forward {} found on {}.", forward, alias);
                 std::process::exit(1);
             }
+            if diff && !confirm_diff(&before, &config) {
+                return Ok(());
+            }
             if let Err(e) = config.write() {
                 eprintln!("Failed to save config: {}", e);
                 std::process::exit(1);
@@ -1190,10 +1719,9 @@ fn prompt_hidden_input(prompt: &str) -> Result<Option<String>> {
                     input.push(c);
                     eprint!("*");
                 }
-                crossterm::event::KeyCode::Backspace => {
-                    if input.pop().is_some() {
-                        eprint!("\x08 \x08");
-                    }
+                crossterm::event::KeyCode::Backspace if !input.is_empty() => {
+                    input.pop();
+                    eprint!("\x08 \x08");
                 }
                 crossterm::event::KeyCode::Esc => {
                     crossterm::terminal::disable_raw_mode()?;
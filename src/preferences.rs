@@ -128,6 +128,30 @@ pub fn save_askpass_default(source: &str) -> io::Result<()> {
     save_value("askpass", source)
 }
 
+/// Load backup retention (backups kept per config file) from ~/.purple/preferences.
+/// Returns 5 if missing or invalid.
+pub fn load_backup_retention() -> usize {
+    load_value("backup_retention")
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(5)
+}
+
+/// Save backup retention to ~/.purple/preferences.
+pub fn save_backup_retention(keep: usize) -> io::Result<()> {
+    save_value("backup_retention", &keep.to_string())
+}
+
+/// Load confirm_writes from ~/.purple/preferences. Returns false if missing or invalid.
+pub fn load_confirm_writes() -> bool {
+    load_value("confirm_writes").is_some_and(|v| v == "true")
+}
+
+/// Save confirm_writes to ~/.purple/preferences.
+pub fn save_confirm_writes(enabled: bool) -> io::Result<()> {
+    save_value("confirm_writes", &enabled.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     // We test load_value/save_value logic by replicating the parsing inline,
@@ -14,6 +14,8 @@ pub struct ProviderSection {
     pub url: String,
     pub verify_tls: bool,
     pub auto_sync: bool,
+    /// Host template applied to hosts this provider adds. Empty for none.
+    pub template: String,
 }
 
 /// Default for auto_sync: false for proxmox (N+1 API calls), true for all others.
@@ -88,6 +90,7 @@ impl ProviderConfig {
                     url: String::new(),
                     verify_tls: true,
                     auto_sync: auto_sync_default,
+                    template: String::new(),
                 });
             } else if let Some(ref mut section) = current {
                 if let Some((key, value)) = trimmed.split_once('=') {
@@ -105,6 +108,7 @@ impl ProviderConfig {
                         "auto_sync" => section.auto_sync = !matches!(
                             value.to_lowercase().as_str(), "false" | "0" | "no"
                         ),
+                        "template" => section.template = value,
                         _ => {}
                     }
                 }
@@ -155,6 +159,9 @@ impl ProviderConfig {
             if section.auto_sync != default_auto_sync(&section.provider) {
                 content.push_str(if section.auto_sync { "auto_sync=true\n" } else { "auto_sync=false\n" });
             }
+            if !section.template.is_empty() {
+                content.push_str(&format!("template={}\n", section.template));
+            }
         }
 
         fs_util::atomic_write(&path, content.as_bytes())
@@ -257,6 +264,7 @@ token=mytoken
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         });
         assert_eq!(config.sections.len(), 1);
     }
@@ -273,6 +281,7 @@ token=mytoken
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         });
         assert_eq!(config.sections.len(), 1);
         assert_eq!(config.sections[0].token, "new");
@@ -406,6 +415,7 @@ verify_tls=false
             url: String::new(),     // empty: not written
             verify_tls: true,       // default: not written
             auto_sync: true,        // default for non-proxmox: not written
+            template: String::new(),
         };
         let mut config = ProviderConfig::default();
         config.set_section(section);
@@ -415,6 +425,23 @@ verify_tls=false
         assert!(s.verify_tls);
     }
 
+    #[test]
+    fn test_template_parsed_and_saved() {
+        let config = ProviderConfig::parse("[hetzner]\ntoken=abc\ntemplate=cloud\n");
+        assert_eq!(config.sections[0].template, "cloud");
+
+        let dir = std::env::temp_dir().join(format!("purple_test_provider_template_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("providers");
+        let config = ProviderConfig { path_override: Some(path.clone()), ..config };
+        config.save().unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("template=cloud\n"));
+        assert!(ProviderConfig::parse("[hetzner]\ntoken=abc\n").sections[0].template.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_proxmox_url_fallback_in_section() {
         // Simulates the update path: existing section has url, new section should preserve it
@@ -434,6 +461,7 @@ verify_tls=false
             url: existing_url,
             verify_tls: true,
             auto_sync: false,
+            template: String::new(),
         });
         assert_eq!(config.sections[0].token, "new");
         assert_eq!(config.sections[0].url, "https://pve.local:8006");
@@ -476,6 +504,7 @@ verify_tls=false
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         });
         // Re-parse: auto_sync should still be true (default)
         assert!(config.sections[0].auto_sync);
@@ -491,6 +520,7 @@ verify_tls=false
             url: "https://pve:8006".to_string(),
             verify_tls: true,
             auto_sync: false,
+            template: String::new(),
         });
         assert!(!config2.sections[0].auto_sync);
     }
@@ -534,6 +564,7 @@ verify_tls=false
             url: "https://pve:8006".to_string(),
             verify_tls: true,
             auto_sync: true, // non-default for proxmox
+            template: String::new(),
         });
         // Simulate save by rebuilding content string (same logic as save())
         let content =
@@ -759,6 +790,7 @@ verify_tls=false
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
         config.set_section(section);
         assert_eq!(config.sections.len(), 1);
@@ -778,6 +810,7 @@ verify_tls=false
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
         config.set_section(section);
         assert_eq!(config.sections.len(), 1);
@@ -1037,6 +1070,7 @@ verify_tls=false
                 url: String::new(),
                 verify_tls: true,
                 auto_sync: true,
+                template: String::new(),
             });
         }
         assert_eq!(config.sections.len(), 3);
@@ -283,6 +283,7 @@ mod tests {
             url: "https://pve.example.com:8006".to_string(),
             verify_tls: false,
             auto_sync: false,
+            template: String::new(),
         };
         let p = get_provider_with_config("proxmox", &section).unwrap();
         assert_eq!(p.name(), "proxmox");
@@ -299,6 +300,7 @@ mod tests {
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
         let p = get_provider_with_config("digitalocean", &section).unwrap();
         assert_eq!(p.name(), "digitalocean");
@@ -315,6 +317,7 @@ mod tests {
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
         assert!(get_provider_with_config("aws", &section).is_none());
     }
@@ -543,6 +546,7 @@ mod tests {
                 },
                 verify_tls: true,
                 auto_sync: true,
+                template: String::new(),
             };
             let p = get_provider_with_config(name, &section);
             assert!(p.is_some(), "get_provider_with_config({}) should return Some", name);
@@ -1,6 +1,7 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::ssh_config::model::{ConfigElement, HostEntry, SshConfigFile};
+use crate::ssh_config::template;
 
 use super::config::ProviderSection;
 use super::{Provider, ProviderHost};
@@ -14,6 +15,8 @@ pub struct SyncResult {
     pub unchanged: usize,
     /// Alias renames: (old_alias, new_alias) pairs.
     pub renames: Vec<(String, String)>,
+    /// Hosts gone from the provider but kept because other hosts jump through them.
+    pub kept: Vec<String>,
 }
 
 /// Sanitize a server name into a valid SSH alias component.
@@ -80,8 +83,9 @@ pub fn sync_provider_with_options(
     reset_tags: bool,
 ) -> SyncResult {
     let mut result = SyncResult::default();
+    config.refresh_index();
 
-    // Build map of server_id -> alias (top-level only, no Include files).
+    // Build map of server_id -> alias (including hosts in Include files).
     // Keep first occurrence if duplicate provider markers exist (e.g. manual copy).
     let existing = config.find_hosts_by_provider(provider.name());
     let mut existing_map: HashMap<String, String> = HashMap::new();
@@ -104,6 +108,14 @@ pub fn sync_provider_with_options(
     // Only add group header if this provider has no existing hosts in config
     let mut needs_header = !dry_run && existing_map.is_empty();
 
+    // Template for new hosts (an unknown name adds plain hosts)
+    let templates = if section.template.is_empty() {
+        Vec::new()
+    } else {
+        template::load_all(config)
+    };
+    let host_template = template::find(&templates, &section.template);
+
     for remote in remote_hosts {
         if !remote_ids.insert(remote.server_id.clone()) {
             continue; // Skip duplicate server_id in same response
@@ -120,14 +132,8 @@ pub fn sync_provider_with_options(
         }
 
         if let Some(existing_alias) = existing_map.get(&remote.server_id) {
-            // Host exists, check if alias, IP or tags changed
-            if let Some(entry) = entries_map.get(existing_alias) {
-                // Included hosts are read-only; recognize them for dedup but skip mutations
-                if entry.source_file.is_some() {
-                    result.unchanged += 1;
-                    continue;
-                }
-
+            // Host exists, check if alias, IP or tags changed (disabled hosts stay as parked)
+            if let Some(entry) = entries_map.get(existing_alias).filter(|e| !e.disabled) {
                 // Check if alias prefix changed (e.g. "do" → "ocean")
                 let sanitized = sanitize_name(&remote.name);
                 let expected_alias = build_alias(&section.alias_prefix, &sanitized);
@@ -232,28 +238,36 @@ pub fn sync_provider_with_options(
                 let wrote_header = needs_header;
                 if needs_header {
                     if !config.elements.is_empty() && !config.last_element_has_trailing_blank() {
-                        config
-                            .elements
-                            .push(ConfigElement::GlobalLine(String::new()));
+                        config.push_element(ConfigElement::GlobalLine(String::new()));
                     }
-                    config
-                        .elements
-                        .push(ConfigElement::GlobalLine(format!(
-                            "# purple:group {}",
-                            super::provider_display_name(provider.name())
-                        )));
+                    config.push_element(ConfigElement::GlobalLine(format!(
+                        "# purple:group {}",
+                        super::provider_display_name(provider.name())
+                    )));
                     needs_header = false;
                 }
 
-                let entry = HostEntry {
+                let mut entry = HostEntry {
                     alias: alias.clone(),
                     hostname: remote.ip.clone(),
                     user: section.user.clone(),
-                    identity_file: section.identity_file.clone(),
+                    identity_files: if section.identity_file.is_empty() {
+                        Vec::new()
+                    } else {
+                        vec![section.identity_file.clone()]
+                    },
                     tags: remote.tags.clone(),
                     provider: Some(provider.name().to_string()),
                     ..Default::default()
                 };
+                // The template's user beats the section default; the section key wins
+                if let Some(host_template) = host_template {
+                    let defaults = host_template.entry();
+                    if !defaults.user.is_empty() {
+                        entry.user = defaults.user;
+                    }
+                    host_template.fill(&mut entry);
+                }
 
                 // Add blank line separator before host (skip when preceded by group header
                 // so the header stays adjacent to the first host)
@@ -261,57 +275,60 @@ pub fn sync_provider_with_options(
                     && !config.elements.is_empty()
                     && !config.last_element_has_trailing_blank()
                 {
-                    config
-                        .elements
-                        .push(ConfigElement::GlobalLine(String::new()));
+                    config.push_element(ConfigElement::GlobalLine(String::new()));
                 }
 
                 let block = SshConfigFile::entry_to_block(&entry);
-                config.elements.push(ConfigElement::HostBlock(block));
+                config.push_element(ConfigElement::HostBlock(block));
                 config.set_host_provider(&alias, provider.name(), &remote.server_id);
                 if !remote.tags.is_empty() {
                     config.set_host_tags(&alias, &remote.tags);
                 }
+                if let Some(host_template) = host_template {
+                    config.apply_template(&alias, host_template);
+                }
             }
 
             result.added += 1;
         }
     }
 
-    // Remove deleted hosts (skip included hosts which are read-only)
-    if remove_deleted && !dry_run {
-        let to_remove: Vec<String> = existing_map
+    // Remove deleted hosts (from the main config or whichever Include file holds them).
+    // Jump hosts that other hosts still go through are kept.
+    if remove_deleted {
+        let gone: HashSet<&str> = existing_map
             .iter()
             .filter(|(id, _)| !remote_ids.contains(id.as_str()))
-            .filter(|(_, alias)| {
-                entries_map
-                    .get(alias.as_str())
-                    .is_none_or(|e| e.source_file.is_none())
-            })
-            .map(|(_, alias)| alias.clone())
+            .map(|(_, alias)| alias.as_str())
+            .filter(|alias| !entries_map.get(*alias).is_some_and(|e| e.disabled))
             .collect();
-        for alias in &to_remove {
-            config.delete_host(alias);
+        let graph = config.jump_graph();
+        let mut to_remove: Vec<String> = Vec::new();
+        for alias in existing.iter().map(|(alias, _)| alias.as_str()) {
+            if !gone.contains(alias) || to_remove.iter().any(|a| a == alias) {
+                continue;
+            }
+            if graph.behind(alias).iter().any(|h| !gone.contains(h)) {
+                result.kept.push(alias.to_string());
+            } else {
+                to_remove.push(alias.to_string());
+            }
         }
         result.removed = to_remove.len();
 
-        // Clean up orphan provider header if all hosts for this provider were removed
-        if config.find_hosts_by_provider(provider.name()).is_empty() {
-            let header_text = format!("# purple:group {}", super::provider_display_name(provider.name()));
-            config
-                .elements
-                .retain(|e| !matches!(e, ConfigElement::GlobalLine(line) if line == &header_text));
+        if !dry_run {
+            for alias in &to_remove {
+                config.delete_host(alias);
+            }
+            // Clean up orphan provider header if all hosts for this provider were removed
+            if config.find_hosts_by_provider(provider.name()).is_empty() {
+                let header_text = format!("# purple:group {}", super::provider_display_name(provider.name()));
+                config
+                    .elements
+                    .retain(|e| !matches!(e, ConfigElement::GlobalLine(line) if line == &header_text));
+                config.invalidate_index();
+            }
         }
-    } else if remove_deleted {
-        result.removed = existing_map
-            .iter()
-            .filter(|(id, _)| !remote_ids.contains(id.as_str()))
-            .filter(|(_, alias)| {
-                entries_map
-                    .get(alias.as_str())
-                    .is_none_or(|e| e.source_file.is_none())
-            })
-            .count();
     }
 
     result
@@ -327,6 +344,7 @@ mod tests {
             elements: Vec::new(),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         }
     }
 
@@ -340,6 +358,7 @@ mod tests {
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         }
     }
 
@@ -480,6 +499,88 @@ mod tests {
         assert_eq!(config.host_entries().len(), 0);
     }
 
+    #[test]
+    fn test_sync_keeps_notes_on_update_and_rename() {
+        let mut config = empty_config();
+        let section = make_section();
+        let mut remote = vec![ProviderHost {
+            server_id: "123".to_string(),
+            name: "web-1".to_string(),
+            ip: "1.2.3.4".to_string(),
+            tags: Vec::new(),
+        }];
+        sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        let notes = vec!["owner: platform".to_string(), String::new(), "runbook: https://wiki/web".to_string()];
+        config.set_host_notes("do-web-1", &notes);
+
+        remote[0].name = "web-2".to_string();
+        remote[0].ip = "5.6.7.8".to_string();
+        remote[0].tags = vec!["prod".to_string()];
+        let result = sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        assert_eq!(result.updated, 1);
+        let entry = &config.host_entries()[0];
+        assert_eq!(entry.alias, "do-web-2");
+        assert_eq!(entry.notes, notes);
+    }
+
+    #[test]
+    fn test_sync_applies_section_template_to_new_hosts() {
+        let mut config = SshConfigFile {
+            elements: SshConfigFile::parse_content(
+                "# purple:template\n# Host cloud\n#   User ubuntu\n#   Port 2222\n#   IdentityFile ~/.ssh/cloud\n#   ServerAliveInterval 30\n#   # purple:tags cloud\n",
+            ),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        };
+        let mut section = make_section();
+        section.template = "cloud".to_string();
+        let remote = vec![ProviderHost {
+            server_id: "1".to_string(),
+            name: "web".to_string(),
+            ip: "1.2.3.4".to_string(),
+            tags: vec!["prod".to_string()],
+        }];
+        let result = sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        assert_eq!(result.added, 1);
+        let entries = config.host_entries();
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!((entry.user.as_str(), entry.port), ("ubuntu", 2222));
+        assert_eq!(entry.identity_files, vec!["~/.ssh/cloud"]);
+        assert_eq!(entry.tags, vec!["prod", "cloud"]);
+        assert_eq!(entry.provider.as_deref(), Some("digitalocean"));
+        assert_eq!(
+            config.host_extra_directives("do-web"),
+            vec![("ServerAliveInterval".to_string(), "30".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_sync_leaves_disabled_host_alone() {
+        let mut config = empty_config();
+        let section = make_section();
+        let mut remote = vec![ProviderHost {
+            server_id: "123".to_string(),
+            name: "web-1".to_string(),
+            ip: "1.2.3.4".to_string(),
+            tags: Vec::new(),
+        }];
+        sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        assert!(config.disable_host("do-web-1"));
+
+        // Not re-added, not updated, not removed
+        remote[0].ip = "5.6.7.8".to_string();
+        let result = sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        assert_eq!((result.added, result.updated), (0, 0));
+        let result = sync_provider(&mut config, &MockProvider, &[], &section, true, false);
+        assert_eq!(result.removed, 0);
+        let entries = config.host_entries();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].disabled);
+        assert_eq!(entries[0].hostname, "1.2.3.4");
+    }
+
     #[test]
     fn test_sync_dry_run_no_mutations() {
         let mut config = empty_config();
@@ -538,6 +639,7 @@ Host do-web-1-copy
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
         let section = make_section();
 
@@ -773,19 +875,21 @@ Host do-web-1-copy
                 resolved_files: vec![IncludedFile {
                     path: PathBuf::from("/tmp/included.conf"),
                     elements: included_elements,
+                    crlf: false,
                 }],
             })],
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         }
     }
 
     #[test]
-    fn test_sync_include_host_skips_update() {
+    fn test_sync_include_host_updated_in_place() {
         let mut config = config_with_include_provider_host();
         let section = make_section();
 
-        // Remote has same server with different IP — should NOT update included host
+        // Remote has same server with different IP — update the included host
         let remote = vec![ProviderHost {
             server_id: "inc1".to_string(),
             name: "included".to_string(),
@@ -793,25 +897,36 @@ Host do-web-1-copy
             tags: Vec::new(),
         }];
         let result = sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
-        assert_eq!(result.unchanged, 1);
-        assert_eq!(result.updated, 0);
+        assert_eq!(result.unchanged, 0);
+        assert_eq!(result.updated, 1);
         assert_eq!(result.added, 0);
 
-        // Verify IP was NOT changed
+        // IP changed inside the included file, nothing added to the main config
         let entries = config.host_entries();
+        assert_eq!(entries.len(), 1);
         let included = entries.iter().find(|e| e.alias == "do-included").unwrap();
-        assert_eq!(included.hostname, "1.2.3.4");
+        assert_eq!(included.hostname, "9.9.9.9");
+        assert_eq!(
+            included.source_file.as_deref(),
+            Some(std::path::Path::new("/tmp/included.conf"))
+        );
+        assert_eq!(config.elements.len(), 1);
     }
 
     #[test]
-    fn test_sync_include_host_skips_remove() {
+    fn test_sync_include_host_removed() {
         let mut config = config_with_include_provider_host();
         let section = make_section();
 
-        // Empty remote + remove_deleted — should NOT remove included host
-        let result = sync_provider(&mut config, &MockProvider, &[], &section, true, false);
-        assert_eq!(result.removed, 0);
+        // Dry run counts the included host
+        let result = sync_provider(&mut config, &MockProvider, &[], &section, true, true);
+        assert_eq!(result.removed, 1);
         assert_eq!(config.host_entries().len(), 1);
+
+        // Empty remote + remove_deleted removes it from the included file
+        let result = sync_provider(&mut config, &MockProvider, &[], &section, true, false);
+        assert_eq!(result.removed, 1);
+        assert!(config.host_entries().is_empty());
     }
 
     #[test]
@@ -877,6 +992,7 @@ Host do-web-1-copy
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
         let section = make_section();
 
@@ -1011,6 +1127,7 @@ Host do-web-1-copy
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
         let section = make_section();
 
@@ -1026,7 +1143,7 @@ Host do-web-1-copy
     }
 
     #[test]
-    fn test_sync_rename_skips_included_host() {
+    fn test_sync_rename_included_host() {
         let mut config = config_with_include_provider_host();
 
         let new_section = ProviderSection {
@@ -1038,6 +1155,7 @@ Host do-web-1-copy
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
 
         // Remote has the included host's server_id with a different prefix
@@ -1048,11 +1166,18 @@ Host do-web-1-copy
             tags: Vec::new(),
         }];
         let result = sync_provider(&mut config, &MockProvider, &remote, &new_section, false, false);
-        assert_eq!(result.unchanged, 1);
-        assert_eq!(result.updated, 0);
+        assert_eq!(result.updated, 1);
+        assert_eq!(result.renames, vec![("do-included".to_string(), "ocean-included".to_string())]);
 
-        // Alias should remain unchanged (included hosts are read-only)
-        assert_eq!(config.host_entries()[0].alias, "do-included");
+        // Renamed in place, provider marker follows the new alias
+        let entries = config.host_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].alias, "ocean-included");
+        assert!(entries[0].source_file.is_some());
+        assert_eq!(
+            config.find_hosts_by_provider("digitalocean"),
+            vec![("ocean-included".to_string(), "inc1".to_string())]
+        );
     }
 
     #[test]
@@ -1418,6 +1543,40 @@ Host do-web-1-copy
         assert_eq!(config.host_entries()[0].alias, "do-web");
     }
 
+    #[test]
+    fn test_sync_remove_keeps_jump_host_in_use() {
+        let mut config = empty_config();
+        let section = make_section();
+        let remote = vec![
+            ProviderHost {
+                server_id: "100".to_string(),
+                name: "bastion".to_string(),
+                ip: "1.2.3.4".to_string(),
+                tags: Vec::new(),
+            },
+            ProviderHost {
+                server_id: "200".to_string(),
+                name: "worker".to_string(),
+                ip: "5.6.7.8".to_string(),
+                tags: Vec::new(),
+            },
+        ];
+        sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
+        config.add_host(&HostEntry {
+            alias: "app".to_string(),
+            hostname: "10.0.0.5".to_string(),
+            proxy_jump: "do-bastion".to_string(),
+            ..Default::default()
+        });
+
+        // Both servers are gone. app still jumps through the bastion.
+        let result = sync_provider(&mut config, &MockProvider, &[], &section, true, false);
+        assert_eq!(result.removed, 1);
+        assert_eq!(result.kept, vec!["do-bastion"]);
+        let aliases: Vec<String> = config.host_entries().into_iter().map(|h| h.alias).collect();
+        assert_eq!(aliases, vec!["do-bastion", "app"]);
+    }
+
     #[test]
     fn test_sync_mixed_resolved_empty_and_missing() {
         let mut config = empty_config();
@@ -1569,7 +1728,7 @@ Host do-web-1-copy
         }];
         sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
         let entries = config.host_entries();
-        assert_eq!(entries[0].identity_file, "~/.ssh/id_rsa");
+        assert_eq!(entries[0].identity_files, vec!["~/.ssh/id_rsa"]);
     }
 
     #[test]
@@ -1917,7 +2076,7 @@ Host do-web-1-copy
         }];
         sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
         let entries = config.host_entries();
-        assert_eq!(entries[0].identity_file, "~/.ssh/do_key");
+        assert_eq!(entries[0].identity_files, vec!["~/.ssh/do_key"]);
     }
 
     // =========================================================================
@@ -2153,6 +2312,7 @@ Host do-web-1-copy
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
 
         // Sync DO hosts
@@ -2193,6 +2353,7 @@ Host do-web-1-copy
             url: String::new(),
             verify_tls: true,
             auto_sync: true,
+            template: String::new(),
         };
 
         // Add hosts from both providers
@@ -2468,6 +2629,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
         let section = make_section();
         let remote = vec![ProviderHost {
@@ -2622,10 +2784,12 @@ Host do-web
                 resolved_files: vec![IncludedFile {
                     path: PathBuf::from("/tmp/included.conf"),
                     elements: included_elements,
+                    crlf: false,
                 }],
             })],
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let hosts = config.find_hosts_by_provider("digitalocean");
@@ -2653,6 +2817,7 @@ Host do-web
             resolved_files: vec![IncludedFile {
                 path: PathBuf::from("/tmp/included.conf"),
                 elements: inc_elements,
+                crlf: false,
             }],
         }));
 
@@ -2660,6 +2825,7 @@ Host do-web
             elements,
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let hosts = config.find_hosts_by_provider("digitalocean");
@@ -2677,10 +2843,12 @@ Host do-web
                 resolved_files: vec![IncludedFile {
                     path: PathBuf::from("/tmp/empty.conf"),
                     elements: vec![],
+                    crlf: false,
                 }],
             })],
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let hosts = config.find_hosts_by_provider("digitalocean");
@@ -2694,6 +2862,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let hosts = config.find_hosts_by_provider("vultr");
@@ -2712,6 +2881,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let alias = config.deduplicate_alias_excluding("do-web", Some("do-web"));
@@ -2726,6 +2896,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let alias = config.deduplicate_alias_excluding("do-web", Some("do-db"));
@@ -2740,6 +2911,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let alias = config.deduplicate_alias_excluding("do-web", Some("do-web"));
@@ -2754,6 +2926,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         // None exclude means normal deduplication
@@ -2772,6 +2945,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         config.set_host_tags("do-web", &[]);
@@ -2786,6 +2960,7 @@ Host do-web
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         config.set_host_provider("do-web", "digitalocean", "new-id");
@@ -2795,7 +2970,7 @@ Host do-web
     }
 
     // =========================================================================
-    // Sync with provider hosts in includes (read-only recognized)
+    // Sync with provider hosts in includes
     // =========================================================================
 
     #[test]
@@ -2812,10 +2987,12 @@ Host do-web
                 resolved_files: vec![IncludedFile {
                     path: PathBuf::from("/tmp/included.conf"),
                     elements: included_elements,
+                    crlf: false,
                 }],
             })],
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         let section = make_section();
@@ -2931,7 +3108,7 @@ Host do-web
     // =========================================================================
 
     #[test]
-    fn test_sync_dry_run_remove_excludes_included_hosts() {
+    fn test_sync_dry_run_remove_counts_included_hosts() {
         use crate::ssh_config::model::{IncludeDirective, IncludedFile};
 
         let include_content =
@@ -2946,10 +3123,12 @@ Host do-web
                 resolved_files: vec![IncludedFile {
                     path: PathBuf::from("/tmp/included.conf"),
                     elements: included_elements,
+                    crlf: false,
                 }],
             })],
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         };
 
         // Add a non-included host
@@ -2963,9 +3142,9 @@ Host do-web
         sync_provider(&mut config, &MockProvider, &remote, &section, false, false);
 
         // Dry-run with empty remote (both hosts would be "deleted")
-        // Only the top-level host should be counted, NOT the included one
+        // The included host counts too, it is removed from its own file
         let result = sync_provider(&mut config, &MockProvider, &[], &section, true, true);
-        assert_eq!(result.removed, 1, "Only top-level host counted in dry-run remove");
+        assert_eq!(result.removed, 2, "Top-level and included host counted in dry-run remove");
     }
 
     // =========================================================================
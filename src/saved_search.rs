@@ -0,0 +1,154 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::fs_util;
+
+/// A search query stored under a name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+}
+
+/// Saved searches from ~/.purple/searches, one `name=query` per line.
+#[derive(Debug, Clone, Default)]
+pub struct SavedSearches {
+    pub entries: Vec<SavedSearch>,
+    path: Option<PathBuf>,
+}
+
+impl SavedSearches {
+    /// Load saved searches from ~/.purple/searches. Missing file means none.
+    pub fn load() -> Self {
+        match Self::searches_path() {
+            Some(path) => Self::load_from(path),
+            None => Self::default(),
+        }
+    }
+
+    /// Load saved searches from a specific file.
+    pub fn load_from(path: PathBuf) -> Self {
+        let content = std::fs::read_to_string(&path).unwrap_or_default();
+        let mut entries: Vec<SavedSearch> = Vec::new();
+        for line in content.lines() {
+            let line = line.trim();
+            if line.starts_with('#') || line.is_empty() {
+                continue;
+            }
+            if let Some((name, query)) = line.split_once('=') {
+                let (name, query) = (name.trim(), query.trim());
+                if !name.is_empty() && !query.is_empty() {
+                    entries.push(SavedSearch {
+                        name: name.to_string(),
+                        query: query.to_string(),
+                    });
+                }
+            }
+        }
+        Self {
+            entries,
+            path: Some(path),
+        }
+    }
+
+    /// Find a saved search by name (case-insensitive).
+    pub fn get(&self, name: &str) -> Option<&SavedSearch> {
+        self.entries.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Add a saved search or replace the query of one with the same name.
+    /// Returns true when an existing entry was replaced.
+    pub fn set(&mut self, name: &str, query: &str) -> bool {
+        if let Some(existing) = self
+            .entries
+            .iter_mut()
+            .find(|s| s.name.eq_ignore_ascii_case(name))
+        {
+            existing.query = query.to_string();
+            return true;
+        }
+        self.entries.push(SavedSearch {
+            name: name.to_string(),
+            query: query.to_string(),
+        });
+        false
+    }
+
+    /// Remove a saved search by position.
+    pub fn remove(&mut self, index: usize) -> Option<SavedSearch> {
+        (index < self.entries.len()).then(|| self.entries.remove(index))
+    }
+
+    /// Write all saved searches back to disk.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut content = String::new();
+        for search in &self.entries {
+            content.push_str(&format!("{}={}\n", search.name, search.query));
+        }
+        fs_util::atomic_write(path, content.as_bytes())
+    }
+
+    /// Check a name before saving. Errors are status bar messages.
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        if name.trim().is_empty() {
+            return Err("Give the search a name.".to_string());
+        }
+        if name.contains('=') {
+            return Err("Search names can't contain '='.".to_string());
+        }
+        Ok(())
+    }
+
+    fn searches_path() -> Option<PathBuf> {
+        // Tests never read or write the real ~/.purple
+        if cfg!(test) {
+            return None;
+        }
+        dirs::home_dir().map(|h| h.join(".purple/searches"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("purple_test_searches_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("searches")
+    }
+
+    #[test]
+    fn round_trip_and_replace() {
+        let path = temp_path("round_trip");
+        let mut searches = SavedSearches::load_from(path.clone());
+        assert!(searches.entries.is_empty());
+        assert!(!searches.set("prod roots", "tag:prod user:root"));
+        assert!(!searches.set("pve", "provider:proxmox"));
+        assert!(searches.set("PVE", "provider:proxmox -tag=old"));
+        searches.save().unwrap();
+
+        let loaded = SavedSearches::load_from(path);
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.get("prod roots").unwrap().query, "tag:prod user:root");
+        // tag= in the query survives the name=query format
+        assert_eq!(loaded.get("pve").unwrap().query, "provider:proxmox -tag=old");
+    }
+
+    #[test]
+    fn skips_comments_and_rejects_bad_names() {
+        let path = temp_path("comments");
+        std::fs::write(&path, "# mine\n\nweb=web\nbroken\n=nameless\n").unwrap();
+        let mut searches = SavedSearches::load_from(path);
+        assert_eq!(searches.entries.len(), 1);
+        assert!(searches.remove(3).is_none());
+        assert_eq!(searches.remove(0).unwrap().name, "web");
+        assert!(SavedSearches::validate_name("  ").is_err());
+        assert!(SavedSearches::validate_name("a=b").is_err());
+        assert!(SavedSearches::validate_name("prod roots").is_ok());
+    }
+}
@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::diff::{DiffLine, line_diff};
+use super::model::{ConfigElement, SshConfigFile};
+
+/// A `.bak.<millis>` copy of the config or one of its Include files. All
+/// backups live next to the config.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    /// The millisecond timestamp suffix, plus `-<file name>` for Include
+    /// files. Used as the id on the command line.
+    pub id: String,
+    pub path: PathBuf,
+    /// The file this is a backup of.
+    pub file: PathBuf,
+    /// Creation time in Unix seconds.
+    pub created: u64,
+}
+
+/// Host-level differences between two versions of a config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl HostChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Compact summary like "+1 -0 ~2".
+    pub fn summary(&self) -> String {
+        format!(
+            "+{} -{} ~{}",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// What changed between a backup and the current file.
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub changes: HostChanges,
+    pub diff: Vec<DiffLine>,
+}
+
+impl Comparison {
+    /// True when the backup matches the current file line for line.
+    pub fn is_identical(&self) -> bool {
+        self.diff.iter().all(|l| matches!(l, DiffLine::Same(_)))
+    }
+}
+
+/// Backups of `config` and of its Include files `includes`, newest first.
+pub fn list(config: &Path, includes: &[PathBuf]) -> Vec<Backup> {
+    let Some(parent) = config.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(parent) else {
+        return Vec::new();
+    };
+    let names: Vec<(String, PathBuf)> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| (e.file_name().to_string_lossy().to_string(), e.path()))
+        .collect();
+    let mut backups: Vec<(u64, Backup)> = Vec::new();
+    for file in std::iter::once(config).chain(includes.iter().map(PathBuf::as_path)) {
+        let Some((_, prefix)) = SshConfigFile::backup_location(file, config) else {
+            continue;
+        };
+        for (name, path) in &names {
+            let Some(suffix) = name.strip_prefix(&prefix) else {
+                continue;
+            };
+            let Ok(millis) = suffix.parse::<u64>() else {
+                continue;
+            };
+            let id = if file == config {
+                suffix.to_string()
+            } else {
+                format!("{}-{}", suffix, file.file_name().unwrap_or_default().to_string_lossy())
+            };
+            backups.push((
+                millis,
+                Backup {
+                    id,
+                    path: path.clone(),
+                    file: file.to_path_buf(),
+                    created: millis / 1000,
+                },
+            ));
+        }
+    }
+    backups.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.id.cmp(&b.1.id)));
+    backups.into_iter().map(|(_, b)| b).collect()
+}
+
+/// Find a backup by id. A unique prefix of the id is enough.
+pub fn find(config: &Path, includes: &[PathBuf], id: &str) -> Option<Backup> {
+    let backups = list(config, includes);
+    if let Some(exact) = backups.iter().find(|b| b.id == id) {
+        return Some(exact.clone());
+    }
+    let mut matches = backups.into_iter().filter(|b| b.id.starts_with(id));
+    let first = matches.next()?;
+    matches.next().is_none().then_some(first)
+}
+
+/// Compare a backup (old) against the current version of its file (new).
+pub fn compare(backup: &Backup) -> Result<Comparison> {
+    let old = fs::read_to_string(&backup.path)
+        .with_context(|| format!("Failed to read {}", backup.path.display()))?;
+    let new = fs::read_to_string(&backup.file).unwrap_or_default();
+    Ok(Comparison {
+        changes: host_changes(&old, &new),
+        diff: line_diff(&old, &new),
+    })
+}
+
+/// Replace the backed up file (the main `config` or one of its Include files)
+/// with the backup's content. The current file is backed up first, like any
+/// other write.
+pub fn restore(backup: &Backup, config: &Path) -> Result<()> {
+    let content = fs::read_to_string(&backup.path)
+        .with_context(|| format!("Failed to read {}", backup.path.display()))?;
+    SshConfigFile::write_file(&backup.file, &content, config)
+}
+
+/// Host blocks added, removed or changed going from `old` to `new`.
+/// Hosts are keyed by their Host pattern. Order follows the file.
+pub fn host_changes(old: &str, new: &str) -> HostChanges {
+    let old_blocks = host_blocks(old);
+    let new_blocks = host_blocks(new);
+    let old_map: HashMap<&str, &str> = old_blocks
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    let new_map: HashMap<&str, &str> = new_blocks
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+
+    let mut changes = HostChanges::default();
+    for (pattern, body) in &new_blocks {
+        match old_map.get(pattern.as_str()) {
+            None => changes.added.push(pattern.clone()),
+            Some(old_body) if old_body != body => changes.changed.push(pattern.clone()),
+            Some(_) => {}
+        }
+    }
+    for (pattern, _) in &old_blocks {
+        if !new_map.contains_key(pattern.as_str()) {
+            changes.removed.push(pattern.clone());
+        }
+    }
+    changes
+}
+
+/// (pattern, directive text) for every Host block, trailing blank lines ignored.
+fn host_blocks(content: &str) -> Vec<(String, String)> {
+    SshConfigFile::parse_content(content)
+        .into_iter()
+        .filter_map(|element| match element {
+            ConfigElement::HostBlock(block) => {
+                let lines: Vec<&str> = block
+                    .directives
+                    .iter()
+                    .map(|d| d.raw_line.trim_end())
+                    .collect();
+                let end = lines
+                    .iter()
+                    .rposition(|l| !l.is_empty())
+                    .map_or(0, |i| i + 1);
+                Some((block.host_pattern, lines[..end].join("\n")))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Format Unix seconds as "YYYY-MM-DD HH:MM:SS UTC".
+pub fn format_utc(secs: u64) -> String {
+    let days = secs / 86_400;
+    let rem = secs % 86_400;
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("purple_backup_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn list_newest_first_and_find_by_prefix() {
+        let dir = temp_dir("list");
+        let path = dir.join("config");
+        fs::write(&path, "Host a\n").unwrap();
+        fs::write(dir.join("config.bak.1700000000000"), "old\n").unwrap();
+        fs::write(dir.join("config.bak.1700000500000"), "newer\n").unwrap();
+        fs::write(dir.join("config.bak.notanumber"), "skip\n").unwrap();
+        fs::write(dir.join("other.bak.1700000600000"), "skip\n").unwrap();
+
+        let backups = list(&path, &[]);
+        let ids: Vec<&str> = backups.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, vec!["1700000500000", "1700000000000"]);
+        assert_eq!(backups[0].created, 1_700_000_500);
+
+        assert_eq!(find(&path, &[], "17000005").unwrap().id, "1700000500000");
+        // Ambiguous prefix
+        assert!(find(&path, &[], "1700000").is_none());
+        assert!(find(&path, &[], "42").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn host_changes_detects_added_removed_changed() {
+        let old = "Host a\n  HostName 1.1.1.1\n\nHost b\n  HostName 2.2.2.2\n\nHost c\n  User x\n";
+        let new = "Host a\n  HostName 1.1.1.1\n\nHost c\n  User y\n\nHost d\n  HostName 4.4.4.4\n";
+        let changes = host_changes(old, new);
+        assert_eq!(changes.added, vec!["d"]);
+        assert_eq!(changes.removed, vec!["b"]);
+        assert_eq!(changes.changed, vec!["c"]);
+        assert_eq!(changes.summary(), "+1 -1 ~1");
+    }
+
+    #[test]
+    fn host_changes_ignores_trailing_blank_lines() {
+        let old = "Host a\n  HostName 1.1.1.1\n\nHost b\n  HostName 2\n";
+        let new = "Host b\n  HostName 2\n\nHost a\n  HostName 1.1.1.1\n";
+        assert!(host_changes(old, new).is_empty());
+    }
+
+    #[test]
+    fn restore_backs_up_current_file() {
+        let dir = temp_dir("restore");
+        let path = dir.join("config");
+        fs::write(&path, "Host current\n").unwrap();
+        fs::write(dir.join("config.bak.1000"), "Host old\n").unwrap();
+        let backup = find(&path, &[], "1000").unwrap();
+
+        let comparison = compare(&backup).unwrap();
+        assert_eq!(comparison.changes.added, vec!["current"]);
+        assert_eq!(comparison.changes.removed, vec!["old"]);
+
+        restore(&backup, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Host old\n");
+        let backups = list(&path, &[]);
+        assert_eq!(backups.len(), 2);
+        assert_eq!(fs::read_to_string(&backups[0].path).unwrap(), "Host current\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn include_backups_listed_and_restored_to_their_file() {
+        let dir = temp_dir("include");
+        fs::create_dir_all(dir.join("config.d")).unwrap();
+        let path = dir.join("config");
+        let work = dir.join("config.d").join("work");
+        fs::write(&path, "Include config.d/*\n").unwrap();
+        fs::write(&work, "Host work\r\n  HostName 10.0.0.1\r\n").unwrap();
+
+        let mut config = SshConfigFile::parse(&path).unwrap();
+        config.update_host(
+            "work",
+            &crate::ssh_config::model::HostEntry {
+                alias: "work".to_string(),
+                hostname: "10.0.0.2".to_string(),
+                ..Default::default()
+            },
+        );
+        config.write().unwrap();
+        assert_eq!(
+            fs::read_to_string(&work).unwrap(),
+            "Host work\r\n  HostName 10.0.0.2\r\n"
+        );
+
+        let includes = config.included_paths();
+        let backups = list(&path, &includes);
+        assert_eq!(backups.len(), 2);
+        let backup = backups.iter().find(|b| b.file == work).unwrap();
+        assert!(backup.id.ends_with("-work"));
+        assert_eq!(backup.path.parent(), Some(dir.as_path()));
+        assert_eq!(compare(backup).unwrap().changes.changed, vec!["work"]);
+
+        // Backups are named by millisecond, keep the next one apart
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        restore(backup, &path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&work).unwrap(),
+            "Host work\r\n  HostName 10.0.0.1\r\n"
+        );
+        assert_eq!(list(&path, &includes).len(), 3);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn format_utc_dates() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20 UTC");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00 UTC");
+    }
+}
@@ -0,0 +1,358 @@
+use super::model::{ConfigElement, Directive, HostBlock, HostEntry, SshConfigFile, rebuild_raw_line};
+
+/// Directives the Defaults screen edits, in display order.
+pub const DEFAULT_KEYS: &[&str] = &[
+    "ServerAliveInterval",
+    "AddKeysToAgent",
+    "UseKeychain",
+    "IdentitiesOnly",
+    "ControlMaster",
+    "ControlPath",
+    "ControlPersist",
+    "HashKnownHosts",
+];
+
+/// Where a default is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DefaultScope {
+    /// A top-level line before the first Host or Match block.
+    Global,
+    /// The `Host *` block.
+    HostStar,
+}
+
+/// A `Host *` block that sits above specific hosts setting the same keys.
+/// OpenSSH keeps the first value it finds, so the wildcard wins for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverriddenHost {
+    pub alias: String,
+    pub keys: Vec<String>,
+}
+
+impl SshConfigFile {
+    /// Directives on the top-level lines before the first Host or Match block.
+    pub fn global_directives(&self) -> Vec<(String, String)> {
+        self.global_lines()
+            .filter_map(|(_, line)| SshConfigFile::parse_directive(line.trim()))
+            .collect()
+    }
+
+    /// Directives of the `Host *` block in the main config.
+    pub fn host_star_directives(&self) -> Vec<(String, String)> {
+        self.host_star()
+            .map(|block| {
+                block
+                    .directives
+                    .iter()
+                    .filter(|d| !d.is_non_directive)
+                    .map(|d| (d.key.clone(), d.value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The value OpenSSH uses for `key` when no host sets it: top-level lines
+    /// first, then `Host *`.
+    pub fn default_value(&self, key: &str) -> Option<(String, DefaultScope)> {
+        let global = self.global_directives();
+        if let Some((_, value)) = global.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+            return Some((value.clone(), DefaultScope::Global));
+        }
+        self.host_star_directives()
+            .into_iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| (value, DefaultScope::HostStar))
+    }
+
+    /// Set a default where it is already defined, otherwise in `Host *`
+    /// (added at the end of the file when missing, below every specific host).
+    /// An empty value removes the key from both places.
+    pub fn set_default(&mut self, key: &str, value: &str) {
+        if value.is_empty() {
+            self.remove_global(key);
+            if let Some(block) = self.host_star_mut() {
+                SshConfigFile::upsert_directive(block, key, "");
+            }
+            return;
+        }
+        if self.set_global(key, value) {
+            return;
+        }
+        if self.host_star().is_none() {
+            self.add_host(&HostEntry {
+                alias: "*".to_string(),
+                ..Default::default()
+            });
+        }
+        if let Some(block) = self.host_star_mut() {
+            SshConfigFile::upsert_directive(block, key, value);
+        }
+    }
+
+    /// Specific hosts below `Host *` that set a key `Host *` already set.
+    /// Include files are walked in order.
+    pub fn host_star_overrides(&self) -> Vec<OverriddenHost> {
+        fn walk(elements: &[ConfigElement], star: &mut Option<Vec<String>>, out: &mut Vec<OverriddenHost>) {
+            for element in elements {
+                match element {
+                    ConfigElement::HostBlock(block) => {
+                        let keys = block.directives.iter().filter(|d| !d.is_non_directive).map(|d| &d.key);
+                        if block.host_pattern.trim() == "*" {
+                            if star.is_none() {
+                                *star = Some(keys.map(|k| k.to_ascii_lowercase()).collect());
+                            }
+                            continue;
+                        }
+                        let Some(star_keys) = star.as_ref() else {
+                            continue;
+                        };
+                        if super::model::is_host_pattern(&block.host_pattern) {
+                            continue;
+                        }
+                        let mut shared: Vec<String> = Vec::new();
+                        for key in keys {
+                            if star_keys.contains(&key.to_ascii_lowercase())
+                                && !shared.iter().any(|k| k.eq_ignore_ascii_case(key))
+                            {
+                                shared.push(key.clone());
+                            }
+                        }
+                        if !shared.is_empty() {
+                            out.push(OverriddenHost {
+                                alias: block.alias(),
+                                keys: shared,
+                            });
+                        }
+                    }
+                    ConfigElement::Include(include) => {
+                        for file in &include.resolved_files {
+                            walk(&file.elements, star, out);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut out = Vec::new();
+        walk(&self.elements, &mut None, &mut out);
+        out
+    }
+
+    /// Top-level lines before the first block, with their element index.
+    fn global_lines(&self) -> impl Iterator<Item = (usize, &String)> {
+        self.elements
+            .iter()
+            .enumerate()
+            .take_while(|(_, e)| !matches!(e, ConfigElement::HostBlock(_) | ConfigElement::MatchBlock(_)))
+            .filter_map(|(i, e)| match e {
+                ConfigElement::GlobalLine(line) if is_directive_line(line) => Some((i, line)),
+                _ => None,
+            })
+    }
+
+    fn set_global(&mut self, key: &str, value: &str) -> bool {
+        let found = self.global_lines().find_map(|(i, line)| {
+            let (k, v) = SshConfigFile::parse_directive(line.trim())?;
+            k.eq_ignore_ascii_case(key).then(|| (i, k, v, line.clone()))
+        });
+        let Some((index, k, v, line)) = found else {
+            return false;
+        };
+        if v != value {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            let directive = Directive {
+                key: k,
+                value: v,
+                raw_line: line.clone(),
+                is_non_directive: false,
+            };
+            self.elements[index] = ConfigElement::GlobalLine(rebuild_raw_line(&directive, indent, value));
+        }
+        true
+    }
+
+    fn remove_global(&mut self, key: &str) {
+        let indexes: Vec<usize> = self
+            .global_lines()
+            .filter(|(_, line)| {
+                SshConfigFile::parse_directive(line.trim()).is_some_and(|(k, _)| k.eq_ignore_ascii_case(key))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        for index in indexes.iter().rev() {
+            self.elements.remove(*index);
+        }
+        if !indexes.is_empty() {
+            self.invalidate_index();
+        }
+    }
+
+    fn host_star(&self) -> Option<&HostBlock> {
+        self.elements.iter().find_map(|e| match e {
+            ConfigElement::HostBlock(block) if block.host_pattern.trim() == "*" => Some(block),
+            _ => None,
+        })
+    }
+
+    fn host_star_mut(&mut self) -> Option<&mut HostBlock> {
+        self.elements.iter_mut().find_map(|e| match e {
+            ConfigElement::HostBlock(block) if block.host_pattern.trim() == "*" => Some(block),
+            _ => None,
+        })
+    }
+}
+
+fn is_directive_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+/// Check a value for one of the `DEFAULT_KEYS`. Empty means "not set".
+pub fn validate_default(key: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() {
+        return Ok(());
+    }
+    if value.chars().any(|c| c.is_control()) {
+        return Err(format!("{} contains control characters.", key));
+    }
+    let lower = value.to_ascii_lowercase();
+    let one_of = |options: &[&str]| -> Result<(), String> {
+        if options.contains(&lower.as_str()) {
+            Ok(())
+        } else {
+            Err(format!("{} takes {}.", key, options.join(", ")))
+        }
+    };
+    match key.to_ascii_lowercase().as_str() {
+        "serveraliveinterval" => match value.parse::<u32>() {
+            Ok(_) => Ok(()),
+            Err(_) => Err("ServerAliveInterval takes a number of seconds.".to_string()),
+        },
+        "addkeystoagent" => {
+            let mut words = lower.split_whitespace();
+            let first = words.next().unwrap_or("");
+            let rest: Vec<&str> = words.collect();
+            let ok = match first {
+                "yes" | "no" | "ask" => rest.is_empty(),
+                "confirm" => rest.len() <= 1 && rest.iter().all(|t| is_time(t)),
+                _ => rest.is_empty() && is_time(first),
+            };
+            if ok {
+                Ok(())
+            } else {
+                Err("AddKeysToAgent takes yes, no, ask, confirm or a time like 1h.".to_string())
+            }
+        }
+        "usekeychain" | "identitiesonly" | "hashknownhosts" => one_of(&["yes", "no"]),
+        "controlmaster" => one_of(&["yes", "no", "ask", "auto", "autoask"]),
+        "controlpath" => {
+            if value.contains(char::is_whitespace) && !value.starts_with('"') {
+                Err("ControlPath can't contain spaces unless quoted.".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        "controlpersist" => {
+            if matches!(lower.as_str(), "yes" | "no") || is_time(&lower) {
+                Ok(())
+            } else {
+                Err("ControlPersist takes yes, no or a time like 10m.".to_string())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// OpenSSH time format: digits with an optional unit, repeated (`90`, `10m`, `1h30m`).
+fn is_time(value: &str) -> bool {
+    let mut chars = value.chars().peekable();
+    if chars.peek().is_none() {
+        return false;
+    }
+    while chars.peek().is_some() {
+        let mut digits = 0;
+        while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+            chars.next();
+            digits += 1;
+        }
+        if digits == 0 {
+            return false;
+        }
+        if chars.peek().is_some_and(|c| "sSmMhHdDwW".contains(*c)) {
+            chars.next();
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        }
+    }
+
+    #[test]
+    fn reads_global_lines_and_host_star() {
+        let config = parse_str(
+            "# defaults\nAddKeysToAgent=yes\nInclude conf.d/*\n\nHost web\n  HostName 10.0.0.1\n\nHost *\n  ServerAliveInterval 30\n  AddKeysToAgent no\n",
+        );
+        assert_eq!(config.global_directives(), vec![("AddKeysToAgent".to_string(), "yes".to_string())]);
+        assert_eq!(config.default_value("addkeystoagent"), Some(("yes".to_string(), DefaultScope::Global)));
+        assert_eq!(config.default_value("ServerAliveInterval"), Some(("30".to_string(), DefaultScope::HostStar)));
+        assert_eq!(config.default_value("ControlMaster"), None);
+    }
+
+    #[test]
+    fn set_default_edits_in_place_or_adds_host_star_at_end() {
+        let mut config = parse_str("AddKeysToAgent = yes\n\nHost web\n  HostName 10.0.0.1\n");
+        config.set_default("AddKeysToAgent", "confirm");
+        config.set_default("ServerAliveInterval", "30");
+        config.set_default("IdentitiesOnly", "yes");
+        assert_eq!(
+            config.serialize(),
+            "AddKeysToAgent = confirm\n\nHost web\n  HostName 10.0.0.1\n\nHost *\n  ServerAliveInterval 30\n  IdentitiesOnly yes\n"
+        );
+
+        config.set_default("AddKeysToAgent", "");
+        config.set_default("ServerAliveInterval", "");
+        assert_eq!(config.serialize(), "\nHost web\n  HostName 10.0.0.1\n\nHost *\n  IdentitiesOnly yes\n");
+    }
+
+    #[test]
+    fn host_star_above_hosts_is_reported() {
+        let config = parse_str(
+            "Host *\n  User root\n  ServerAliveInterval 30\n\nHost web\n  User deploy\n\nHost db\n  HostName 10.0.0.2\n\nHost *.corp\n  User x\n",
+        );
+        assert_eq!(
+            config.host_star_overrides(),
+            vec![OverriddenHost { alias: "web".to_string(), keys: vec!["User".to_string()] }]
+        );
+        let config = parse_str("Host web\n  User deploy\n\nHost *\n  User root\n");
+        assert!(config.host_star_overrides().is_empty());
+    }
+
+    #[test]
+    fn validates_values() {
+        assert!(validate_default("ServerAliveInterval", "30").is_ok());
+        assert!(validate_default("ServerAliveInterval", "30s").is_err());
+        assert!(validate_default("AddKeysToAgent", "confirm 1h").is_ok());
+        assert!(validate_default("AddKeysToAgent", "4h30m").is_ok());
+        assert!(validate_default("AddKeysToAgent", "maybe").is_err());
+        assert!(validate_default("UseKeychain", "YES").is_ok());
+        assert!(validate_default("IdentitiesOnly", "true").is_err());
+        assert!(validate_default("ControlMaster", "autoask").is_ok());
+        assert!(validate_default("ControlPath", "~/.ssh/cm-%r@%h:%p").is_ok());
+        assert!(validate_default("ControlPath", "~/my dir/%C").is_err());
+        assert!(validate_default("ControlPersist", "10m").is_ok());
+        assert!(validate_default("ControlPersist", "m10").is_err());
+        assert!(validate_default("HashKnownHosts", "").is_ok());
+    }
+}
@@ -0,0 +1,230 @@
+use std::path::PathBuf;
+
+use super::model::SshConfigFile;
+
+/// Above this many cells the line diff skips the LCS and replaces the
+/// differing middle wholesale (keeps huge configs from stalling the UI).
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// One line of a line diff.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// Unified diff of every file `after` would write that differs from `before`.
+/// Each changed file starts with "--- path" / "+++ path" headers.
+pub fn config_diff(before: &SshConfigFile, after: &SshConfigFile) -> Vec<String> {
+    let old_files = before.snapshot();
+    let new_files = after.snapshot();
+    let mut out = Vec::new();
+    let mut paths: Vec<&PathBuf> = new_files.iter().map(|(p, _)| p).collect();
+    for (path, _) in &old_files {
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    for path in paths {
+        let content = |files: &[(PathBuf, String)]| {
+            files
+                .iter()
+                .find(|(p, _)| p == path)
+                .map(|(_, c)| c.clone())
+                .unwrap_or_default()
+        };
+        let (old, new) = (content(&old_files), content(&new_files));
+        if old == new {
+            continue;
+        }
+        out.push(format!("--- {}", path.display()));
+        out.push(format!("+++ {}", path.display()));
+        out.extend(unified(&line_diff(&old, &new), 3));
+    }
+    out
+}
+
+/// Line diff from `old` to `new` (longest common subsequence).
+pub fn line_diff(old: &str, new: &str) -> Vec<DiffLine> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Trim the common prefix and suffix, diff only the middle
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut result: Vec<DiffLine> = a[..prefix]
+        .iter()
+        .map(|l| DiffLine::Same(l.to_string()))
+        .collect();
+    if mid_a.len().saturating_mul(mid_b.len()) > MAX_DIFF_CELLS {
+        result.extend(mid_a.iter().map(|l| DiffLine::Removed(l.to_string())));
+        result.extend(mid_b.iter().map(|l| DiffLine::Added(l.to_string())));
+    } else {
+        result.extend(lcs_diff(mid_a, mid_b));
+    }
+    result.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .map(|l| DiffLine::Same(l.to_string())),
+    );
+    result
+}
+
+fn lcs_diff(a: &[&str], b: &[&str]) -> Vec<DiffLine> {
+    let (n, m) = (a.len(), b.len());
+    // lengths[i][j] = LCS length of a[i..] and b[j..]
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if a[i] == b[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if a[i] == b[j] {
+            result.push(DiffLine::Same(a[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            result.push(DiffLine::Removed(a[i].to_string()));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(b[j].to_string()));
+            j += 1;
+        }
+    }
+    result.extend(a[i..].iter().map(|l| DiffLine::Removed(l.to_string())));
+    result.extend(b[j..].iter().map(|l| DiffLine::Added(l.to_string())));
+    result
+}
+
+/// Render a diff as unified-style lines with `context` unchanged lines around
+/// each change. Hunks are separated by "@@ line N @@" headers (N is the line in `old`).
+pub fn unified(diff: &[DiffLine], context: usize) -> Vec<String> {
+    let changed: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| !matches!(l, DiffLine::Same(_)))
+        .map(|(i, _)| i)
+        .collect();
+    let mut out = Vec::new();
+    let mut last_shown: Option<usize> = None;
+    let mut idx = 0;
+    while idx < changed.len() {
+        let start = changed[idx].saturating_sub(context);
+        // Extend the hunk while the next change is within 2*context lines
+        let mut end = changed[idx];
+        while idx + 1 < changed.len() && changed[idx + 1] <= end + 2 * context + 1 {
+            idx += 1;
+            end = changed[idx];
+        }
+        let end = (end + context + 1).min(diff.len());
+        let start = last_shown.map_or(start, |l| start.max(l));
+        let old_line = diff[..start]
+            .iter()
+            .filter(|l| !matches!(l, DiffLine::Added(_)))
+            .count()
+            + 1;
+        out.push(format!("@@ line {} @@", old_line));
+        for line in &diff[start..end] {
+            out.push(match line {
+                DiffLine::Same(l) => format!(" {}", l),
+                DiffLine::Added(l) => format!("+{}", l),
+                DiffLine::Removed(l) => format!("-{}", l),
+            });
+        }
+        last_shown = Some(end);
+        idx += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssh_config::model::HostEntry;
+
+    #[test]
+    fn line_diff_marks_changes() {
+        let diff = line_diff("a\nb\nc\nd\n", "a\nc\nd\ne\n");
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Same("a".into()),
+                DiffLine::Removed("b".into()),
+                DiffLine::Same("c".into()),
+                DiffLine::Same("d".into()),
+                DiffLine::Added("e".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unified_groups_hunks_with_context() {
+        let old: Vec<String> = (1..=20).map(|i| format!("line{}", i)).collect();
+        let mut new = old.clone();
+        new[1] = "changed2".into();
+        new[17] = "changed18".into();
+        let diff = line_diff(&old.join("\n"), &new.join("\n"));
+        let out = unified(&diff, 1);
+        assert_eq!(
+            out,
+            vec![
+                "@@ line 1 @@",
+                " line1",
+                "-line2",
+                "+changed2",
+                " line3",
+                "@@ line 17 @@",
+                " line17",
+                "-line18",
+                "+changed18",
+                " line19",
+            ]
+        );
+        assert!(unified(&line_diff("x\n", "x\n"), 3).is_empty());
+    }
+
+    #[test]
+    fn config_diff_shows_pending_changes() {
+        let before = SshConfigFile {
+            elements: SshConfigFile::parse_content("Host a\n  HostName 1.1.1.1\n"),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        };
+        let mut after = before.clone();
+        after.add_host(&HostEntry {
+            alias: "b".to_string(),
+            hostname: "2.2.2.2".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(
+            config_diff(&before, &after),
+            vec![
+                "--- /tmp/test_config",
+                "+++ /tmp/test_config",
+                "@@ line 1 @@",
+                " Host a",
+                "   HostName 1.1.1.1",
+                "+",
+                "+Host b",
+                "+  HostName 2.2.2.2",
+            ]
+        );
+        assert!(config_diff(&before, &before).is_empty());
+    }
+}
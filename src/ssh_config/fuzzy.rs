@@ -0,0 +1,149 @@
+/// Points for every matched character.
+const SCORE_MATCH: i64 = 16;
+/// Match right after the previous one (`web` in `prod-web`).
+const BONUS_CONSECUTIVE: i64 = 12;
+/// Match on the first character of the text.
+const BONUS_FIRST: i64 = 10;
+/// Match after a separator (`-`, `.`, `_`, space ...).
+const BONUS_BOUNDARY: i64 = 8;
+/// Match on a camelCase hump or where digits start (`web01`).
+const BONUS_CAMEL: i64 = 6;
+/// Opening a gap between two matched characters.
+const PENALTY_GAP_START: i64 = 3;
+/// Every further character skipped inside a gap.
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+/// A subsequence match: higher scores are better matches.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i64,
+    /// Character (not byte) indices of the matched characters in the text.
+    pub positions: Vec<usize>,
+}
+
+/// fzf-style match of `pattern` as a case-insensitive subsequence of `text`.
+/// Picks the alignment with the best score, so `pw` in `api-prod-web` lands
+/// on the word starts rather than the first `p` and `w` it finds.
+pub fn fuzzy_match(text: &str, pattern: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern.chars().map(fold).collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch::default());
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let folded: Vec<char> = chars.iter().map(|&c| fold(c)).collect();
+    if !is_subsequence(&folded, &pattern) {
+        return None;
+    }
+
+    let n = chars.len();
+    // best[i][j]: best score with pattern[i] matched at text[j]; from[i][j]: where pattern[i - 1] sat
+    let mut best: Vec<Vec<Option<i64>>> = vec![vec![None; n]; pattern.len()];
+    let mut from: Vec<Vec<usize>> = vec![vec![0; n]; pattern.len()];
+    for j in 0..n {
+        if folded[j] == pattern[0] {
+            best[0][j] = Some(SCORE_MATCH + bonus(&chars, j));
+        }
+    }
+    for i in 1..pattern.len() {
+        // Best predecessor at least two characters back, gap penalty included
+        let mut gapped: Option<(i64, usize)> = None;
+        for j in i..n {
+            if let Some(g) = gapped.as_mut() {
+                g.0 -= PENALTY_GAP_EXTENSION;
+            }
+            if j >= 2 {
+                if let Some(score) = best[i - 1][j - 2] {
+                    let candidate = score - PENALTY_GAP_START;
+                    if gapped.is_none_or(|(g, _)| candidate >= g) {
+                        gapped = Some((candidate, j - 2));
+                    }
+                }
+            }
+            if folded[j] != pattern[i] {
+                continue;
+            }
+            let mut prev = gapped;
+            if let Some(score) = best[i - 1][j - 1] {
+                let candidate = score + BONUS_CONSECUTIVE;
+                if prev.is_none_or(|(p, _)| candidate > p) {
+                    prev = Some((candidate, j - 1));
+                }
+            }
+            if let Some((score, k)) = prev {
+                best[i][j] = Some(score + SCORE_MATCH + bonus(&chars, j));
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = pattern.len() - 1;
+    let (mut j, score) = best[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, s)| s.map(|s| (j, s)))
+        .max_by_key(|&(j, s)| (s, std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; pattern.len()];
+    for i in (0..pattern.len()).rev() {
+        positions[i] = j;
+        j = from[i][j];
+    }
+    Some(FuzzyMatch { score, positions })
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn is_subsequence(text: &[char], pattern: &[char]) -> bool {
+    let mut rest = pattern.iter().peekable();
+    for c in text {
+        if rest.peek() == Some(&c) {
+            rest.next();
+        }
+    }
+    rest.peek().is_none()
+}
+
+fn bonus(chars: &[char], j: usize) -> i64 {
+    if j == 0 {
+        return BONUS_FIRST;
+    }
+    let (prev, c) = (chars[j - 1], chars[j]);
+    if !prev.is_alphanumeric() {
+        BONUS_BOUNDARY
+    } else if (prev.is_lowercase() && c.is_uppercase())
+        || (!prev.is_ascii_digit() && c.is_ascii_digit())
+    {
+        BONUS_CAMEL
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(text: &str, pattern: &str) -> i64 {
+        fuzzy_match(text, pattern).unwrap().score
+    }
+
+    #[test]
+    fn subsequence_across_separators() {
+        let m = fuzzy_match("prod-web-01", "prdweb").unwrap();
+        assert_eq!(m.positions, vec![0, 1, 3, 5, 6, 7]);
+        assert!(fuzzy_match("prod-web-01", "webprod").is_none());
+        assert!(fuzzy_match("PROD-Web", "prodweb").is_some());
+        assert_eq!(fuzzy_match("web", ""), Some(FuzzyMatch::default()));
+    }
+
+    #[test]
+    fn prefers_word_starts_and_runs() {
+        // The p that starts "prod", not the one inside "api"
+        let m = fuzzy_match("api-prod-web", "pw").unwrap();
+        assert_eq!(m.positions, vec![4, 9]);
+        assert!(score("web-01", "web") > score("dweb-01", "web"));
+        assert!(score("staging-db", "db") > score("dashboard", "db"));
+        assert!(score("web", "web") > score("w-e-b", "web"));
+    }
+}
@@ -0,0 +1,713 @@
+//! User-defined host groups. A group is a `# purple:group <name>` line in the
+//! main config and holds the hosts below it, up to the next group header.
+//! Hosts above the first header are ungrouped. Hosts can also be moved one
+//! step at a time, across headers, for manual ordering.
+
+use std::ops::Range;
+
+use super::model::{CommentedBlock, ConfigElement, Directive, HostEntry, SshConfigFile, is_host_pattern};
+use super::resolve::match_pattern_list;
+
+/// Comment that opens a group.
+pub const GROUP_PREFIX: &str = "# purple:group ";
+
+/// A group header and the hosts under it, in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostGroup {
+    pub name: String,
+    pub hosts: Vec<String>,
+}
+
+/// Where [`SshConfigFile::move_host`] takes a host.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostMove {
+    /// Above the previous block, or to the end of the group above.
+    Up,
+    /// Below the next block, or to the top of the group below.
+    Down,
+    /// First in its group.
+    Top,
+    /// Last in its group.
+    Bottom,
+}
+
+/// Name of the group a `# purple:group` line opens.
+pub fn group_name(line: &str) -> Option<&str> {
+    let text = line.trim().strip_prefix('#')?.trim_start();
+    let name = text.strip_prefix("purple:group ")?.trim();
+    (!name.is_empty()).then_some(name)
+}
+
+/// The last group header in a block's separator tail. The parser keeps the
+/// lines between two blocks in the first one, so headers usually end up here.
+pub fn trailing_group(directives: &[Directive]) -> Option<&str> {
+    directives[tail_start(directives)..]
+        .iter()
+        .rev()
+        .find_map(|d| group_name(&d.raw_line))
+}
+
+/// Start of the lines that separate a block from the next element: the first
+/// blank line or group header after the block's last directive or purple
+/// metadata comment.
+fn tail_start(directives: &[Directive]) -> usize {
+    let owned = directives.iter().rposition(|d| {
+        !d.is_non_directive
+            || (d.raw_line.trim().starts_with("# purple:") && group_name(&d.raw_line).is_none())
+    });
+    directives
+        .iter()
+        .enumerate()
+        .position(|(i, d)| {
+            owned.is_none_or(|last| i > last)
+                && (d.raw_line.trim().is_empty() || group_name(&d.raw_line).is_some())
+        })
+        .unwrap_or(directives.len())
+}
+
+fn is_blank(element: &ConfigElement) -> bool {
+    matches!(element, ConfigElement::GlobalLine(line) if line.trim().is_empty())
+}
+
+fn header_of(element: &ConfigElement) -> Option<&str> {
+    match element {
+        ConfigElement::GlobalLine(line) => group_name(line),
+        _ => None,
+    }
+}
+
+/// A comment line that belongs to the host right below it.
+fn is_leading_comment(element: &ConfigElement) -> bool {
+    matches!(element, ConfigElement::GlobalLine(line)
+        if line.trim_start().starts_with('#') && group_name(line).is_none())
+}
+
+/// Anything a host can be moved past. Top-level directives and Include lines
+/// are not: a host above them would change what they apply to.
+fn is_movable(element: &ConfigElement) -> bool {
+    match element {
+        ConfigElement::GlobalLine(line) => line.trim_start().starts_with('#'),
+        ConfigElement::Include(_) => false,
+        _ => true,
+    }
+}
+
+fn is_block(element: &ConfigElement) -> bool {
+    !matches!(element, ConfigElement::GlobalLine(_) | ConfigElement::Include(_))
+}
+
+/// Alias of a top-level host a group can hold (not a wildcard pattern).
+fn host_alias(element: &ConfigElement) -> Option<String> {
+    match element {
+        ConfigElement::HostBlock(block)
+        | ConfigElement::DisabledHost(CommentedBlock { block, .. })
+            if !is_host_pattern(&block.host_pattern) =>
+        {
+            Some(block.alias())
+        }
+        _ => None,
+    }
+}
+
+impl SshConfigFile {
+    /// Groups in the main config, in file order.
+    pub fn groups(&self) -> Vec<HostGroup> {
+        let mut groups: Vec<HostGroup> = Vec::new();
+        let open = |groups: &mut Vec<HostGroup>, name: &str| {
+            groups.push(HostGroup { name: name.to_string(), hosts: Vec::new() });
+        };
+        for element in &self.elements {
+            if let Some(name) = header_of(element) {
+                open(&mut groups, name);
+                continue;
+            }
+            if let Some(alias) = host_alias(element) {
+                if let Some(group) = groups.last_mut() {
+                    group.hosts.push(alias);
+                }
+            }
+            let directives = match element {
+                ConfigElement::HostBlock(block) => &block.directives,
+                ConfigElement::MatchBlock(block) => &block.directives,
+                _ => continue,
+            };
+            for d in &directives[tail_start(directives)..] {
+                if let Some(name) = group_name(&d.raw_line) {
+                    open(&mut groups, name);
+                }
+            }
+        }
+        groups
+    }
+
+    /// Name of the group `alias` is in, if any.
+    pub fn host_group(&self, alias: &str) -> Option<String> {
+        self.groups()
+            .into_iter()
+            .find(|g| g.hosts.iter().any(|h| h == alias))
+            .map(|g| g.name)
+    }
+
+    /// The group called `name`, ignoring case.
+    pub fn find_group(&self, name: &str) -> Option<HostGroup> {
+        self.groups().into_iter().find(|g| g.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Append an empty group header. Returns false if the group exists.
+    pub fn add_group(&mut self, name: &str) -> bool {
+        if self.find_group(name).is_some() {
+            return false;
+        }
+        self.split_separators();
+        if !self.elements.is_empty() && !self.last_element_has_trailing_blank() {
+            self.elements.push(ConfigElement::GlobalLine(String::new()));
+        }
+        self.elements.push(ConfigElement::GlobalLine(format!("{}{}", GROUP_PREFIX, name)));
+        self.invalidate_index();
+        true
+    }
+
+    /// Rename a group header. Returns false if `old` doesn't exist or another
+    /// group is already called `new`.
+    pub fn rename_group(&mut self, old: &str, new: &str) -> bool {
+        if self.find_group(new).is_some_and(|g| !g.name.eq_ignore_ascii_case(old)) {
+            return false;
+        }
+        self.split_separators();
+        let Some(pos) = self.header_position(old) else {
+            return false;
+        };
+        self.elements[pos] = ConfigElement::GlobalLine(format!("{}{}", GROUP_PREFIX, new));
+        true
+    }
+
+    /// Remove a group header. Its hosts stay where they are and join the
+    /// group above (or become ungrouped). Returns false if there is no such group.
+    pub fn delete_group(&mut self, name: &str) -> bool {
+        self.split_separators();
+        let Some(pos) = self.header_position(name) else {
+            return false;
+        };
+        self.elements.remove(pos);
+        self.close_gap(pos);
+        self.invalidate_index();
+        true
+    }
+
+    /// Move a host's block (with the comments right above it) to the end of
+    /// `group`, or to the end of the ungrouped hosts for None. Returns false
+    /// when the host or group doesn't exist, the host lives in an Include file
+    /// or is already there.
+    pub fn move_host_to_group(&mut self, alias: &str, group: Option<&str>) -> bool {
+        if group.is_some_and(|name| self.find_group(name).is_none()) {
+            return false;
+        }
+        let current = self.host_group(alias);
+        let same = match (&current, group) {
+            (Some(current), Some(target)) => current.eq_ignore_ascii_case(target),
+            (None, None) => true,
+            _ => false,
+        };
+        if same || self.host_position(alias).is_none() {
+            return false;
+        }
+        self.split_separators();
+        let Some(pos) = self.host_position(alias) else {
+            return false;
+        };
+        let unit = self.take_unit(pos);
+        let Some(range) = self.group_range(group) else {
+            return false;
+        };
+        self.place_unit(range.end, unit);
+        self.invalidate_index();
+        true
+    }
+
+    /// Sort the hosts of `group` (or the ungrouped hosts) by alias in the file.
+    /// Each host keeps the comments above it, separators stay in place.
+    /// Returns false when there is nothing to reorder.
+    pub fn sort_group(&mut self, group: Option<&str>) -> bool {
+        self.split_separators();
+        let Some(range) = self.group_range(group) else {
+            return false;
+        };
+        let units: Vec<(Range<usize>, String)> = range
+            .clone()
+            .filter_map(|i| {
+                host_alias(&self.elements[i]).map(|alias| (self.unit_start(i)..i + 1, alias))
+            })
+            .collect();
+        let mut sorted = units.clone();
+        sorted.sort_by_cached_key(|(_, alias)| alias.to_lowercase());
+        if sorted.iter().map(|(_, a)| a).eq(units.iter().map(|(_, a)| a)) {
+            return false;
+        }
+        let mut reordered = Vec::with_capacity(range.len());
+        let mut i = range.start;
+        let mut next = 0;
+        while i < range.end {
+            match units.get(next) {
+                Some((unit, _)) if unit.start == i => {
+                    reordered.extend(self.elements[sorted[next].0.clone()].iter().cloned());
+                    i = unit.end;
+                    next += 1;
+                }
+                _ => {
+                    reordered.push(self.elements[i].clone());
+                    i += 1;
+                }
+            }
+        }
+        self.elements.splice(range, reordered);
+        self.invalidate_index();
+        true
+    }
+
+    /// Add a host at the end of `group`, creating the header when it's missing.
+    pub fn add_host_to_group(&mut self, entry: &HostEntry, group: &str) {
+        let name = match self.find_group(group) {
+            Some(existing) => existing.name,
+            None => {
+                self.add_group(group);
+                group.to_string()
+            }
+        };
+        self.split_separators();
+        if let Some(range) = self.group_range(Some(&name)) {
+            // Above a Host * or Match block in the group, so its defaults
+            // don't win over the new host's own settings (first match)
+            let end = range
+                .clone()
+                .find(|&i| match &self.elements[i] {
+                    ConfigElement::HostBlock(block) => {
+                        is_host_pattern(&block.host_pattern)
+                            && match_pattern_list(&entry.alias, &block.patterns())
+                    }
+                    ConfigElement::MatchBlock(_) => true,
+                    _ => false,
+                })
+                .map_or(range.end, |i| self.unit_start(i));
+            let block = ConfigElement::HostBlock(Self::entry_to_block(entry));
+            self.place_unit(end, vec![block]);
+        }
+        self.invalidate_index();
+    }
+
+    /// Move a host's block (with the comments right above it) one step, or to
+    /// the top or bottom of its group. The blank lines between blocks stay
+    /// in place. Returns false when the host can't go further that way or
+    /// lives in an Include file.
+    pub fn move_host(&mut self, alias: &str, to: HostMove) -> bool {
+        if self.host_position(alias).is_none() {
+            return false;
+        }
+        self.split_separators();
+        let Some(pos) = self.host_position(alias) else {
+            return false;
+        };
+        let start = self.unit_start(pos);
+        let non_blank = |i: &usize| !is_blank(&self.elements[*i]);
+        // Insertion point in the current elements, before the unit is taken out
+        let target = match to {
+            HostMove::Up => {
+                let Some(above) = (0..start).rev().find(non_blank) else {
+                    return false;
+                };
+                if header_of(&self.elements[above]).is_some() {
+                    above
+                } else if is_movable(&self.elements[above]) {
+                    self.unit_start(above)
+                } else {
+                    return false;
+                }
+            }
+            HostMove::Down => {
+                let Some(below) = (pos + 1..self.elements.len()).find(non_blank) else {
+                    return false;
+                };
+                if header_of(&self.elements[below]).is_some() {
+                    below + 1
+                } else if is_movable(&self.elements[below]) {
+                    let mut end = below;
+                    while is_leading_comment(&self.elements[end])
+                        && self.elements.get(end + 1).is_some_and(|e| !is_blank(e) && header_of(e).is_none())
+                    {
+                        end += 1;
+                    }
+                    end + 1
+                } else {
+                    return false;
+                }
+            }
+            HostMove::Top | HostMove::Bottom => {
+                let group = self.host_group(alias);
+                let Some(range) = self.group_range(group.as_deref()) else {
+                    return false;
+                };
+                let mut blocks = range.filter(|&i| is_block(&self.elements[i]));
+                let edge = if to == HostMove::Top { blocks.next() } else { blocks.next_back() };
+                match edge {
+                    Some(i) if i == pos => return false,
+                    Some(i) if to == HostMove::Top => self.unit_start(i),
+                    Some(i) => i + 1,
+                    None => return false,
+                }
+            }
+        };
+        let before = self.elements.len();
+        let unit = self.take_unit(pos);
+        let target = if target > pos { target - (before - self.elements.len()) } else { target };
+        self.place_unit(target, unit);
+        self.invalidate_index();
+        true
+    }
+
+    /// Patterns of the wildcard Host blocks above `alias` that match it. With
+    /// OpenSSH's first-match rule their values win over the host's own.
+    pub fn wildcards_above(&self, alias: &str) -> Vec<String> {
+        let mut patterns = Vec::new();
+        for element in &self.elements {
+            match element {
+                ConfigElement::HostBlock(block)
+                | ConfigElement::DisabledHost(CommentedBlock { block, .. })
+                    if block.is_alias(alias) =>
+                {
+                    break;
+                }
+                ConfigElement::HostBlock(block)
+                    if is_host_pattern(&block.host_pattern)
+                        && match_pattern_list(alias, &block.patterns()) =>
+                {
+                    patterns.push(block.host_pattern.clone());
+                }
+                _ => {}
+            }
+        }
+        patterns
+    }
+
+    /// Move every top-level block's separator tail out into global lines, so
+    /// group headers and blank separators are elements of their own. The
+    /// serialized file doesn't change.
+    fn split_separators(&mut self) {
+        let mut elements = Vec::with_capacity(self.elements.len());
+        for mut element in std::mem::take(&mut self.elements) {
+            let tail = match &mut element {
+                ConfigElement::HostBlock(block) => {
+                    let start = tail_start(&block.directives);
+                    block.directives.split_off(start)
+                }
+                ConfigElement::MatchBlock(block) => {
+                    let start = tail_start(&block.directives);
+                    block.directives.split_off(start)
+                }
+                _ => Vec::new(),
+            };
+            elements.push(element);
+            elements.extend(tail.into_iter().map(|d| ConfigElement::GlobalLine(d.raw_line)));
+        }
+        self.elements = elements;
+        self.invalidate_index();
+    }
+
+    fn header_position(&self, name: &str) -> Option<usize> {
+        self.elements
+            .iter()
+            .position(|e| header_of(e).is_some_and(|h| h.eq_ignore_ascii_case(name)))
+    }
+
+    fn host_position(&self, alias: &str) -> Option<usize> {
+        self.elements.iter().position(|e| match e {
+            ConfigElement::HostBlock(block)
+            | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => block.is_alias(alias),
+            _ => false,
+        })
+    }
+
+    /// Element range of a group's body (after its header), or of the
+    /// ungrouped section above the first header. Needs split separators.
+    fn group_range(&self, group: Option<&str>) -> Option<Range<usize>> {
+        let next_header = |from: usize| {
+            self.elements[from..]
+                .iter()
+                .position(|e| header_of(e).is_some())
+                .map_or(self.elements.len(), |p| from + p)
+        };
+        match group {
+            None => Some(0..next_header(0)),
+            Some(name) => {
+                let start = self.header_position(name)? + 1;
+                Some(start..next_header(start))
+            }
+        }
+    }
+
+    /// First element of the host at `pos`, including the comments right above it.
+    fn unit_start(&self, pos: usize) -> usize {
+        let mut start = pos;
+        while start > 0 && is_leading_comment(&self.elements[start - 1]) {
+            start -= 1;
+        }
+        start
+    }
+
+    /// Remove the host at `pos` with its leading comments and tidy the blank
+    /// lines around the gap.
+    fn take_unit(&mut self, pos: usize) -> Vec<ConfigElement> {
+        let start = self.unit_start(pos);
+        let unit = self.elements.drain(start..=pos).collect();
+        self.close_gap(start);
+        unit
+    }
+
+    /// After removing elements at `at`: drop a blank line that is now doubled,
+    /// sits at the top or end of the file, or separates a header from its
+    /// first host.
+    fn close_gap(&mut self, at: usize) {
+        let len = self.elements.len();
+        if at == len {
+            if at > 0 && is_blank(&self.elements[at - 1]) {
+                self.elements.remove(at - 1);
+            }
+            return;
+        }
+        if !is_blank(&self.elements[at]) {
+            return;
+        }
+        let above = at.checked_sub(1).map(|i| &self.elements[i]);
+        let below = self.elements.get(at + 1);
+        let redundant = match above {
+            None => true,
+            Some(e) if is_blank(e) => true,
+            // Keep the blank line between a header and the next header
+            Some(e) if header_of(e).is_some() => below.is_some_and(|b| header_of(b).is_none()),
+            Some(_) => below.is_none(),
+        };
+        if redundant {
+            self.elements.remove(at);
+        }
+    }
+
+    /// Insert a host unit at the end of the section ending at `end`, above the
+    /// blank lines that separate it from the next header.
+    fn place_unit(&mut self, end: usize, unit: Vec<ConfigElement>) {
+        let mut at = end;
+        while at > 0 && is_blank(&self.elements[at - 1]) {
+            at -= 1;
+        }
+        let mut lines = Vec::with_capacity(unit.len() + 2);
+        // A host goes right below its header, like sync writes it
+        if at > 0 && header_of(&self.elements[at - 1]).is_none() {
+            lines.push(ConfigElement::GlobalLine(String::new()));
+        }
+        lines.extend(unit);
+        if self.elements.get(at).is_some_and(|e| !is_blank(e)) {
+            lines.push(ConfigElement::GlobalLine(String::new()));
+        }
+        self.elements.splice(at..at, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        let mut config = SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        };
+        config.refresh_index();
+        config
+    }
+
+    const CONFIG: &str = "\
+Host loose
+  HostName 10.0.0.1
+
+# purple:group Production
+Host web
+  HostName 10.0.1.1
+
+# the database
+Host db
+  HostName 10.0.1.2
+
+# purple:group Staging
+Host stage
+  HostName 10.0.2.1
+";
+
+    #[test]
+    fn groups_follow_headers_in_block_tails() {
+        let config = parse_str(CONFIG);
+        let groups = config.groups();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0], HostGroup { name: "Production".into(), hosts: vec!["web".into(), "db".into()] });
+        assert_eq!(groups[1].hosts, vec!["stage"]);
+        assert_eq!(config.host_group("loose"), None);
+        assert_eq!(config.host_group("db").as_deref(), Some("Production"));
+    }
+
+    #[test]
+    fn move_host_keeps_comments_and_separators() {
+        let mut config = parse_str(CONFIG);
+        assert!(config.move_host_to_group("db", Some("staging")));
+        assert_eq!(
+            config.serialize(),
+            "\
+Host loose
+  HostName 10.0.0.1
+
+# purple:group Production
+Host web
+  HostName 10.0.1.1
+
+# purple:group Staging
+Host stage
+  HostName 10.0.2.1
+
+# the database
+Host db
+  HostName 10.0.1.2
+"
+        );
+        assert!(!config.move_host_to_group("db", Some("Staging")));
+        assert!(config.move_host_to_group("web", None));
+        assert_eq!(
+            config.serialize(),
+            "\
+Host loose
+  HostName 10.0.0.1
+
+Host web
+  HostName 10.0.1.1
+
+# purple:group Production
+
+# purple:group Staging
+Host stage
+  HostName 10.0.2.1
+
+# the database
+Host db
+  HostName 10.0.1.2
+"
+        );
+        // Back into the now empty group: right below its header
+        assert!(config.move_host_to_group("web", Some("Production")));
+        assert!(config.serialize().contains("# purple:group Production\nHost web\n"));
+    }
+
+    #[test]
+    fn add_rename_delete_group() {
+        let mut config = parse_str(CONFIG);
+        assert!(config.add_group("Lab"));
+        assert!(!config.add_group("lab"));
+        assert!(config.serialize().ends_with("  HostName 10.0.2.1\n\n# purple:group Lab\n"));
+        assert!(config.rename_group("Staging", "QA"));
+        assert!(!config.rename_group("QA", "production"));
+        assert_eq!(config.host_group("stage").as_deref(), Some("QA"));
+
+        assert!(config.delete_group("QA"));
+        assert_eq!(config.host_group("stage").as_deref(), Some("Production"));
+        assert!(config.serialize().contains("  HostName 10.0.1.2\n\nHost stage\n"));
+        assert!(!config.delete_group("QA"));
+    }
+
+    #[test]
+    fn sort_group_reorders_hosts_only() {
+        let mut config = parse_str(CONFIG);
+        assert!(config.sort_group(Some("Production")));
+        assert_eq!(config.groups()[0].hosts, vec!["db", "web"]);
+        assert!(config.serialize().contains(
+            "# purple:group Production\n# the database\nHost db\n  HostName 10.0.1.2\n\nHost web\n  HostName 10.0.1.1\n\n# purple:group Staging\n"
+        ));
+        assert!(!config.sort_group(Some("Production")));
+    }
+
+    #[test]
+    fn add_host_to_group_creates_missing_header() {
+        let mut config = parse_str(CONFIG);
+        let entry = HostEntry { alias: "new".into(), hostname: "10.0.1.9".into(), ..Default::default() };
+        config.add_host_to_group(&entry, "production");
+        assert_eq!(config.groups()[0].hosts, vec!["web", "db", "new"]);
+
+        let entry = HostEntry { alias: "lab1".into(), hostname: "10.0.3.1".into(), ..Default::default() };
+        config.add_host_to_group(&entry, "Lab");
+        assert!(config.serialize().ends_with("\n\n# purple:group Lab\nHost lab1\n  HostName 10.0.3.1\n"));
+        assert!(config.has_host("lab1"));
+    }
+
+    #[test]
+    fn add_host_to_group_stays_above_catch_alls() {
+        let mut config = parse_str(
+            "# purple:group Web\nHost web1\n  HostName 10.0.1.1\n\n# defaults\nHost *\n  User deploy\n\nMatch user root\n  IdentityFile ~/.ssh/root\n",
+        );
+        let entry = HostEntry { alias: "web2".into(), hostname: "10.0.1.2".into(), ..Default::default() };
+        config.add_host_to_group(&entry, "web");
+        assert_eq!(
+            config.serialize(),
+            "# purple:group Web\nHost web1\n  HostName 10.0.1.1\n\nHost web2\n  HostName 10.0.1.2\n\n# defaults\nHost *\n  User deploy\n\nMatch user root\n  IdentityFile ~/.ssh/root\n"
+        );
+        assert!(config.wildcards_above("web2").is_empty());
+
+        // Patterns that don't match the alias aren't in the way
+        let mut config = parse_str("# purple:group Db\nHost db1\n  HostName 10.0.2.1\n\nHost web*\n  User www\n");
+        let entry = HostEntry { alias: "db2".into(), hostname: "10.0.2.2".into(), ..Default::default() };
+        config.add_host_to_group(&entry, "Db");
+        assert!(config.serialize().ends_with("Host web*\n  User www\n\nHost db2\n  HostName 10.0.2.2\n"));
+    }
+
+    #[test]
+    fn move_host_steps_across_headers() {
+        let mut config = parse_str(CONFIG);
+        assert!(config.move_host("db", HostMove::Up));
+        assert_eq!(config.groups()[0].hosts, vec!["db", "web"]);
+        assert!(config.move_host("db", HostMove::Up));
+        assert_eq!(config.host_group("db"), None);
+        assert_eq!(
+            config.serialize(),
+            "Host loose\n  HostName 10.0.0.1\n\n# the database\nHost db\n  HostName 10.0.1.2\n\n# purple:group Production\nHost web\n  HostName 10.0.1.1\n\n# purple:group Staging\nHost stage\n  HostName 10.0.2.1\n"
+        );
+        assert!(config.move_host("db", HostMove::Up));
+        assert!(config.serialize().starts_with("# the database\nHost db\n  HostName 10.0.1.2\n\nHost loose\n"));
+        assert!(!config.move_host("db", HostMove::Up));
+
+        // Down into a group lands right below its header
+        let mut config = parse_str(CONFIG);
+        assert!(config.move_host("stage", HostMove::Up));
+        assert_eq!(config.groups()[0].hosts, vec!["web", "db", "stage"]);
+        assert!(config.move_host("stage", HostMove::Down));
+        assert_eq!(config.serialize(), CONFIG);
+        assert!(!config.move_host("stage", HostMove::Down));
+    }
+
+    #[test]
+    fn move_host_to_top_and_bottom_of_group() {
+        let mut config = parse_str(CONFIG);
+        assert!(config.move_host("db", HostMove::Top));
+        assert!(config.serialize().contains(
+            "# purple:group Production\n# the database\nHost db\n  HostName 10.0.1.2\n\nHost web\n  HostName 10.0.1.1\n\n# purple:group Staging\n"
+        ));
+        assert!(!config.move_host("db", HostMove::Top));
+        assert!(config.move_host("db", HostMove::Bottom));
+        assert_eq!(config.serialize(), CONFIG);
+        assert!(!config.move_host("db", HostMove::Bottom));
+        assert!(!config.move_host("missing", HostMove::Up));
+    }
+
+    #[test]
+    fn move_host_stops_at_top_level_directives() {
+        let mut config = parse_str("User admin\n\nHost a\n  HostName 1\n\nHost *\n  User root\n");
+        assert!(!config.move_host("a", HostMove::Up));
+        assert!(config.wildcards_above("a").is_empty());
+        assert!(config.move_host("a", HostMove::Down));
+        assert_eq!(config.wildcards_above("a"), vec!["*"]);
+        assert_eq!(config.serialize(), "User admin\n\nHost *\n  User root\n\nHost a\n  HostName 1\n");
+    }
+}
@@ -0,0 +1,377 @@
+//! Alias and provider lookups for large configs.
+//!
+//! Walking every element (and every Include) per lookup is fine for a few
+//! dozen hosts but stalls the TUI with thousands of synced ones. The index
+//! maps each Host pattern and each provider marker to the block's position.
+
+use std::collections::HashMap;
+
+use super::model::{CommentedBlock, ConfigElement, HostBlock, SshConfigFile};
+
+/// Position of a block in the element tree: its index in the main config,
+/// then a (resolved file, element) index pair per Include on the way.
+pub(super) type ElementPath = Vec<usize>;
+
+/// Index from Host pattern and provider id to block position. Built by
+/// `refresh_index`, kept current by appends and alias or provider edits, and
+/// dropped (`invalidate_index`) by every other mutator that adds, removes or
+/// replaces elements. Without an index lookups walk the elements.
+#[derive(Debug, Clone, Default)]
+pub struct HostIndex(Option<IndexData>);
+
+#[derive(Debug, Clone, Default)]
+struct IndexData {
+    /// Host pattern -> Host and disabled blocks using it, in file order.
+    aliases: HashMap<String, Vec<ElementPath>>,
+    /// (provider, server id) -> blocks with that marker, in file order.
+    providers: HashMap<(String, String), Vec<ElementPath>>,
+}
+
+impl IndexData {
+    fn build(elements: &[ConfigElement]) -> Self {
+        let mut data = IndexData::default();
+        let mut path = Vec::new();
+        for (i, element) in elements.iter().enumerate() {
+            path.push(i);
+            data.add(element, &mut path);
+            path.pop();
+        }
+        data
+    }
+
+    /// Index `element` (at `path`) and, for an Include, every file under it.
+    fn add(&mut self, element: &ConfigElement, path: &mut ElementPath) {
+        match element {
+            ConfigElement::HostBlock(block)
+            | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => self.insert(path, block),
+            ConfigElement::Include(include) => {
+                for (f, file) in include.resolved_files.iter().enumerate() {
+                    path.push(f);
+                    for (i, element) in file.elements.iter().enumerate() {
+                        path.push(i);
+                        self.add(element, path);
+                        path.pop();
+                    }
+                    path.pop();
+                }
+            }
+            ConfigElement::GlobalLine(_) | ConfigElement::MatchBlock(_) | ConfigElement::Template(_) => {}
+        }
+    }
+
+    fn insert(&mut self, path: &[usize], block: &HostBlock) {
+        for key in alias_keys(block) {
+            insert_sorted(self.aliases.entry(key).or_default(), path);
+        }
+        if let Some(id) = block.provider() {
+            insert_sorted(self.providers.entry(id).or_default(), path);
+        }
+    }
+
+    fn remove(&mut self, path: &[usize], block: &HostBlock) {
+        for key in alias_keys(block) {
+            if let Some(paths) = self.aliases.get_mut(&key) {
+                paths.retain(|p| p != path);
+                if paths.is_empty() {
+                    self.aliases.remove(&key);
+                }
+            }
+        }
+        if let Some(id) = block.provider() {
+            if let Some(paths) = self.providers.get_mut(&id) {
+                paths.retain(|p| p != path);
+                if paths.is_empty() {
+                    self.providers.remove(&id);
+                }
+            }
+        }
+    }
+}
+
+/// Keys a block is found under: every pattern, plus the Host line as
+/// written when it differs (multi-pattern or quoted). `HostBlock::alias` is
+/// always one of them.
+fn alias_keys(block: &HostBlock) -> Vec<String> {
+    let mut keys = block.patterns();
+    if !keys.contains(&block.host_pattern) {
+        keys.push(block.host_pattern.clone());
+    }
+    keys
+}
+
+fn insert_sorted(paths: &mut Vec<ElementPath>, path: &[usize]) {
+    if let Err(at) = paths.binary_search_by(|p| p.as_slice().cmp(path)) {
+        paths.insert(at, path.to_vec());
+    }
+}
+
+/// The block of a Host or disabled host element.
+pub(super) fn host_block(element: &ConfigElement) -> Option<&HostBlock> {
+    match element {
+        ConfigElement::HostBlock(block)
+        | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => Some(block),
+        _ => None,
+    }
+}
+
+pub(super) fn element_at<'a>(elements: &'a [ConfigElement], path: &[usize]) -> Option<&'a ConfigElement> {
+    let (&first, rest) = path.split_first()?;
+    let element = elements.get(first)?;
+    match (rest, element) {
+        ([], _) => Some(element),
+        ([file, rest @ ..], ConfigElement::Include(include)) => {
+            element_at(&include.resolved_files.get(*file)?.elements, rest)
+        }
+        _ => None,
+    }
+}
+
+fn element_at_mut<'a>(elements: &'a mut [ConfigElement], path: &[usize]) -> Option<&'a mut ConfigElement> {
+    let (&first, rest) = path.split_first()?;
+    let element = elements.get_mut(first)?;
+    match (rest, element) {
+        ([], element) => Some(element),
+        ([file, rest @ ..], ConfigElement::Include(include)) => {
+            element_at_mut(&mut include.resolved_files.get_mut(*file)?.elements, rest)
+        }
+        _ => None,
+    }
+}
+
+/// Walk the elements in document order (Include files inlined) for the
+/// first element matching `pred`, leaving its position in `path`.
+fn find_path(elements: &[ConfigElement], pred: &dyn Fn(&ConfigElement) -> bool, path: &mut ElementPath) -> bool {
+    for (i, element) in elements.iter().enumerate() {
+        path.push(i);
+        if pred(element) {
+            return true;
+        }
+        if let ConfigElement::Include(include) = element {
+            for (f, file) in include.resolved_files.iter().enumerate() {
+                path.push(f);
+                if find_path(&file.elements, pred, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+        path.pop();
+    }
+    false
+}
+
+impl SshConfigFile {
+    /// Build the alias and provider index unless it is already current.
+    pub fn refresh_index(&mut self) {
+        if self.index.0.is_none() {
+            self.index.0 = Some(IndexData::build(&self.elements));
+        }
+    }
+
+    /// Drop the index after adding, removing or replacing elements other
+    /// than through `push_element`, `add_host` or `edit_block`. Lookups walk
+    /// the elements until the next `refresh_index`.
+    pub fn invalidate_index(&mut self) {
+        self.index.0 = None;
+    }
+
+    fn current_index(&self) -> Option<&IndexData> {
+        self.index.0.as_ref()
+    }
+
+    /// Positions of the blocks with pattern or alias `key`, in file order.
+    /// None when there is no current index and the caller has to walk.
+    pub(super) fn indexed_aliases(&self, key: &str) -> Option<&[ElementPath]> {
+        let data = self.current_index()?;
+        Some(data.aliases.get(key).map_or(&[], Vec::as_slice))
+    }
+
+    /// Positions of every block with a `provider_name` marker and their
+    /// server ids, in file order. None when there is no current index.
+    pub(super) fn indexed_provider(&self, provider_name: &str) -> Option<Vec<(&ElementPath, &str)>> {
+        let data = self.current_index()?;
+        let mut found: Vec<(&ElementPath, &str)> = data
+            .providers
+            .iter()
+            .filter(|((name, _), _)| name == provider_name)
+            .flat_map(|((_, id), paths)| paths.iter().map(move |p| (p, id.as_str())))
+            .collect();
+        found.sort();
+        Some(found)
+    }
+
+    /// Position of the first enabled Host block known as `key` that passes
+    /// `pred`. `pred` must only accept blocks that have `key` as a pattern
+    /// or alias.
+    pub(super) fn block_path(&self, key: &str, pred: &dyn Fn(&HostBlock) -> bool) -> Option<ElementPath> {
+        let is_match = |e: &ConfigElement| matches!(e, ConfigElement::HostBlock(b) if pred(b));
+        match self.indexed_aliases(key) {
+            Some(paths) => paths
+                .iter()
+                .find(|p| element_at(&self.elements, p).is_some_and(is_match))
+                .cloned(),
+            None => {
+                let mut path = Vec::new();
+                find_path(&self.elements, &is_match, &mut path).then_some(path)
+            }
+        }
+    }
+
+    /// The first enabled Host block known as `key` that passes `pred`.
+    pub(super) fn find_block(&self, key: &str, pred: &dyn Fn(&HostBlock) -> bool) -> Option<&HostBlock> {
+        let path = self.block_path(key, pred)?;
+        match element_at(&self.elements, &path) {
+            Some(ConfigElement::HostBlock(block)) => Some(block),
+            _ => None,
+        }
+    }
+
+    /// Mutable `find_block`. Edits through it must leave the alias and
+    /// provider alone; use `edit_block` for those.
+    pub(super) fn find_block_mut(&mut self, key: &str, pred: &dyn Fn(&HostBlock) -> bool) -> Option<&mut HostBlock> {
+        let path = self.block_path(key, pred)?;
+        self.block_at_mut(&path)
+    }
+
+    /// The enabled Host block at `path`. Same rule as `find_block_mut`.
+    pub(super) fn block_at_mut(&mut self, path: &[usize]) -> Option<&mut HostBlock> {
+        match element_at_mut(&mut self.elements, path) {
+            Some(ConfigElement::HostBlock(block)) => Some(block),
+            _ => None,
+        }
+    }
+
+    /// Edit the Host block at `path`, moving its index entries along with
+    /// any alias or provider change.
+    pub(super) fn edit_block(&mut self, path: &[usize], edit: impl FnOnce(&mut HostBlock)) {
+        let mut data = self.index.0.as_mut();
+        let Some(ConfigElement::HostBlock(block)) = element_at_mut(&mut self.elements, path) else {
+            return;
+        };
+        if let Some(data) = data.as_mut() {
+            data.remove(path, block);
+        }
+        edit(block);
+        if let Some(data) = data {
+            data.insert(path, block);
+        }
+    }
+
+    /// Index elements appended to the main config after it had `old_len`.
+    pub(super) fn index_appended(&mut self, old_len: usize) {
+        let Some(data) = self.index.0.as_mut() else {
+            return;
+        };
+        for (i, element) in self.elements.iter().enumerate().skip(old_len) {
+            data.add(element, &mut vec![i]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ssh_config::model::HostEntry;
+    use std::path::PathBuf;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        let mut config = SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: HostIndex::default(),
+        };
+        config.refresh_index();
+        config
+    }
+
+    /// The index must answer exactly like a walk over the elements.
+    fn assert_matches_walk(config: &SshConfigFile) {
+        let mut walked = config.clone();
+        walked.invalidate_index();
+        for entry in walked.host_entries() {
+            assert!(config.has_host(&entry.alias), "{} missing", entry.alias);
+            assert_eq!(
+                config.block_path(&entry.alias, &|b| b.is_alias(&entry.alias)),
+                walked.block_path(&entry.alias, &|b| b.is_alias(&entry.alias)),
+                "{}",
+                entry.alias
+            );
+        }
+        for provider in ["digitalocean", "hetzner"] {
+            assert_eq!(config.find_hosts_by_provider(provider), walked.find_hosts_by_provider(provider));
+        }
+        assert!(!config.has_host("nope"));
+    }
+
+    #[test]
+    fn index_follows_edits() {
+        let mut config = parse_str(
+            "Host a\n  HostName 1.1.1.1\n  # purple:provider digitalocean:1\n\nHost b c\n  HostName 2.2.2.2\n\nHost \"d e\"\n  # purple:provider hetzner:9\n",
+        );
+        assert_matches_walk(&config);
+
+        config.add_host(&HostEntry {
+            alias: "new".to_string(),
+            hostname: "3.3.3.3".to_string(),
+            ..Default::default()
+        });
+        config.set_host_provider("new", "digitalocean", "2");
+        assert_matches_walk(&config);
+
+        let entry = HostEntry {
+            alias: "renamed".to_string(),
+            hostname: "1.1.1.1".to_string(),
+            ..Default::default()
+        };
+        config.update_host("a", &entry);
+        assert!(!config.has_host("a"));
+        assert_matches_walk(&config);
+        assert_eq!(
+            config.find_hosts_by_provider("digitalocean"),
+            vec![("renamed".to_string(), "1".to_string()), ("new".to_string(), "2".to_string())]
+        );
+
+        // Shifting edits drop the index; lookups still work and refresh rebuilds it
+        config.delete_host("b c");
+        assert!(config.current_index().is_none());
+        assert!(config.has_host("new"));
+        config.refresh_index();
+        assert_matches_walk(&config);
+
+        // Appended elements are indexed as they go in
+        config.push_element(ConfigElement::HostBlock(SshConfigFile::entry_to_block(&HostEntry {
+            alias: "pushed".to_string(),
+            ..Default::default()
+        })));
+        assert!(config.current_index().is_some());
+        assert!(config.has_host("pushed"));
+        assert_matches_walk(&config);
+    }
+
+    #[test]
+    fn index_never_outlives_a_mutation() {
+        let mut config = parse_str(
+            "Host a\n  HostName 1.1.1.1\n  # purple:provider digitalocean:1\n\nHost b\n  HostName 2.2.2.2\n\nHost c\n  HostName 3.3.3.3\n",
+        );
+        // Each step keeps the element count or restores it, which the old
+        // length check took for an unchanged config
+        config.delete_host_undoable("a").unwrap();
+        config.add_host(&HostEntry {
+            alias: "a".to_string(),
+            ..Default::default()
+        });
+        assert!(config.has_host("a"));
+        assert_matches_walk(&config);
+
+        assert!(config.disable_host("b"));
+        assert_matches_walk(&config);
+        assert!(config.enable_host("b"));
+        assert_matches_walk(&config);
+        assert!(config.swap_hosts("a", "c"));
+        assert_matches_walk(&config);
+        let clone = config.clone_host("c").unwrap();
+        assert!(config.has_host(&clone));
+        assert_matches_walk(&config);
+    }
+}
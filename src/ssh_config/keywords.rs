@@ -0,0 +1,370 @@
+use ValueKind::{Choice, Duration, Number, Port, Text, YesNo, YesNoOrDuration};
+
+/// What kind of value a directive accepts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ValueKind {
+    /// `yes` or `no`.
+    YesNo,
+    /// One of a fixed set of words (case-insensitive).
+    Choice(&'static [&'static str]),
+    /// A time value: seconds or a sequence like `1h30m` (s, m, h, d, w suffixes).
+    Duration,
+    /// `yes`, `no` or a duration (ControlPersist).
+    YesNoOrDuration,
+    /// A TCP port (1-65535).
+    Port,
+    /// A non-negative integer.
+    Number,
+    /// Free-form text.
+    Text,
+}
+
+impl ValueKind {
+    /// Short hint shown next to the value input.
+    pub fn hint(&self) -> String {
+        match self {
+            YesNo => "yes | no".to_string(),
+            Choice(options) => options.join(" | "),
+            Duration => "duration, e.g. 30 or 5m".to_string(),
+            YesNoOrDuration => "yes | no | duration".to_string(),
+            Port => "port 1-65535".to_string(),
+            Number => "number".to_string(),
+            Text => "text".to_string(),
+        }
+    }
+}
+
+/// A known OpenSSH client directive.
+#[derive(Debug, Clone, Copy)]
+pub struct Keyword {
+    /// Canonical spelling as in ssh_config(5).
+    pub name: &'static str,
+    pub kind: ValueKind,
+}
+
+const fn kw(name: &'static str, kind: ValueKind) -> Keyword {
+    Keyword { name, kind }
+}
+
+/// OpenSSH client directives (ssh_config(5)), sorted alphabetically.
+/// Host and Match are block headers and not listed.
+pub const KEYWORDS: &[Keyword] = &[
+    kw("AddKeysToAgent", Text),
+    kw(
+        "AddressFamily",
+        Choice(&["any", "inet", "inet6"]),
+    ),
+    kw("BatchMode", YesNo),
+    kw("BindAddress", Text),
+    kw("BindInterface", Text),
+    kw("CanonicalDomains", Text),
+    kw("CanonicalizeFallbackLocal", YesNo),
+    kw(
+        "CanonicalizeHostname",
+        Choice(&["yes", "no", "always", "none"]),
+    ),
+    kw("CanonicalizeMaxDots", Number),
+    kw("CanonicalizePermittedCNAMEs", Text),
+    kw("CASignatureAlgorithms", Text),
+    kw("CertificateFile", Text),
+    kw("ChannelTimeout", Text),
+    kw("CheckHostIP", YesNo),
+    kw("Ciphers", Text),
+    kw("ClearAllForwardings", YesNo),
+    kw("Compression", YesNo),
+    kw("ConnectionAttempts", Number),
+    kw("ConnectTimeout", Duration),
+    kw(
+        "ControlMaster",
+        Choice(&["yes", "no", "ask", "auto", "autoask"]),
+    ),
+    kw("ControlPath", Text),
+    kw("ControlPersist", YesNoOrDuration),
+    kw("DynamicForward", Text),
+    kw("EnableEscapeCommandline", YesNo),
+    kw("EnableSSHKeysign", YesNo),
+    kw("EscapeChar", Text),
+    kw("ExitOnForwardFailure", YesNo),
+    kw("FingerprintHash", Choice(&["md5", "sha256"])),
+    kw("ForkAfterAuthentication", YesNo),
+    kw("ForwardAgent", Text),
+    kw("ForwardX11", YesNo),
+    kw("ForwardX11Timeout", Duration),
+    kw("ForwardX11Trusted", YesNo),
+    kw("GatewayPorts", YesNo),
+    kw("GlobalKnownHostsFile", Text),
+    kw("GSSAPIAuthentication", YesNo),
+    kw("GSSAPIDelegateCredentials", YesNo),
+    kw("HashKnownHosts", YesNo),
+    kw("HostbasedAcceptedAlgorithms", Text),
+    kw("HostbasedAuthentication", YesNo),
+    kw("HostKeyAlgorithms", Text),
+    kw("HostKeyAlias", Text),
+    kw("HostName", Text),
+    kw("IdentitiesOnly", YesNo),
+    kw("IdentityAgent", Text),
+    kw("IdentityFile", Text),
+    kw("IgnoreUnknown", Text),
+    kw("Include", Text),
+    kw("IPQoS", Text),
+    kw("KbdInteractiveAuthentication", YesNo),
+    kw("KbdInteractiveDevices", Text),
+    kw("KexAlgorithms", Text),
+    kw("KnownHostsCommand", Text),
+    kw("LocalCommand", Text),
+    kw("LocalForward", Text),
+    kw(
+        "LogLevel",
+        Choice(&[
+            "QUIET", "FATAL", "ERROR", "INFO", "VERBOSE", "DEBUG", "DEBUG1", "DEBUG2", "DEBUG3",
+        ]),
+    ),
+    kw("LogVerbose", Text),
+    kw("MACs", Text),
+    kw("NoHostAuthenticationForLocalhost", YesNo),
+    kw("NumberOfPasswordPrompts", Number),
+    kw("ObscureKeystrokeTiming", Text),
+    kw("PasswordAuthentication", YesNo),
+    kw("PermitLocalCommand", YesNo),
+    kw("PermitRemoteOpen", Text),
+    kw("PKCS11Provider", Text),
+    kw("Port", Port),
+    kw("PreferredAuthentications", Text),
+    kw("ProxyCommand", Text),
+    kw("ProxyJump", Text),
+    kw("ProxyUseFdpass", YesNo),
+    kw("PubkeyAcceptedAlgorithms", Text),
+    kw(
+        "PubkeyAuthentication",
+        Choice(&["yes", "no", "unbound", "host-bound"]),
+    ),
+    kw("RekeyLimit", Text),
+    kw("RemoteCommand", Text),
+    kw("RemoteForward", Text),
+    kw(
+        "RequestTTY",
+        Choice(&["yes", "no", "force", "auto"]),
+    ),
+    kw("RequiredRSASize", Number),
+    kw("RevokedHostKeys", Text),
+    kw("SecurityKeyProvider", Text),
+    kw("SendEnv", Text),
+    kw("ServerAliveCountMax", Number),
+    kw("ServerAliveInterval", Duration),
+    kw(
+        "SessionType",
+        Choice(&["none", "subsystem", "default"]),
+    ),
+    kw("SetEnv", Text),
+    kw("StdinNull", YesNo),
+    kw("StreamLocalBindMask", Text),
+    kw("StreamLocalBindUnlink", YesNo),
+    kw(
+        "StrictHostKeyChecking",
+        Choice(&["yes", "no", "ask", "accept-new", "off"]),
+    ),
+    kw("SyslogFacility", Text),
+    kw("Tag", Text),
+    kw("TCPKeepAlive", YesNo),
+    kw(
+        "Tunnel",
+        Choice(&["yes", "no", "point-to-point", "ethernet"]),
+    ),
+    kw("TunnelDevice", Text),
+    kw("UpdateHostKeys", Choice(&["yes", "no", "ask"])),
+    kw("UseKeychain", YesNo),
+    kw("User", Text),
+    kw("UserKnownHostsFile", Text),
+    kw("VerifyHostKeyDNS", Choice(&["yes", "no", "ask"])),
+    kw("VisualHostKey", YesNo),
+    kw("XAuthLocation", Text),
+];
+
+/// Look up a directive by name (case-insensitive).
+pub fn lookup(name: &str) -> Option<&'static Keyword> {
+    KEYWORDS.iter().find(|k| k.name.eq_ignore_ascii_case(name))
+}
+
+/// Keywords starting with `prefix` (case-insensitive), in table order.
+pub fn complete(prefix: &str) -> Vec<&'static str> {
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let lower = prefix.to_ascii_lowercase();
+    KEYWORDS
+        .iter()
+        .filter(|k| k.name.to_ascii_lowercase().starts_with(&lower))
+        .map(|k| k.name)
+        .collect()
+}
+
+/// Closest known keyword for a misspelled name (edit distance of at most 2).
+/// Returns None for exact (case-insensitive) matches and for names too far off.
+pub fn suggest(name: &str) -> Option<&'static str> {
+    if lookup(name).is_some() {
+        return None;
+    }
+    let lower = name.to_ascii_lowercase();
+    KEYWORDS
+        .iter()
+        .map(|k| (k.name, edit_distance(&lower, &k.name.to_ascii_lowercase())))
+        .filter(|(_, d)| *d <= 2)
+        .min_by_key(|(_, d)| *d)
+        .map(|(name, _)| name)
+}
+
+/// Validate a value against the keyword's kind. Unknown keywords only
+/// require a non-empty value.
+pub fn validate_value(name: &str, value: &str) -> Result<(), String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(format!("{} needs a value.", name));
+    }
+    if value.chars().any(|c| c.is_control()) {
+        return Err(format!("{} contains control characters.", name));
+    }
+    let Some(keyword) = lookup(name) else {
+        return Ok(());
+    };
+    let ok = match keyword.kind {
+        YesNo => is_yes_no(value),
+        Choice(options) => options.iter().any(|o| o.eq_ignore_ascii_case(value)),
+        Duration => is_duration(value),
+        YesNoOrDuration => is_yes_no(value) || is_duration(value),
+        Port => value.parse::<u16>().is_ok_and(|p| p > 0),
+        Number => value.parse::<u32>().is_ok(),
+        Text => true,
+    };
+    if ok {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} expects {}, got '{}'.",
+            keyword.name,
+            keyword.kind.hint(),
+            value
+        ))
+    }
+}
+
+fn is_yes_no(value: &str) -> bool {
+    value.eq_ignore_ascii_case("yes") || value.eq_ignore_ascii_case("no")
+}
+
+/// sshd time format: plain seconds or number+unit groups (e.g. "90", "1h30m").
+fn is_duration(value: &str) -> bool {
+    let mut digits = false;
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits = true;
+        } else if matches!(c.to_ascii_lowercase(), 's' | 'm' | 'h' | 'd' | 'w') && digits {
+            digits = false;
+        } else {
+            return false;
+        }
+    }
+    // Every unit followed digits; an empty string is not a duration
+    !value.is_empty()
+}
+
+/// Levenshtein distance between two ASCII-lowercased strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_is_sorted_case_insensitively() {
+        let names: Vec<String> = KEYWORDS
+            .iter()
+            .map(|k| k.name.to_ascii_lowercase())
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort();
+        assert_eq!(names, sorted);
+    }
+
+    #[test]
+    fn lookup_is_case_insensitive() {
+        assert_eq!(lookup("forwardagent").unwrap().name, "ForwardAgent");
+        assert_eq!(
+            lookup("SERVERALIVEINTERVAL").unwrap().kind,
+            Duration
+        );
+        assert!(lookup("NotADirective").is_none());
+    }
+
+    #[test]
+    fn complete_matches_prefix() {
+        assert_eq!(
+            complete("serveral"),
+            vec!["ServerAliveCountMax", "ServerAliveInterval"]
+        );
+        assert!(complete("").is_empty());
+        assert!(complete("zzz").is_empty());
+    }
+
+    #[test]
+    fn suggest_finds_misspellings() {
+        assert_eq!(suggest("ForwardAgnet"), Some("ForwardAgent"));
+        assert_eq!(suggest("Hostnme"), Some("HostName"));
+        assert_eq!(suggest("ForwardAgent"), None);
+        assert_eq!(suggest("CompletelyDifferent"), None);
+    }
+
+    #[test]
+    fn validate_yes_no() {
+        assert!(validate_value("ForwardX11", "yes").is_ok());
+        assert!(validate_value("ForwardX11", "No").is_ok());
+        let err = validate_value("ForwardX11", "maybe").unwrap_err();
+        assert!(err.contains("yes | no"), "got: {}", err);
+    }
+
+    #[test]
+    fn validate_choice() {
+        assert!(validate_value("RequestTTY", "force").is_ok());
+        assert!(validate_value("StrictHostKeyChecking", "accept-new").is_ok());
+        assert!(validate_value("LogLevel", "debug2").is_ok());
+        assert!(validate_value("RequestTTY", "sometimes").is_err());
+    }
+
+    #[test]
+    fn validate_duration() {
+        assert!(validate_value("ServerAliveInterval", "60").is_ok());
+        assert!(validate_value("ConnectTimeout", "1m30s").is_ok());
+        assert!(validate_value("ServerAliveInterval", "5min").is_err());
+        assert!(validate_value("ServerAliveInterval", "m").is_err());
+        assert!(validate_value("ControlPersist", "yes").is_ok());
+        assert!(validate_value("ControlPersist", "10m").is_ok());
+        assert!(validate_value("ControlPersist", "forever").is_err());
+    }
+
+    #[test]
+    fn validate_port_and_number() {
+        assert!(validate_value("Port", "2222").is_ok());
+        assert!(validate_value("Port", "0").is_err());
+        assert!(validate_value("Port", "70000").is_err());
+        assert!(validate_value("ServerAliveCountMax", "3").is_ok());
+        assert!(validate_value("ServerAliveCountMax", "-1").is_err());
+    }
+
+    #[test]
+    fn validate_empty_and_unknown() {
+        assert!(validate_value("SetEnv", "  ").is_err());
+        assert!(validate_value("SetEnv", "FOO=bar").is_ok());
+        assert!(validate_value("SomethingCustom", "anything").is_ok());
+    }
+}
@@ -0,0 +1,712 @@
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::keywords;
+use super::model::{
+    ConfigElement, Directive, SshConfigFile, is_host_pattern, is_managed_key, is_plain_word, split_patterns,
+    unquote,
+};
+use super::resolve::{MULTI_VALUE_KEYS, match_pattern, match_pattern_list};
+use super::topology::{JumpGraph, jump_alias, proxy_command_hops};
+
+/// Characters in a Host alias that break purple's own bookkeeping:
+/// `#` starts a comment (and a `# purple:` marker), `/` ends up in the askpass
+/// marker file name, quotes confuse the parser.
+const UNSAFE_ALIAS_CHARS: &[char] = &['#', '/', '"', '\''];
+
+/// What a lint finding is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintKind {
+    DuplicateAlias,
+    ShadowedHost,
+    UnknownKey,
+    MissingIdentityFile,
+    UnresolvedProxyJump,
+    ProxyJumpCycle,
+    InvalidPort,
+    UnsafeAlias,
+}
+
+/// A single problem found by `SshConfigFile::lint`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub kind: LintKind,
+    /// Host pattern of the block the finding belongs to. None for Match blocks
+    /// and global directives.
+    pub alias: Option<String>,
+    /// Block header ("Host web", "Match user root") or "global".
+    pub block: String,
+    /// File the block lives in (the main config or an Include file).
+    pub file: PathBuf,
+    pub message: String,
+}
+
+/// The block a finding belongs to. Formatted only when there is a finding,
+/// so clean configs don't pay for a header string per block.
+#[derive(Clone, Copy)]
+enum Header<'a> {
+    Host(&'a str),
+    Match(&'a str),
+    Global,
+}
+
+impl fmt::Display for Header<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Header::Host(pattern) => write!(f, "Host {}", pattern),
+            Header::Match(criteria) => write!(f, "Match {}", criteria),
+            Header::Global => f.write_str("global"),
+        }
+    }
+}
+
+/// A block flattened out of the element tree (Include files inlined in order).
+enum Item<'a> {
+    Host {
+        pattern: &'a str,
+        directives: &'a [Directive],
+        file: &'a Path,
+    },
+    Match {
+        criteria: &'a str,
+        directives: &'a [Directive],
+        file: &'a Path,
+    },
+    Global {
+        line: &'a str,
+        file: &'a Path,
+    },
+}
+
+impl SshConfigFile {
+    /// Check the config (including Include files) for common mistakes.
+    /// Findings are returned in file order.
+    pub fn lint(&self) -> Vec<Finding> {
+        self.lint_with_graph(&self.jump_graph())
+    }
+
+    /// `lint` for callers that already built the jump graph.
+    pub fn lint_with_graph(&self, graph: &JumpGraph) -> Vec<Finding> {
+        let mut items = Vec::new();
+        flatten(&self.elements, &self.path, &mut items);
+
+        // The wildcard blocks, the only ones that can shadow a host
+        let wildcards: Vec<Wildcard> = items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| match item {
+                Item::Host {
+                    pattern,
+                    directives,
+                    ..
+                } if is_host_pattern(pattern) => Some(Wildcard {
+                    index,
+                    pattern,
+                    tokens: split_patterns(pattern),
+                    directives,
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let mut linter = Linter {
+            graph,
+            ignored: Vec::new(),
+            unknown: Vec::new(),
+            known: HashSet::new(),
+            identities: HashMap::new(),
+            services: None,
+            first_seen: HashMap::new(),
+            findings: Vec::new(),
+        };
+        for (index, item) in items.iter().enumerate() {
+            match item {
+                Item::Host {
+                    pattern,
+                    directives,
+                    file,
+                } => {
+                    let block = Header::Host(pattern);
+                    let alias = if is_plain_word(pattern) {
+                        Cow::Borrowed(*pattern)
+                    } else {
+                        Cow::Owned(unquote(pattern))
+                    };
+                    linter.check_aliases(pattern, &alias, block, file);
+                    let earlier = wildcards.partition_point(|w| w.index < index);
+                    if wildcards.get(earlier).is_none_or(|w| w.index != index) {
+                        linter.check_shadowed(&wildcards[..earlier], &alias, directives, block, file);
+                        linter.check_cycle(&alias, block, file);
+                    }
+                    for d in directives.iter().filter(|d| !d.is_non_directive) {
+                        linter.check_directive(&d.key, &d.value, Some(&alias), block, file);
+                    }
+                }
+                Item::Match {
+                    criteria,
+                    directives,
+                    file,
+                } => {
+                    let block = Header::Match(criteria);
+                    for d in directives.iter().filter(|d| !d.is_non_directive) {
+                        linter.check_directive(&d.key, &d.value, None, block, file);
+                    }
+                }
+                Item::Global { line, file } => {
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+                    if let Some((key, value)) = SshConfigFile::parse_directive(trimmed) {
+                        linter.check_directive(&key, &value, None, Header::Global, file);
+                    }
+                }
+            }
+        }
+        linter.finish()
+    }
+}
+
+fn flatten<'a>(elements: &'a [ConfigElement], file: &'a Path, items: &mut Vec<Item<'a>>) {
+    for element in elements {
+        match element {
+            ConfigElement::HostBlock(block) => items.push(Item::Host {
+                pattern: &block.host_pattern,
+                directives: &block.directives,
+                file,
+            }),
+            ConfigElement::MatchBlock(block) => items.push(Item::Match {
+                criteria: &block.criteria_text,
+                directives: &block.directives,
+                file,
+            }),
+            ConfigElement::GlobalLine(line) => items.push(Item::Global { line, file }),
+            // ssh skips commented-out hosts, so there's nothing to check
+            ConfigElement::DisabledHost(_) | ConfigElement::Template(_) => {}
+            ConfigElement::Include(include) => {
+                for included in &include.resolved_files {
+                    flatten(&included.elements, &included.path, items);
+                }
+            }
+        }
+    }
+}
+
+/// A wildcard Host block, with its position in the flattened items.
+struct Wildcard<'a> {
+    index: usize,
+    pattern: &'a str,
+    tokens: Vec<String>,
+    directives: &'a [Directive],
+}
+
+struct Linter<'a> {
+    /// For jump targets and cycles.
+    graph: &'a JumpGraph,
+    /// Patterns from the IgnoreUnknown directives seen so far.
+    ignored: Vec<String>,
+    /// Unknown key findings (index, key). IgnoreUnknown covers keys before
+    /// it too, so they are only dropped once every pattern is known.
+    unknown: Vec<(usize, String)>,
+    /// Keys (as written) already found in the keyword table.
+    known: HashSet<String>,
+    /// IdentityFile values already checked, and whether the file exists.
+    /// Synced hosts tend to share a key, so each path is stat'ed once.
+    identities: HashMap<String, bool>,
+    /// TCP service names from /etc/services, read on the first Port that
+    /// isn't a number. Some(None) when the file can't be read.
+    services: Option<Option<HashSet<String>>>,
+    /// Alias -> file it was first defined in. Left empty when the graph
+    /// saw no name twice.
+    first_seen: HashMap<Cow<'a, str>, &'a Path>,
+    findings: Vec<Finding>,
+}
+
+impl<'a> Linter<'a> {
+    fn push(&mut self, kind: LintKind, alias: Option<&str>, block: Header, file: &Path, message: String) {
+        self.findings.push(Finding {
+            kind,
+            alias: alias.map(str::to_string),
+            block: block.to_string(),
+            file: file.to_path_buf(),
+            message,
+        });
+    }
+
+    /// Whether `name` is a TCP service ssh can resolve. Anything goes when
+    /// /etc/services can't be read.
+    fn is_service(&mut self, name: &str) -> bool {
+        self.services
+            .get_or_insert_with(|| fs::read_to_string("/etc/services").ok().map(|c| tcp_services(&c)))
+            .as_ref()
+            .is_none_or(|services| services.contains(name))
+    }
+
+    /// `pattern` is the Host value as written, `alias` the unquoted one.
+    fn check_aliases(&mut self, pattern: &'a str, alias: &str, block: Header, file: &'a Path) {
+        if !pattern.is_empty() && is_plain_word(pattern) {
+            // A plain word is its own only token, no need to split or copy it
+            self.check_alias(Cow::Borrowed(pattern), pattern, block, file);
+        } else {
+            for token in split_patterns(alias) {
+                self.check_alias(Cow::Owned(token), alias, block, file);
+            }
+        }
+    }
+
+    fn check_alias(&mut self, token: Cow<'a, str>, pattern: &str, block: Header, file: &'a Path) {
+        let name = token.strip_prefix('!').unwrap_or(&token);
+        // Most aliases are letters, digits, `-`, `.` and `_`, skip the char scan for those
+        let plain = name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_'));
+        if let Some(c) = name
+            .chars()
+            .filter(|_| !plain)
+            .find(|c| UNSAFE_ALIAS_CHARS.contains(c) || c.is_control())
+        {
+            self.push(
+                LintKind::UnsafeAlias,
+                Some(pattern),
+                block,
+                file,
+                format!("Alias '{}' contains {:?}, which breaks purple's markers.", name, c),
+            );
+        }
+        if token.contains(['*', '?', '[']) || token.starts_with('!') || !self.graph.has_repeats() {
+            return;
+        }
+        match self.first_seen.entry(token) {
+            Entry::Occupied(first) => {
+                let message = format!(
+                    "Duplicate alias '{}' (first defined in {}). SSH only uses the first one.",
+                    first.key(),
+                    first.get().display()
+                );
+                self.push(LintKind::DuplicateAlias, Some(pattern), block, file, message);
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(file);
+            }
+        }
+    }
+
+    /// A host is fully shadowed when every directive it sets was already set
+    /// by an earlier matching wildcard block (first value wins).
+    fn check_shadowed(
+        &mut self,
+        earlier: &[Wildcard],
+        alias: &str,
+        directives: &[Directive],
+        block: Header,
+        file: &Path,
+    ) {
+        if earlier.is_empty() {
+            return;
+        }
+        let keys = directives
+            .iter()
+            .filter(|d| !d.is_non_directive)
+            .map(|d| d.key.as_str());
+        // The first matching wildcard that sets each key wins it
+        let winner = |key: &str| {
+            earlier.iter().find(|w| {
+                w.directives
+                    .iter()
+                    .any(|d| !d.is_non_directive && d.key.eq_ignore_ascii_case(key))
+                    && match_pattern_list(alias, &w.tokens)
+            })
+        };
+        // Most hosts set something no wildcard does, so check that first
+        if keys.clone().next().is_none()
+            || !keys.clone().all(|key| winner(key).is_some())
+            || keys
+                .clone()
+                .any(|k| MULTI_VALUE_KEYS.iter().any(|m| m.eq_ignore_ascii_case(k)))
+        {
+            return;
+        }
+        let mut shadowing: Vec<&str> = Vec::new();
+        for wildcard in keys.filter_map(winner) {
+            if !shadowing.contains(&wildcard.pattern) {
+                shadowing.push(wildcard.pattern);
+            }
+        }
+        let headers: Vec<String> = shadowing.iter().map(|p| format!("Host {}", p)).collect();
+        self.push(
+            LintKind::ShadowedHost,
+            Some(alias),
+            block,
+            file,
+            format!(
+                "Every directive is already set by {} above. First value wins, so this block has no effect.",
+                headers.join(", ")
+            ),
+        );
+    }
+
+    fn check_cycle(&mut self, alias: &str, block: Header, file: &Path) {
+        if self.graph.is_cyclic(alias) {
+            let route = self.graph.route(alias);
+            self.push(
+                LintKind::ProxyJumpCycle,
+                Some(alias),
+                block,
+                file,
+                format!("Jump hosts loop back on themselves: {}.", route.describe(alias)),
+            );
+        }
+    }
+
+    fn check_directive(&mut self, key: &str, value: &str, alias: Option<&str>, block: Header, file: &Path) {
+        // eq_ignore_ascii_case rather than a lowercased copy: this runs for
+        // every directive of every host
+        let is = |name: &str| key.eq_ignore_ascii_case(name);
+        if is("port") {
+            // Like OpenSSH, a Port is a number or a service name
+            let port = unquote(value.trim());
+            let message = if port.bytes().all(|b| b.is_ascii_digit()) {
+                (!port.parse::<u16>().is_ok_and(|p| p > 0))
+                    .then(|| format!("Port '{}' is out of range (1-65535).", port))
+            } else {
+                (!self.is_service(&port))
+                    .then(|| format!("Port '{}' is not a number or a known service name.", port))
+            };
+            if let Some(message) = message {
+                self.push(LintKind::InvalidPort, alias, block, file, message);
+            }
+        } else if is("identityfile") {
+            let exists = match self.identities.get(value) {
+                Some(&exists) => exists,
+                None => {
+                    let exists = identity_path(value).is_none_or(|path| path.exists());
+                    self.identities.insert(value.to_string(), exists);
+                    exists
+                }
+            };
+            if !exists {
+                self.push(
+                    LintKind::MissingIdentityFile,
+                    alias,
+                    block,
+                    file,
+                    format!("IdentityFile {} doesn't exist.", value.trim()),
+                );
+            }
+        } else if is("proxyjump") {
+            for hop in value.split(',') {
+                let Some(target) = jump_alias(hop) else {
+                    continue;
+                };
+                if !self.graph.is_known(target) {
+                    self.push(
+                        LintKind::UnresolvedProxyJump,
+                        alias,
+                        block,
+                        file,
+                        format!("ProxyJump target '{}' doesn't match any Host alias.", target),
+                    );
+                }
+            }
+        } else if is("proxycommand") {
+            for hop in proxy_command_hops(value) {
+                let Some(target) = jump_alias(&hop) else {
+                    continue;
+                };
+                if !self.graph.is_known(target) {
+                    self.push(
+                        LintKind::UnresolvedProxyJump,
+                        alias,
+                        block,
+                        file,
+                        format!("ProxyCommand target '{}' doesn't match any Host alias.", target),
+                    );
+                }
+            }
+        } else if is("include") {
+            // A valid directive inside a block that isn't in the keyword table
+        } else {
+            if is("ignoreunknown") {
+                self.ignored.extend(value.split(',').map(|p| p.trim().to_string()));
+            }
+            // The keys purple writes itself skip the cache lookup
+            if is_managed_key(key) || self.known.contains(key) {
+                return;
+            }
+            if keywords::lookup(key).is_some() {
+                self.known.insert(key.to_string());
+                return;
+            }
+            self.unknown.push((self.findings.len(), key.to_string()));
+            let message = match keywords::suggest(key) {
+                Some(suggestion) => {
+                    format!("Unknown directive '{}'. Did you mean {}?", key, suggestion)
+                }
+                None => format!("Unknown directive '{}'.", key),
+            };
+            self.push(LintKind::UnknownKey, alias, block, file, message);
+        }
+    }
+
+    /// The findings, minus unknown keys covered by an IgnoreUnknown.
+    fn finish(mut self) -> Vec<Finding> {
+        if self.ignored.is_empty() {
+            return self.findings;
+        }
+        let ignored: HashSet<usize> = self
+            .unknown
+            .iter()
+            .filter(|(_, key)| self.ignored.iter().any(|p| match_pattern(key, p)))
+            .map(|(index, _)| *index)
+            .collect();
+        let mut index = 0;
+        self.findings.retain(|_| {
+            index += 1;
+            !ignored.contains(&(index - 1))
+        });
+        self.findings
+    }
+}
+
+/// Names and aliases of the TCP services in an /etc/services file.
+fn tcp_services(content: &str) -> HashSet<String> {
+    let mut services = HashSet::new();
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if port.ends_with("/tcp") {
+            services.insert(name.to_string());
+            services.extend(fields.map(str::to_string));
+        }
+    }
+    services
+}
+
+/// Local path of an IdentityFile value. None when it can't be checked
+/// statically (tokens, environment variables, relative paths, `none`).
+fn identity_path(value: &str) -> Option<PathBuf> {
+    let value = unquote(value.trim());
+    let value = value.as_str();
+    if value.is_empty()
+        || value.eq_ignore_ascii_case("none")
+        || value.contains('%')
+        || value.contains("${")
+    {
+        return None;
+    }
+    let expanded = SshConfigFile::expand_tilde(value);
+    let path = PathBuf::from(expanded);
+    path.is_absolute().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        }
+    }
+
+    fn kinds(config: &SshConfigFile) -> Vec<LintKind> {
+        config.lint().iter().map(|f| f.kind).collect()
+    }
+
+    #[test]
+    fn clean_config_has_no_findings() {
+        let config = parse_str(
+            "Host *\n  ServerAliveInterval 60\n\nHost bastion\n  HostName 10.0.0.1\n  Port 2222\n\nHost web\n  HostName 10.0.0.2\n  ProxyJump admin@bastion:2222\n",
+        );
+        assert!(config.lint().is_empty());
+    }
+
+    #[test]
+    fn duplicate_alias_reported_on_second_block() {
+        let config = parse_str(
+            "Host web\n  HostName a\n\nHost db web\n  HostName b\n",
+        );
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, LintKind::DuplicateAlias);
+        assert_eq!(findings[0].alias.as_deref(), Some("db web"));
+        assert!(findings[0].message.contains("'web'"));
+    }
+
+    #[test]
+    fn host_shadowed_by_earlier_wildcard() {
+        let config = parse_str(
+            "Host web-*\n  User deploy\n  Port 2222\n\nHost web-1\n  User root\n  Port 22\n\nHost db\n  User root\n",
+        );
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, LintKind::ShadowedHost);
+        assert_eq!(findings[0].alias.as_deref(), Some("web-1"));
+        assert!(findings[0].message.contains("Host web-*"));
+    }
+
+    #[test]
+    fn partially_shadowed_host_is_fine() {
+        let config = parse_str("Host *\n  User deploy\n\nHost web\n  User root\n  HostName 10.0.0.1\n");
+        assert!(config.lint().is_empty());
+    }
+
+    #[test]
+    fn wildcard_below_host_does_not_shadow() {
+        let config = parse_str("Host web\n  User root\n\nHost *\n  User deploy\n");
+        assert!(config.lint().is_empty());
+    }
+
+    #[test]
+    fn unknown_key_with_suggestion() {
+        let config = parse_str(
+            "ServerAliveIntervall 30\n\nHost web\n  Hostnme 10.0.0.1\n  Frobnicate yes\n\nMatch user root\n  Usr admin\n",
+        );
+        let findings = config.lint();
+        assert_eq!(findings.len(), 4);
+        assert!(findings.iter().all(|f| f.kind == LintKind::UnknownKey));
+        assert_eq!(findings[0].block, "global");
+        assert!(findings[0].message.contains("Did you mean ServerAliveInterval?"));
+        assert!(findings[1].message.contains("Did you mean HostName?"));
+        assert_eq!(findings[2].message, "Unknown directive 'Frobnicate'.");
+        assert_eq!(findings[3].block, "Match user root");
+        assert!(findings[3].alias.is_none());
+    }
+
+    #[test]
+    fn ignore_unknown_silences_keys() {
+        let config = parse_str("IgnoreUnknown UseKeychain,Custom*\n\nHost web\n  CustomThing yes\n");
+        assert!(config.lint().is_empty());
+    }
+
+    #[test]
+    fn ignore_unknown_covers_earlier_keys() {
+        let config = parse_str("Host web\n  CustomThing yes\n  Bogus 1\n\nHost *\n  IgnoreUnknown Custom*\n");
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].message.contains("Bogus"));
+    }
+
+    #[test]
+    fn missing_identity_file() {
+        let dir = std::env::temp_dir().join(format!("purple_lint_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = dir.join("id_present");
+        std::fs::write(&key, "key").unwrap();
+        let config = parse_str(&format!(
+            "Host a\n  IdentityFile {}\n  IdentityFile {}\n  IdentityFile ~/.ssh/id_%h\n  IdentityFile relative_key\n",
+            key.display(),
+            dir.join("id_missing").display()
+        ));
+        let findings = config.lint();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, LintKind::MissingIdentityFile);
+        assert!(findings[0].message.contains("id_missing"));
+    }
+
+    #[test]
+    fn unresolved_proxy_jump() {
+        let config = parse_str(
+            "Host jump-*\n  User admin\n\nHost web\n  ProxyJump bastion,jump-eu,gw.example.com,10.0.0.1:22\n\nHost db\n  ProxyJump none\n",
+        );
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, LintKind::UnresolvedProxyJump);
+        assert!(findings[0].message.contains("'bastion'"));
+    }
+
+    #[test]
+    fn bare_star_does_not_resolve_proxy_jump() {
+        let config = parse_str("Host *\n  User admin\n\nHost web\n  ProxyJump bastion\n");
+        assert_eq!(kinds(&config), vec![LintKind::UnresolvedProxyJump]);
+    }
+
+    #[test]
+    fn unresolved_proxy_command_target() {
+        let config = parse_str("Host web\n  ProxyCommand ssh -W %h:%p bastion\n\nHost db\n  ProxyCommand nc %h %p\n");
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert!(findings[0].message.contains("ProxyCommand target 'bastion'"));
+    }
+
+    #[test]
+    fn proxy_jump_cycle() {
+        let config = parse_str("Host a\n  ProxyJump b\n\nHost b\n  ProxyJump a\n\nHost c\n  ProxyJump a\n\nHost d\n  HostName d.example.com\n");
+        let findings = config.lint();
+        assert_eq!(kinds(&config), vec![LintKind::ProxyJumpCycle; 3]);
+        assert_eq!(findings[0].message, "Jump hosts loop back on themselves: a \u{2192} b \u{2192} a.");
+    }
+
+    #[test]
+    fn repeated_later_hop_is_not_a_cycle() {
+        let config = parse_str(
+            "Host work1\n  ProxyJump web-01,bastion\n\nHost web-01\n  ProxyJump bastion\n\nHost bastion\n  HostName 10.0.0.1\n",
+        );
+        assert!(kinds(&config).is_empty());
+    }
+
+    #[test]
+    fn invalid_ports() {
+        let config = parse_str("Host a\n  Port 0\n\nHost b\n  Port 70000\n\nHost c\n  Port 22\n");
+        assert_eq!(kinds(&config), vec![LintKind::InvalidPort; 2]);
+        assert!(config.lint()[0].message.contains("out of range"));
+    }
+
+    #[test]
+    fn port_service_names() {
+        // OpenSSH resolves a name through /etc/services
+        assert!(kinds(&parse_str("Host a\n  Port ssh\n")).is_empty());
+        let services = tcp_services(
+            "# comment\nssh\t\t22/tcp\t\t\t# SSH\nhttp 80/tcp www\nsyslog 514/udp\n",
+        );
+        let mut names: Vec<&str> = services.iter().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["http", "ssh", "www"]);
+    }
+
+    #[test]
+    fn unsafe_alias_characters() {
+        let config = parse_str(
+            "Host web/1\n  HostName a\n\nHost \"it\\\"s\"\n  HostName b\n\nHost \"my box\"\n  HostName c\n",
+        );
+        assert_eq!(kinds(&config), vec![LintKind::UnsafeAlias; 2]);
+    }
+
+    #[test]
+    fn findings_in_included_files_carry_their_path() {
+        let mut config = parse_str("Include conf.d/*\n");
+        let included = SshConfigFile::parse_content("Host web\n  Port 99999\n");
+        if let ConfigElement::Include(include) = &mut config.elements[0] {
+            include.resolved_files.push(super::super::model::IncludedFile {
+                path: PathBuf::from("/tmp/conf.d/work"),
+                elements: included,
+                crlf: false,
+            });
+        }
+        let findings = config.lint();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].file, PathBuf::from("/tmp/conf.d/work"));
+    }
+
+    #[test]
+    fn json_uses_snake_case_kinds() {
+        let config = parse_str("Host a\n  Port 0\n");
+        let json = serde_json::to_string(&config.lint()).unwrap();
+        assert!(json.contains("\"kind\":\"invalid_port\""));
+        assert!(json.contains("\"alias\":\"a\""));
+    }
+}
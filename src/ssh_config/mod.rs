@@ -1,3 +1,15 @@
+pub mod backup;
+pub mod defaults;
+pub mod diff;
+pub mod fuzzy;
+pub mod groups;
+pub mod index;
+pub mod keywords;
+pub mod lint;
 pub mod model;
 pub mod parser;
+pub mod query;
+pub mod resolve;
+pub mod template;
+pub mod topology;
 pub mod writer;
@@ -1,13 +1,19 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use super::index::{self, HostIndex};
 
 /// Represents the entire SSH config file as a sequence of elements.
 /// Preserves the original structure for round-trip fidelity.
 #[derive(Debug, Clone)]
 pub struct SshConfigFile {
+    /// Edited through the methods below. Code that changes it directly must
+    /// call `invalidate_index` (or append with `push_element`).
     pub elements: Vec<ConfigElement>,
     pub path: PathBuf,
     /// Whether the original file used CRLF line endings.
     pub crlf: bool,
+    /// Alias and provider lookups (see `refresh_index`).
+    pub index: HostIndex,
 }
 
 /// An Include directive that references other config files.
@@ -24,6 +30,8 @@ pub struct IncludeDirective {
 pub struct IncludedFile {
     pub path: PathBuf,
     pub elements: Vec<ConfigElement>,
+    /// Whether the file uses CRLF line endings.
+    pub crlf: bool,
 }
 
 /// A single element in the config file.
@@ -33,8 +41,44 @@ pub enum ConfigElement {
     HostBlock(HostBlock),
     /// A comment, blank line, or global directive not inside a Host block.
     GlobalLine(String),
-    /// An Include directive referencing other config files (read-only).
+    /// An Include directive referencing other config files.
     Include(IncludeDirective),
+    /// A Match block: the "Match <criteria>" line plus all indented directives.
+    MatchBlock(MatchBlock),
+    /// A Host block commented out behind a `# purple:disabled` marker.
+    DisabledHost(CommentedBlock),
+    /// A host template: a commented-out Host block behind `# purple:template`.
+    Template(CommentedBlock),
+}
+
+/// Comment line that marks the start of a disabled Host block.
+pub const DISABLED_MARKER: &str = "# purple:disabled";
+
+/// Comment line that marks the start of a host template block.
+pub const TEMPLATE_MARKER: &str = "# purple:template";
+
+/// A Host block behind a purple marker with every line commented out, so ssh
+/// ignores it. `raw_lines` (marker included) are written back verbatim;
+/// `block` is the uncommented block.
+#[derive(Debug, Clone)]
+pub struct CommentedBlock {
+    pub raw_lines: Vec<String>,
+    pub block: HostBlock,
+}
+
+impl CommentedBlock {
+    /// Comment out `block` behind `marker`.
+    pub fn new(marker: &str, block: HostBlock) -> Self {
+        let mut raw_lines = vec![marker.to_string(), format!("# {}", block.raw_host_line)];
+        for d in &block.directives {
+            if d.raw_line.trim().is_empty() {
+                raw_lines.push("#".to_string());
+            } else {
+                raw_lines.push(format!("# {}", d.raw_line));
+            }
+        }
+        Self { raw_lines, block }
+    }
 }
 
 /// A parsed Host block with its directives.
@@ -48,7 +92,126 @@ pub struct HostBlock {
     pub directives: Vec<Directive>,
 }
 
-/// A directive line inside a Host block.
+/// A parsed Match block with its criteria and directives.
+#[derive(Debug, Clone)]
+pub struct MatchBlock {
+    /// The criteria text (the value after "Match", inline comment stripped).
+    pub criteria_text: String,
+    /// Parsed criteria, in the order they appear on the Match line.
+    pub criteria: Vec<MatchCriterion>,
+    /// The original raw "Match ..." line for faithful reproduction.
+    pub raw_match_line: String,
+    /// Parsed directives inside this block.
+    pub directives: Vec<Directive>,
+}
+
+/// The kind of a single Match criterion.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MatchKind {
+    Host,
+    OriginalHost,
+    User,
+    LocalUser,
+    Exec,
+    Canonical,
+    Final,
+    All,
+    /// A criterion purple doesn't know about (e.g. localnetwork, tagged).
+    Other(String),
+}
+
+impl MatchKind {
+    fn from_keyword(keyword: &str) -> Self {
+        match keyword.to_ascii_lowercase().as_str() {
+            "host" => MatchKind::Host,
+            "originalhost" => MatchKind::OriginalHost,
+            "user" => MatchKind::User,
+            "localuser" => MatchKind::LocalUser,
+            "exec" => MatchKind::Exec,
+            "canonical" => MatchKind::Canonical,
+            "final" => MatchKind::Final,
+            "all" => MatchKind::All,
+            _ => MatchKind::Other(keyword.to_string()),
+        }
+    }
+
+    pub fn keyword(&self) -> &str {
+        match self {
+            MatchKind::Host => "host",
+            MatchKind::OriginalHost => "originalhost",
+            MatchKind::User => "user",
+            MatchKind::LocalUser => "localuser",
+            MatchKind::Exec => "exec",
+            MatchKind::Canonical => "canonical",
+            MatchKind::Final => "final",
+            MatchKind::All => "all",
+            MatchKind::Other(keyword) => keyword,
+        }
+    }
+
+    /// Whether this criterion stands alone (no argument follows it).
+    pub fn takes_argument(&self) -> bool {
+        !matches!(self, MatchKind::Canonical | MatchKind::Final | MatchKind::All)
+    }
+}
+
+/// A single criterion on a Match line (e.g. `host *.example.com`, `!exec "test -f x"`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchCriterion {
+    pub kind: MatchKind,
+    /// Criterion prefixed with `!`.
+    pub negated: bool,
+    /// The argument with surrounding quotes removed. None for all/canonical/final.
+    pub arg: Option<String>,
+}
+
+impl MatchCriterion {
+    /// Parse the criteria portion of a Match line.
+    /// Quoted arguments (e.g. `exec "test -f ~/.vpn"`) are kept as one token.
+    pub fn parse_list(text: &str) -> Vec<MatchCriterion> {
+        let tokens = split_match_tokens(text);
+        let mut criteria = Vec::new();
+        let mut iter = tokens.into_iter();
+        while let Some(token) = iter.next() {
+            let (negated, keyword) = match token.strip_prefix('!') {
+                Some(rest) => (true, rest),
+                None => (false, token.as_str()),
+            };
+            let kind = MatchKind::from_keyword(keyword);
+            let arg = if kind.takes_argument() { iter.next() } else { None };
+            criteria.push(MatchCriterion { kind, negated, arg });
+        }
+        criteria
+    }
+}
+
+/// Split a Match criteria string on whitespace, keeping double-quoted runs together.
+fn split_match_tokens(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quote = false;
+    let mut has_token = false;
+    for c in text.chars() {
+        if c == '"' {
+            in_quote = !in_quote;
+            has_token = true;
+        } else if c.is_whitespace() && !in_quote {
+            if has_token {
+                tokens.push(std::mem::take(&mut current));
+                has_token = false;
+            }
+        } else {
+            current.push(c);
+            has_token = true;
+        }
+    }
+    if has_token {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// A directive line inside a Host or Match block.
 #[derive(Debug, Clone)]
 pub struct Directive {
     /// The directive key (e.g., "HostName", "User", "Port").
@@ -68,7 +231,10 @@ pub struct HostEntry {
     pub hostname: String,
     pub user: String,
     pub port: u16,
-    pub identity_file: String,
+    /// IdentityFile values in file order (ssh tries them in this order).
+    pub identity_files: Vec<String>,
+    /// CertificateFile values in file order.
+    pub certificate_files: Vec<String>,
     pub proxy_jump: String,
     /// If this host comes from an included file, the file path.
     pub source_file: Option<PathBuf>,
@@ -80,6 +246,10 @@ pub struct HostEntry {
     pub tunnel_count: u16,
     /// Password source from purple:askpass comment (e.g. "keychain", "op://...", "pass:...").
     pub askpass: Option<String>,
+    /// The block is commented out behind `# purple:disabled`.
+    pub disabled: bool,
+    /// Free-form note lines from purple:note comments, in file order.
+    pub notes: Vec<String>,
 }
 
 impl Default for HostEntry {
@@ -89,13 +259,16 @@ impl Default for HostEntry {
             hostname: String::new(),
             user: String::new(),
             port: 22,
-            identity_file: String::new(),
+            identity_files: Vec::new(),
+            certificate_files: Vec::new(),
             proxy_jump: String::new(),
             source_file: None,
             tags: Vec::new(),
             provider: None,
             tunnel_count: 0,
             askpass: None,
+            disabled: false,
+            notes: Vec::new(),
         }
     }
 }
@@ -113,28 +286,362 @@ impl HostEntry {
 /// negation or whitespace-separated multi-patterns (*, ?, [], !, space/tab).
 /// These are SSH match patterns, not concrete hosts.
 pub fn is_host_pattern(pattern: &str) -> bool {
-    pattern.contains('*')
-        || pattern.contains('?')
-        || pattern.contains('[')
-        || pattern.starts_with('!')
-        || pattern.contains(' ')
-        || pattern.contains('\t')
+    if is_plain_word(pattern) {
+        return pattern.contains(['*', '?', '[']) || pattern.starts_with('!');
+    }
+    let words = split_patterns(pattern);
+    match words.as_slice() {
+        [word] => word.contains(['*', '?', '[']) || word.starts_with('!'),
+        _ => words.len() > 1,
+    }
 }
 
-impl HostBlock {
-    /// Index of the first trailing blank line (for inserting content before separators).
-    fn content_end(&self) -> usize {
-        let mut pos = self.directives.len();
-        while pos > 0 {
-            if self.directives[pos - 1].is_non_directive
-                && self.directives[pos - 1].raw_line.trim().is_empty()
-            {
-                pos -= 1;
+/// The patterns of a Host line value. A quoted pattern may contain spaces.
+pub fn split_patterns(pattern: &str) -> Vec<String> {
+    if is_plain_word(pattern) && !pattern.is_empty() {
+        return vec![pattern.to_string()];
+    }
+    argv_split(pattern).unwrap_or_else(|| pattern.split_whitespace().map(String::from).collect())
+}
+
+/// Split an argument string into words the way OpenSSH's `argv_split` does.
+/// Spaces and tabs separate words, double or single quotes group them, and a
+/// backslash escapes a quote, a backslash or (outside quotes) a space. A `#`
+/// at the start of a word ends the line. None when a quote is never closed.
+pub fn argv_split(s: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| *c == ' ' || *c == '\t').is_some() {}
+        if matches!(chars.peek(), None | Some('#')) {
+            return Some(words);
+        }
+        let mut word = String::new();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.peek() {
+                    Some(&next) if matches!(next, '"' | '\'' | '\\') || (quote.is_none() && next == ' ') => {
+                        word.push(next);
+                        chars.next();
+                    }
+                    _ => word.push(c),
+                },
+                ' ' | '\t' if quote.is_none() => break,
+                '"' | '\'' if quote.is_none() => quote = Some(c),
+                _ if quote == Some(c) => quote = None,
+                _ => word.push(c),
+            }
+        }
+        if quote.is_some() {
+            return None;
+        }
+        words.push(word);
+    }
+}
+
+/// The value of a single-argument directive with quotes and escapes removed.
+/// Values that aren't exactly one word (or have an unclosed quote) are returned as written.
+pub fn unquote(value: &str) -> String {
+    if is_plain_word(value) {
+        return value.to_string();
+    }
+    match argv_split(value).as_deref() {
+        Some([word]) => word.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// `unquote` into an existing string, reusing its buffer.
+fn set_unquoted(target: &mut String, value: &str) {
+    if is_plain_word(value) {
+        target.clear();
+        target.push_str(value);
+    } else {
+        *target = unquote(value);
+    }
+}
+
+/// Set `target[*len]` to the unquoted value (pushing when the list is
+/// shorter) and advance `len`.
+fn push_unquoted(target: &mut Vec<String>, len: &mut usize, value: &str) {
+    match target.get_mut(*len) {
+        Some(existing) => set_unquoted(existing, value),
+        None => target.push(unquote(value)),
+    }
+    *len += 1;
+}
+
+/// Overwrite `target` with `values`, reusing its strings.
+fn set_list<'a>(target: &mut Vec<String>, values: impl Iterator<Item = &'a str>) {
+    let mut len = 0;
+    for value in values {
+        match target.get_mut(len) {
+            Some(existing) => {
+                existing.clear();
+                existing.push_str(value);
+            }
+            None => target.push(value.to_string()),
+        }
+        len += 1;
+    }
+    target.truncate(len);
+}
+
+fn set_option(target: &mut Option<String>, value: Option<&str>) {
+    match (target.as_mut(), value) {
+        (Some(existing), Some(value)) => {
+            existing.clear();
+            existing.push_str(value);
+        }
+        (_, value) => *target = value.map(str::to_string),
+    }
+}
+
+/// Quote a value so `argv_split` reads it back as one word. Plain values are
+/// returned unchanged, and so is an empty value (callers treat it as "remove").
+pub fn quote_arg(value: &str) -> String {
+    if is_plain_word(value) {
+        return value.to_string();
+    }
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// A value `argv_split` reads back unchanged as one word (or none, when
+/// empty): no whitespace, quotes or backslashes, and no leading `#`.
+pub(super) fn is_plain_word(value: &str) -> bool {
+    !value.starts_with('#')
+        && !value.chars().any(|c| c.is_whitespace() || matches!(c, '"' | '\'' | '\\'))
+}
+
+/// Whether two directive values mean the same thing once quoting is undone.
+fn same_value(a: &str, b: &str) -> bool {
+    a == b || matches!((argv_split(a), argv_split(b)), (Some(x), Some(y)) if x == y)
+}
+
+/// Directives edited through dedicated host form fields or the tunnel editor.
+/// Everything else shows up in the form's advanced directive list.
+pub const MANAGED_KEYS: &[&str] = &[
+    "hostname",
+    "user",
+    "port",
+    "identityfile",
+    "certificatefile",
+    "proxyjump",
+    "localforward",
+    "remoteforward",
+    "dynamicforward",
+];
+
+/// Whether a directive key is edited through a dedicated form field or the tunnel editor.
+pub fn is_managed_key(key: &str) -> bool {
+    MANAGED_KEYS.iter().any(|k| k.eq_ignore_ascii_case(key))
+}
+
+/// Index of the first trailing blank line in a directive list.
+fn directives_content_end(directives: &[Directive]) -> usize {
+    let mut pos = directives.len();
+    while pos > 0 {
+        if directives[pos - 1].is_non_directive && directives[pos - 1].raw_line.trim().is_empty() {
+            pos -= 1;
+        } else {
+            break;
+        }
+    }
+    pos
+}
+
+/// Indentation used by the first indented directive (falls back to "  ").
+fn directives_indent(directives: &[Directive]) -> String {
+    for d in directives {
+        if !d.is_non_directive && !d.raw_line.is_empty() {
+            let trimmed = d.raw_line.trim_start();
+            let indent_len = d.raw_line.len() - trimmed.len();
+            if indent_len > 0 {
+                return d.raw_line[..indent_len].to_string();
+            }
+        }
+    }
+    "  ".to_string()
+}
+
+/// Rebuild a directive's raw line for a new value.
+/// Detects the separator style from the original raw_line and preserves it.
+/// Handles: "Key value", "Key=value", "Key = value", "Key =value".
+/// Only considers '=' as separator if it appears before any non-whitespace
+/// content (avoids matching '=' inside values like "IdentityFile ~/.ssh/id=prod").
+pub(super) fn rebuild_raw_line(d: &Directive, indent: &str, value: &str) -> String {
+    let trimmed = d.raw_line.trim_start();
+    let after_key = trimmed.get(d.key.len()..).unwrap_or("");
+    let sep = if after_key.trim_start().starts_with('=') {
+        let eq_pos = after_key.find('=').unwrap();
+        let after_eq = &after_key[eq_pos + 1..];
+        let trailing_ws = after_eq.len() - after_eq.trim_start().len();
+        after_key[..eq_pos + 1 + trailing_ws].to_string()
+    } else {
+        " ".to_string()
+    };
+    format!("{}{}{}{}", indent, d.key, sep, value)
+}
+
+/// Rename `old` to `new` in a comma-separated ProxyJump chain. Hops may carry
+/// a user, a port or an ssh:// scheme. Returns None when no hop names `old`.
+fn rename_jump_hosts(value: &str, old: &str, new: &str) -> Option<String> {
+    let mut changed = false;
+    let hops: Vec<String> = value
+        .split(',')
+        .map(|hop| {
+            let (scheme, rest) = match hop.strip_prefix("ssh://") {
+                Some(rest) => ("ssh://", rest),
+                None => ("", hop),
+            };
+            let (user, host_port) = match rest.rfind('@') {
+                Some(i) => rest.split_at(i + 1),
+                None => ("", rest),
+            };
+            let (host, port) = match host_port.rfind(':') {
+                Some(i) if !host_port.starts_with('[') => host_port.split_at(i),
+                _ => (host_port, ""),
+            };
+            if host == old {
+                changed = true;
+                format!("{}{}{}{}", scheme, user, new, port)
+            } else {
+                hop.to_string()
+            }
+        })
+        .collect();
+    changed.then(|| hops.join(","))
+}
+
+/// netcat options that take an argument (`nc -x proxy:1080 %h %p`).
+const NC_ARG_FLAGS: &str = "IiOPpqsTVwXx";
+
+/// Rename `old` to `new` where a ProxyCommand names it as a host to reach:
+/// the destination, `-J` hops and `-W` target of `ssh`, or the host argument
+/// of `nc`/`ncat`/`netcat`. The program, other options and the remote command
+/// are left alone. Returns None when nothing changed.
+fn rename_command_host(value: &str, old: &str, new: &str) -> Option<String> {
+    // Split on single spaces so the original spacing survives the join
+    let mut words: Vec<String> = value.split(' ').map(str::to_string).collect();
+    let mut args = (0..words.len()).filter(|&i| !words[i].is_empty()).collect::<Vec<_>>().into_iter();
+    let mut program = args.next()?;
+    if words[program] == "exec" {
+        program = args.next()?;
+    }
+    let name = words[program].rsplit('/').next().unwrap_or_default().to_string();
+    let arg_flags = match name.as_str() {
+        "ssh" => super::topology::SSH_ARG_FLAGS,
+        "nc" | "ncat" | "netcat" => NC_ARG_FLAGS,
+        _ => return None,
+    };
+    let mut changed = false;
+    while let Some(i) = args.next() {
+        let Some(flags) = words[i].strip_prefix('-') else {
+            // The first operand is the host to connect to
+            let renamed = if name == "ssh" {
+                rename_jump_hosts(&words[i], old, new)
             } else {
-                break;
+                (words[i] == old).then(|| new.to_string())
+            };
+            if let Some(renamed) = renamed {
+                words[i] = renamed;
+                changed = true;
             }
+            break;
+        };
+        let Some(at) = flags.find(|c| arg_flags.contains(c)) else {
+            continue;
+        };
+        let flag = flags[at..].chars().next().unwrap_or_default();
+        // The option value is either attached (-Jbastion) or the next word
+        let (slot, start) = if flags.len() > at + flag.len_utf8() {
+            (i, 1 + at + flag.len_utf8())
+        } else {
+            match args.next() {
+                Some(next) => (next, 0),
+                None => break,
+            }
+        };
+        if name != "ssh" || !matches!(flag, 'J' | 'W') {
+            continue;
+        }
+        if let Some(renamed) = rename_jump_hosts(&words[slot][start..], old, new) {
+            words[slot] = format!("{}{}", &words[slot][..start], renamed);
+            changed = true;
         }
-        pos
+    }
+    changed.then(|| words.join(" "))
+}
+
+/// Rename jump references in ProxyJump and ProxyCommand directives, keeping
+/// inline comments. Returns the index and previous raw line of each
+/// directive that changed.
+fn rename_jump_references(directives: &mut [Directive], old: &str, new: &str) -> Vec<(usize, String)> {
+    let indent = directives_indent(directives);
+    let mut changed = Vec::new();
+    for (i, d) in directives.iter_mut().enumerate().filter(|(_, d)| !d.is_non_directive) {
+        let renamed = if d.key.eq_ignore_ascii_case("proxyjump") {
+            rename_jump_hosts(&d.value, old, new)
+        } else if d.key.eq_ignore_ascii_case("proxycommand") {
+            rename_command_host(&d.value, old, new)
+        } else {
+            None
+        };
+        let Some(value) = renamed else {
+            continue;
+        };
+        let previous = d.raw_line.clone();
+        // Swap the value in place so inline comments survive
+        let key_end = d.raw_line.len() - d.raw_line.trim_start().len() + d.key.len();
+        let found = d.raw_line.get(key_end..).and_then(|rest| rest.find(d.value.as_str()));
+        d.raw_line = match found.map(|pos| key_end + pos) {
+            Some(pos) => format!(
+                "{}{}{}",
+                &d.raw_line[..pos],
+                value,
+                &d.raw_line[pos + d.value.len()..]
+            ),
+            None => rebuild_raw_line(d, &indent, &value),
+        };
+        d.value = value;
+        changed.push((i, previous));
+    }
+    changed
+}
+
+/// The purple markers of a host block, borrowed from its comment lines.
+#[derive(Default)]
+struct Markers<'a> {
+    tags: Option<&'a str>,
+    provider: Option<(&'a str, &'a str)>,
+    askpass: Option<&'a str>,
+    notes: Vec<&'a str>,
+}
+
+impl<'a> Markers<'a> {
+    fn tags(&self) -> impl Iterator<Item = &'a str> {
+        self.tags
+            .into_iter()
+            .flat_map(|tags| tags.split(','))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+    }
+}
+
+impl HostBlock {
+    /// Index of the first trailing blank line (for inserting content before separators).
+    fn content_end(&self) -> usize {
+        directives_content_end(&self.directives)
     }
 
     /// Remove and return trailing blank lines.
@@ -156,49 +663,56 @@ impl HostBlock {
 
     /// Detect indentation used by existing directives (falls back to "  ").
     fn detect_indent(&self) -> String {
-        for d in &self.directives {
-            if !d.is_non_directive && !d.raw_line.is_empty() {
-                let trimmed = d.raw_line.trim_start();
-                let indent_len = d.raw_line.len() - trimmed.len();
-                if indent_len > 0 {
-                    return d.raw_line[..indent_len].to_string();
-                }
-            }
-        }
-        "  ".to_string()
+        directives_indent(&self.directives)
     }
 
-    /// Extract tags from purple:tags comment in directives.
-    pub fn tags(&self) -> Vec<String> {
-        for d in &self.directives {
-            if d.is_non_directive {
-                let trimmed = d.raw_line.trim();
-                if let Some(rest) = trimmed.strip_prefix("# purple:tags ") {
-                    return rest
-                        .split(',')
-                        .map(|t| t.trim().to_string())
-                        .filter(|t| !t.is_empty())
-                        .collect();
+    /// Index of the first blank line that only has blanks and comments after
+    /// it. That tail separates this block from the next (group headers, the
+    /// next host's leading comments) rather than belonging to it.
+    fn separator_start(&self) -> usize {
+        let last_directive = self.directives.iter().rposition(|d| !d.is_non_directive);
+        self.directives
+            .iter()
+            .enumerate()
+            .position(|(i, d)| {
+                last_directive.is_none_or(|last| i > last) && d.raw_line.trim().is_empty()
+            })
+            .unwrap_or(self.directives.len())
+    }
+
+    /// Read every purple marker in one pass. The first tags, provider and
+    /// askpass marker wins.
+    fn markers(&self) -> Markers<'_> {
+        let mut markers = Markers::default();
+        for d in self.directives.iter().filter(|d| d.is_non_directive) {
+            let Some(marker) = d.raw_line.trim().strip_prefix("# purple:") else {
+                continue;
+            };
+            if let Some(rest) = marker.strip_prefix("tags ") {
+                markers.tags.get_or_insert(rest);
+            } else if let Some(rest) = marker.strip_prefix("provider ") {
+                if markers.provider.is_none() {
+                    markers.provider = rest.split_once(':').map(|(name, id)| (name.trim(), id.trim()));
+                }
+            } else if let Some(rest) = marker.strip_prefix("askpass ") {
+                if markers.askpass.is_none() && !rest.trim().is_empty() {
+                    markers.askpass = Some(rest.trim());
                 }
+            } else if marker == "note" {
+                markers.notes.push("");
+            } else if let Some(rest) = marker.strip_prefix("note ") {
+                markers.notes.push(rest);
             }
         }
-        Vec::new()
+        markers
     }
 
     /// Extract provider info from purple:provider comment in directives.
     /// Returns (provider_name, server_id), e.g. ("digitalocean", "412345678").
     pub fn provider(&self) -> Option<(String, String)> {
-        for d in &self.directives {
-            if d.is_non_directive {
-                let trimmed = d.raw_line.trim();
-                if let Some(rest) = trimmed.strip_prefix("# purple:provider ") {
-                    if let Some((name, id)) = rest.split_once(':') {
-                        return Some((name.trim().to_string(), id.trim().to_string()));
-                    }
-                }
-            }
-        }
-        None
+        self.markers()
+            .provider
+            .map(|(name, id)| (name.to_string(), id.to_string()))
     }
 
     /// Set provider on a host block. Replaces existing purple:provider comment or adds one.
@@ -219,22 +733,6 @@ impl HostBlock {
         );
     }
 
-    /// Extract askpass source from purple:askpass comment in directives.
-    pub fn askpass(&self) -> Option<String> {
-        for d in &self.directives {
-            if d.is_non_directive {
-                let trimmed = d.raw_line.trim();
-                if let Some(rest) = trimmed.strip_prefix("# purple:askpass ") {
-                    let val = rest.trim();
-                    if !val.is_empty() {
-                        return Some(val.to_string());
-                    }
-                }
-            }
-        }
-        None
-    }
-
     /// Set askpass source on a host block. Replaces existing purple:askpass comment or adds one.
     /// Pass an empty string to remove the comment.
     pub fn set_askpass(&mut self, source: &str) {
@@ -256,6 +754,31 @@ impl HostBlock {
         }
     }
 
+    /// Set note lines on a host block. Replaces existing purple:note comments
+    /// in place (or adds them at the end). Pass no lines to remove the note.
+    pub fn set_notes(&mut self, notes: &[String]) {
+        let indent = self.detect_indent();
+        let is_note = |d: &Directive| {
+            d.is_non_directive
+                && (d.raw_line.trim() == "# purple:note"
+                    || d.raw_line.trim().starts_with("# purple:note "))
+        };
+        let existing = self.directives.iter().position(is_note);
+        self.directives.retain(|d| !is_note(d));
+        let pos = existing.unwrap_or_else(|| self.content_end());
+        let lines = notes.iter().map(|line| Directive {
+            key: String::new(),
+            value: String::new(),
+            raw_line: if line.is_empty() {
+                format!("{}# purple:note", indent)
+            } else {
+                format!("{}# purple:note {}", indent, line)
+            },
+            is_non_directive: true,
+        });
+        self.directives.splice(pos..pos, lines);
+    }
+
     /// Set tags on a host block. Replaces existing purple:tags comment or adds one.
     pub fn set_tags(&mut self, tags: &[String]) {
         let indent = self.detect_indent();
@@ -276,51 +799,107 @@ impl HostBlock {
         }
     }
 
+    /// The patterns on the Host line, unquoted.
+    pub fn patterns(&self) -> Vec<String> {
+        split_patterns(&self.host_pattern)
+    }
+
+    /// The alias of a single-pattern block, unquoted. Multi-pattern blocks
+    /// return the Host line value as written.
+    pub fn alias(&self) -> String {
+        if is_plain_word(&self.host_pattern) {
+            return self.host_pattern.clone();
+        }
+        match self.patterns().as_slice() {
+            [only] => only.clone(),
+            _ => self.host_pattern.clone(),
+        }
+    }
+
+    /// Whether this block is the host `alias` (quoted or not on the Host line).
+    pub fn is_alias(&self, alias: &str) -> bool {
+        self.host_pattern == alias || self.alias() == alias
+    }
+
+    /// Point the block at a new alias, quoting it when needed.
+    pub(super) fn set_alias(&mut self, alias: &str) {
+        let indent = self.raw_host_line.len() - self.raw_host_line.trim_start().len();
+        self.host_pattern = quote_arg(alias);
+        self.raw_host_line = format!("{}Host {}", &self.raw_host_line[..indent], self.host_pattern);
+    }
+
     /// Extract a convenience HostEntry view from this block.
     pub fn to_host_entry(&self) -> HostEntry {
-        let mut entry = HostEntry {
-            alias: self.host_pattern.clone(),
-            port: 22,
-            ..Default::default()
-        };
+        let mut entry = HostEntry::default();
+        self.fill_host_entry(&mut entry);
+        entry
+    }
+
+    /// `to_host_entry` into an existing entry, reusing its strings: reloads
+    /// rebuild every entry and most of them haven't changed. `source_file`
+    /// is left to the caller.
+    fn fill_host_entry(&self, entry: &mut HostEntry) {
+        let HostEntry {
+            alias,
+            hostname,
+            user,
+            port,
+            identity_files,
+            certificate_files,
+            proxy_jump,
+            source_file: _,
+            tags,
+            provider,
+            tunnel_count,
+            askpass,
+            disabled,
+            notes,
+        } = entry;
+        if is_plain_word(&self.host_pattern) {
+            alias.clear();
+            alias.push_str(&self.host_pattern);
+        } else {
+            *alias = self.alias();
+        }
+        hostname.clear();
+        user.clear();
+        proxy_jump.clear();
+        *port = 22;
+        *tunnel_count = 0;
+        *disabled = false;
+        let (mut identities, mut certificates) = (0, 0);
         for d in &self.directives {
             if d.is_non_directive {
                 continue;
             }
             if d.key.eq_ignore_ascii_case("hostname") {
-                entry.hostname = d.value.clone();
+                set_unquoted(hostname, &d.value);
             } else if d.key.eq_ignore_ascii_case("user") {
-                entry.user = d.value.clone();
+                set_unquoted(user, &d.value);
             } else if d.key.eq_ignore_ascii_case("port") {
-                entry.port = d.value.parse().unwrap_or(22);
+                *port = unquote(&d.value).parse().unwrap_or(22);
             } else if d.key.eq_ignore_ascii_case("identityfile") {
-                if entry.identity_file.is_empty() {
-                    entry.identity_file = d.value.clone();
-                }
+                push_unquoted(identity_files, &mut identities, &d.value);
+            } else if d.key.eq_ignore_ascii_case("certificatefile") {
+                push_unquoted(certificate_files, &mut certificates, &d.value);
             } else if d.key.eq_ignore_ascii_case("proxyjump") {
-                entry.proxy_jump = d.value.clone();
+                set_unquoted(proxy_jump, &d.value);
+            } else if d.key.eq_ignore_ascii_case("localforward")
+                || d.key.eq_ignore_ascii_case("remoteforward")
+                || d.key.eq_ignore_ascii_case("dynamicforward")
+            {
+                *tunnel_count = tunnel_count.saturating_add(1);
             }
         }
-        entry.tags = self.tags();
-        entry.provider = self.provider().map(|(name, _)| name);
-        entry.tunnel_count = self.tunnel_count();
-        entry.askpass = self.askpass();
-        entry
-    }
-
-    /// Count forwarding directives (LocalForward, RemoteForward, DynamicForward).
-    pub fn tunnel_count(&self) -> u16 {
-        let count = self
-            .directives
-            .iter()
-            .filter(|d| {
-                !d.is_non_directive
-                    && (d.key.eq_ignore_ascii_case("localforward")
-                        || d.key.eq_ignore_ascii_case("remoteforward")
-                        || d.key.eq_ignore_ascii_case("dynamicforward"))
-            })
-            .count();
-        count.min(u16::MAX as usize) as u16
+        identity_files.truncate(identities);
+        certificate_files.truncate(certificates);
+        // One marker pass rather than one per field: this runs for every
+        // host on every reload
+        let markers = self.markers();
+        set_list(tags, markers.tags());
+        set_option(provider, markers.provider.map(|(name, _)| name));
+        set_option(askpass, markers.askpass);
+        set_list(notes, markers.notes.iter().copied());
     }
 
     /// Check if this block has any tunnel forwarding directives.
@@ -342,16 +921,185 @@ impl HostBlock {
             .filter_map(|d| crate::tunnel::TunnelRule::parse_value(&d.key, &d.value))
             .collect()
     }
+
+    /// Directives not covered by form fields or the tunnel editor, in file order.
+    pub fn extra_directives(&self) -> Vec<(String, String)> {
+        self.directives
+            .iter()
+            .filter(|d| !d.is_non_directive && !is_managed_key(&d.key))
+            .map(|d| (d.key.clone(), d.value.clone()))
+            .collect()
+    }
+}
+
+impl MatchBlock {
+    /// Index of the first trailing blank line (for inserting content before separators).
+    fn content_end(&self) -> usize {
+        directives_content_end(&self.directives)
+    }
+
+    /// Detect indentation used by existing directives (falls back to "  ").
+    fn detect_indent(&self) -> String {
+        directives_indent(&self.directives)
+    }
+
+    /// Key/value pairs of the real directives in this block, in file order.
+    pub fn directive_pairs(&self) -> Vec<(String, String)> {
+        self.directives
+            .iter()
+            .filter(|d| !d.is_non_directive)
+            .map(|d| (d.key.clone(), d.value.clone()))
+            .collect()
+    }
+
+    /// Replace the criteria. Keeps the raw line when the text is unchanged.
+    pub fn set_criteria(&mut self, text: &str) {
+        let text = text.trim();
+        if text == self.criteria_text {
+            return;
+        }
+        self.criteria_text = text.to_string();
+        self.criteria = MatchCriterion::parse_list(text);
+        self.raw_match_line = format!("Match {}", text);
+    }
+
+    /// Replace the directives with the given key/value pairs, position by position.
+    /// Unchanged directives keep their raw line (inline comments, separators),
+    /// changed ones are rewritten in place, surplus ones are removed and new ones
+    /// are appended before the trailing blank lines. Comments are left alone.
+    pub fn set_directives(&mut self, pairs: &[(String, String)]) {
+        let indent = self.detect_indent();
+        let mut next = 0;
+        self.directives.retain_mut(|d| {
+            if d.is_non_directive {
+                return true;
+            }
+            let Some((key, value)) = pairs.get(next) else {
+                return false;
+            };
+            next += 1;
+            if d.key.eq_ignore_ascii_case(key) {
+                if d.value != *value {
+                    d.value = value.clone();
+                    d.raw_line = rebuild_raw_line(d, &indent, value);
+                }
+            } else {
+                d.key = key.clone();
+                d.value = value.clone();
+                d.raw_line = format!("{}{} {}", indent, key, value);
+            }
+            true
+        });
+        let pos = self.content_end();
+        let added: Vec<Directive> = pairs[next.min(pairs.len())..]
+            .iter()
+            .map(|(key, value)| Directive {
+                key: key.clone(),
+                value: value.clone(),
+                raw_line: format!("{}{} {}", indent, key, value),
+                is_non_directive: false,
+            })
+            .collect();
+        self.directives.splice(pos..pos, added);
+    }
 }
 
 impl SshConfigFile {
+    /// Top-level Match blocks in file order (Match blocks in Include files are read-only).
+    pub fn match_blocks(&self) -> Vec<&MatchBlock> {
+        self.elements
+            .iter()
+            .filter_map(|e| match e {
+                ConfigElement::MatchBlock(block) => Some(block),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Mutable access to the nth top-level Match block.
+    fn match_block_mut(&mut self, index: usize) -> Option<&mut MatchBlock> {
+        self.elements
+            .iter_mut()
+            .filter_map(|e| match e {
+                ConfigElement::MatchBlock(block) => Some(block),
+                _ => None,
+            })
+            .nth(index)
+    }
+
+    /// Append a new Match block at the end of the file.
+    /// Match blocks apply to every host after them, so the end is the safe spot.
+    pub fn add_match_block(&mut self, criteria: &str, pairs: &[(String, String)]) {
+        let criteria = criteria.trim();
+        let mut block = MatchBlock {
+            criteria_text: criteria.to_string(),
+            criteria: MatchCriterion::parse_list(criteria),
+            raw_match_line: format!("Match {}", criteria),
+            directives: Vec::new(),
+        };
+        block.set_directives(pairs);
+        if !self.elements.is_empty() && !self.last_element_has_trailing_blank() {
+            self.push_element(ConfigElement::GlobalLine(String::new()));
+        }
+        self.push_element(ConfigElement::MatchBlock(block));
+    }
+
+    /// Update criteria and directives of the nth top-level Match block.
+    /// Returns false if the block doesn't exist.
+    pub fn update_match_block(
+        &mut self,
+        index: usize,
+        criteria: &str,
+        pairs: &[(String, String)],
+    ) -> bool {
+        let Some(block) = self.match_block_mut(index) else {
+            return false;
+        };
+        block.set_criteria(criteria);
+        block.set_directives(pairs);
+        true
+    }
+
+    /// Delete the nth top-level Match block. Returns false if it doesn't exist.
+    pub fn delete_match_block(&mut self, index: usize) -> bool {
+        let Some(pos) = self
+            .elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| matches!(e, ConfigElement::MatchBlock(_)))
+            .map(|(i, _)| i)
+            .nth(index)
+        else {
+            return false;
+        };
+        self.elements.remove(pos);
+        // Collapse consecutive blank lines left by deletion
+        self.elements.dedup_by(|a, b| {
+            matches!(
+                (&*a, &*b),
+                (ConfigElement::GlobalLine(x), ConfigElement::GlobalLine(y))
+                if x.trim().is_empty() && y.trim().is_empty()
+            )
+        });
+        self.invalidate_index();
+        true
+    }
+
     /// Get all host entries as convenience views (including from Include files).
     pub fn host_entries(&self) -> Vec<HostEntry> {
-        let mut entries = Vec::new();
-        Self::collect_host_entries(&self.elements, &mut entries);
+        let mut entries = Vec::with_capacity(self.elements.len());
+        self.refresh_host_entries(&mut entries);
         entries
     }
 
+    /// `host_entries` into `entries`, reusing the strings of the entries
+    /// already there.
+    pub fn refresh_host_entries(&self, entries: &mut Vec<HostEntry>) {
+        let mut len = 0;
+        Self::collect_host_entries(&self.elements, entries, &mut len);
+        entries.truncate(len);
+    }
+
     /// Collect all resolved Include file paths (recursively).
     pub fn include_paths(&self) -> Vec<PathBuf> {
         let mut paths = Vec::new();
@@ -373,18 +1121,29 @@ impl SshConfigFile {
     /// Collect parent directories of Include glob patterns.
     /// When a file is added/removed under a glob dir, the directory's mtime changes.
     pub fn include_glob_dirs(&self) -> Vec<PathBuf> {
+        let mut seen = std::collections::HashSet::new();
+        self.include_globs()
+            .into_iter()
+            .filter_map(|glob| glob.parent().map(|p| p.to_path_buf()))
+            .filter(|dir| seen.insert(dir.clone()))
+            .collect()
+    }
+
+    /// Collect Include patterns as absolute globs, resolved the way
+    /// `resolve_include` resolves them.
+    pub fn include_globs(&self) -> Vec<PathBuf> {
         let config_dir = self.path.parent();
         let mut seen = std::collections::HashSet::new();
-        let mut dirs = Vec::new();
-        Self::collect_include_glob_dirs(&self.elements, config_dir, &mut seen, &mut dirs);
-        dirs
+        let mut globs = Vec::new();
+        Self::collect_include_globs(&self.elements, config_dir, &mut seen, &mut globs);
+        globs
     }
 
-    fn collect_include_glob_dirs(
+    fn collect_include_globs(
         elements: &[ConfigElement],
         config_dir: Option<&std::path::Path>,
         seen: &mut std::collections::HashSet<PathBuf>,
-        dirs: &mut Vec<PathBuf>,
+        globs: &mut Vec<PathBuf>,
     ) {
         for e in elements {
             if let ConfigElement::Include(include) = e {
@@ -399,64 +1158,76 @@ impl SshConfigFile {
                     } else {
                         continue;
                     };
-                    if let Some(parent) = resolved.parent() {
-                        let parent = parent.to_path_buf();
-                        if seen.insert(parent.clone()) {
-                            dirs.push(parent);
-                        }
+                    if seen.insert(resolved.clone()) {
+                        globs.push(resolved);
                     }
                 }
                 // Recurse into resolved files
                 for file in &include.resolved_files {
-                    Self::collect_include_glob_dirs(
-                        &file.elements,
-                        file.path.parent(),
-                        seen,
-                        dirs,
-                    );
+                    Self::collect_include_globs(&file.elements, file.path.parent(), seen, globs);
                 }
             }
         }
     }
 
 
-    /// Recursively collect host entries from a list of elements.
-    fn collect_host_entries(elements: &[ConfigElement], entries: &mut Vec<HostEntry>) {
+    /// Recursively collect host entries from a list of elements, refilling
+    /// `entries[*len..]` before pushing new ones.
+    fn collect_host_entries(elements: &[ConfigElement], entries: &mut Vec<HostEntry>, len: &mut usize) {
         for e in elements {
-            match e {
-                ConfigElement::HostBlock(block) => {
-                    if is_host_pattern(&block.host_pattern) {
-                        continue;
-                    }
-                    entries.push(block.to_host_entry());
-                }
+            let (block, disabled) = match e {
+                ConfigElement::HostBlock(block) => (block, false),
+                ConfigElement::DisabledHost(disabled) => (&disabled.block, true),
                 ConfigElement::Include(include) => {
                     for file in &include.resolved_files {
-                        let start = entries.len();
-                        Self::collect_host_entries(&file.elements, entries);
-                        for entry in &mut entries[start..] {
+                        let start = *len;
+                        Self::collect_host_entries(&file.elements, entries, len);
+                        for entry in &mut entries[start..*len] {
                             if entry.source_file.is_none() {
                                 entry.source_file = Some(file.path.clone());
                             }
                         }
                     }
+                    continue;
+                }
+                ConfigElement::GlobalLine(_) | ConfigElement::MatchBlock(_) | ConfigElement::Template(_) => {
+                    continue;
                 }
-                ConfigElement::GlobalLine(_) => {}
+            };
+            if is_host_pattern(&block.host_pattern) {
+                continue;
             }
+            if *len == entries.len() {
+                entries.push(HostEntry::default());
+            }
+            let entry = &mut entries[*len];
+            block.fill_host_entry(entry);
+            entry.disabled = disabled;
+            entry.source_file = None;
+            *len += 1;
         }
     }
 
     /// Check if a host alias already exists (including in Include files).
-    /// Walks the element tree directly without building HostEntry structs.
+    /// Uses the index when current, otherwise walks the element tree without
+    /// building HostEntry structs.
     pub fn has_host(&self, alias: &str) -> bool {
-        Self::has_host_in_elements(&self.elements, alias)
+        match self.indexed_aliases(alias) {
+            Some(paths) => paths.iter().any(|path| {
+                index::element_at(&self.elements, path)
+                    .and_then(index::host_block)
+                    .is_some_and(|block| block.patterns().iter().any(|p| p == alias))
+            }),
+            None => Self::has_host_in_elements(&self.elements, alias),
+        }
     }
 
     fn has_host_in_elements(elements: &[ConfigElement], alias: &str) -> bool {
         for e in elements {
             match e {
-                ConfigElement::HostBlock(block) => {
-                    if block.host_pattern.split_whitespace().any(|p| p == alias) {
+                ConfigElement::HostBlock(block)
+                | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => {
+                    if block.patterns().iter().any(|p| p == alias) {
                         return true;
                     }
                 }
@@ -467,20 +1238,21 @@ impl SshConfigFile {
                         }
                     }
                 }
-                ConfigElement::GlobalLine(_) => {}
+                ConfigElement::GlobalLine(_) | ConfigElement::MatchBlock(_) | ConfigElement::Template(_) => {}
             }
         }
         false
     }
 
-    /// Check if a host alias is from an included file (read-only).
+    /// Check if a host alias is from an included file.
     /// Handles multi-pattern Host lines by splitting on whitespace.
     pub fn is_included_host(&self, alias: &str) -> bool {
         // Not in top-level elements → must be in an Include
         for e in &self.elements {
             match e {
-                ConfigElement::HostBlock(block) => {
-                    if block.host_pattern.split_whitespace().any(|p| p == alias) {
+                ConfigElement::HostBlock(block)
+                | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => {
+                    if block.patterns().iter().any(|p| p == alias) {
                         return false;
                     }
                 }
@@ -491,7 +1263,7 @@ impl SshConfigFile {
                         }
                     }
                 }
-                ConfigElement::GlobalLine(_) => {}
+                ConfigElement::GlobalLine(_) | ConfigElement::MatchBlock(_) | ConfigElement::Template(_) => {}
             }
         }
         false
@@ -499,60 +1271,195 @@ impl SshConfigFile {
 
     /// Add a new host entry to the config.
     pub fn add_host(&mut self, entry: &HostEntry) {
-        let block = Self::entry_to_block(entry);
-        // Add a blank line separator if the file isn't empty and doesn't already end with one
-        if !self.elements.is_empty() && !self.last_element_has_trailing_blank() {
-            self.elements
-                .push(ConfigElement::GlobalLine(String::new()));
+        let len = self.elements.len();
+        Self::append_block(&mut self.elements, Self::entry_to_block(entry));
+        self.index_appended(len);
+    }
+
+    /// Append an element to the main config as is, keeping the index current.
+    pub fn push_element(&mut self, element: ConfigElement) {
+        let len = self.elements.len();
+        self.elements.push(element);
+        self.index_appended(len);
+    }
+
+    /// Add a new host entry to an included file. Returns false when `file`
+    /// is not one of the resolved Include files.
+    pub fn add_host_to_file(&mut self, entry: &HostEntry, file: &Path) -> bool {
+        match Self::included_file_mut(&mut self.elements, file) {
+            Some(included) => {
+                Self::append_block(&mut included.elements, Self::entry_to_block(entry));
+                self.invalidate_index();
+                true
+            }
+            None => false,
         }
-        self.elements.push(ConfigElement::HostBlock(block));
+    }
+
+    /// Append a host block, adding a blank line separator if the elements
+    /// aren't empty and don't already end with one.
+    fn append_block(elements: &mut Vec<ConfigElement>, block: HostBlock) {
+        if !elements.is_empty() && !Self::ends_with_blank(elements) {
+            elements.push(ConfigElement::GlobalLine(String::new()));
+        }
+        elements.push(ConfigElement::HostBlock(block));
     }
 
     /// Check if the last element already ends with a blank line.
     pub fn last_element_has_trailing_blank(&self) -> bool {
-        match self.elements.last() {
+        Self::ends_with_blank(&self.elements)
+    }
+
+    fn ends_with_blank(elements: &[ConfigElement]) -> bool {
+        match elements.last() {
             Some(ConfigElement::HostBlock(block)) => block
                 .directives
                 .last()
                 .is_some_and(|d| d.is_non_directive && d.raw_line.trim().is_empty()),
+            Some(ConfigElement::MatchBlock(block)) => block
+                .directives
+                .last()
+                .is_some_and(|d| d.is_non_directive && d.raw_line.trim().is_empty()),
             Some(ConfigElement::GlobalLine(line)) => line.trim().is_empty(),
+            Some(ConfigElement::DisabledHost(disabled)) => {
+                disabled.raw_lines.last().is_some_and(|l| l.trim().is_empty())
+            }
             _ => false,
         }
     }
 
+    /// Find a resolved Include file by path (recursively).
+    fn included_file_mut<'a>(
+        elements: &'a mut [ConfigElement],
+        path: &Path,
+    ) -> Option<&'a mut IncludedFile> {
+        for e in elements {
+            if let ConfigElement::Include(include) = e {
+                for file in &mut include.resolved_files {
+                    if file.path == path {
+                        return Some(file);
+                    }
+                    if let Some(found) = Self::included_file_mut(&mut file.elements, path) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// List the files new hosts can be written to: the main config followed
+    /// by every resolved Include file.
+    pub fn writable_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.path.clone()];
+        files.extend(self.include_paths());
+        files
+    }
+
     /// Update an existing host entry by alias.
     /// Merges changes into the existing block, preserving unknown directives.
     pub fn update_host(&mut self, old_alias: &str, entry: &HostEntry) {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern == old_alias {
-                    // Update host pattern (preserve raw_host_line when alias unchanged)
-                    if entry.alias != block.host_pattern {
-                        block.host_pattern = entry.alias.clone();
-                        block.raw_host_line = format!("Host {}", entry.alias);
-                    }
+        let Some(path) = self.block_path(old_alias, &|b| b.is_alias(old_alias)) else {
+            return;
+        };
+        // Update host pattern (preserve raw_host_line when alias unchanged)
+        self.edit_block(&path, |block| {
+            if entry.alias != block.alias() {
+                block.set_alias(&entry.alias);
+            }
+        });
+        let Some(block) = self.block_at_mut(&path) else {
+            return;
+        };
+
+        // Merge known directives (update existing, add missing, remove empty)
+        Self::upsert_directive(block, "HostName", &quote_arg(&entry.hostname));
+        Self::upsert_directive(block, "User", &quote_arg(&entry.user));
+        if entry.port != 22 {
+            Self::upsert_directive(block, "Port", &entry.port.to_string());
+        } else {
+            // Remove explicit Port 22 (it's the default)
+            block
+                .directives
+                .retain(|d| d.is_non_directive || !d.key.eq_ignore_ascii_case("port"));
+        }
+        for (key, files) in [
+            ("IdentityFile", &entry.identity_files),
+            ("CertificateFile", &entry.certificate_files),
+        ] {
+            let files: Vec<String> = files.iter().filter(|f| !f.is_empty()).map(|f| quote_arg(f)).collect();
+            let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+            Self::set_repeated_directive(block, key, &files);
+        }
+        Self::upsert_directive(block, "ProxyJump", &quote_arg(&entry.proxy_jump));
+    }
 
-                    // Merge known directives (update existing, add missing, remove empty)
-                    Self::upsert_directive(block, "HostName", &entry.hostname);
-                    Self::upsert_directive(block, "User", &entry.user);
-                    if entry.port != 22 {
-                        Self::upsert_directive(block, "Port", &entry.port.to_string());
+    /// Rename a host and rewrite `ProxyJump`/`ProxyCommand` references to it in
+    /// every Host and Match block, Include files included. Returns the blocks
+    /// whose references changed, or None when no host has `old_alias`.
+    pub fn rename_host(&mut self, old_alias: &str, new_alias: &str) -> Option<Vec<String>> {
+        let path = self.block_path(old_alias, &|b| b.is_alias(old_alias))?;
+        if old_alias != new_alias {
+            self.edit_block(&path, |block| block.set_alias(new_alias));
+        }
+        let mut touched = Vec::new();
+        Self::rename_references_in(&mut self.elements, old_alias, new_alias, &mut touched);
+        Some(touched)
+    }
+
+    fn rename_references_in(
+        elements: &mut [ConfigElement],
+        old_alias: &str,
+        new_alias: &str,
+        touched: &mut Vec<String>,
+    ) {
+        for e in elements {
+            let is_template = matches!(e, ConfigElement::Template(_));
+            let (name, changed) = match e {
+                ConfigElement::HostBlock(block) => (
+                    block.host_pattern.clone(),
+                    rename_jump_references(&mut block.directives, old_alias, new_alias),
+                ),
+                ConfigElement::MatchBlock(block) => (
+                    format!("Match {}", block.criteria_text),
+                    rename_jump_references(&mut block.directives, old_alias, new_alias),
+                ),
+                ConfigElement::DisabledHost(commented) | ConfigElement::Template(commented) => {
+                    let changed = rename_jump_references(&mut commented.block.directives, old_alias, new_alias);
+                    // Directive i is commented out on raw line i + 2 (after
+                    // the marker and the Host line)
+                    for (i, previous) in &changed {
+                        let current = &commented.block.directives[*i].raw_line;
+                        if let Some(line) = commented.raw_lines.get_mut(i + 2) {
+                            *line = match line.strip_suffix(previous.as_str()) {
+                                Some(prefix) => format!("{}{}", prefix, current),
+                                None => format!("# {}", current),
+                            };
+                        }
+                    }
+                    let name = if is_template {
+                        format!("template {}", commented.block.host_pattern)
                     } else {
-                        // Remove explicit Port 22 (it's the default)
-                        block
-                            .directives
-                            .retain(|d| d.is_non_directive || !d.key.eq_ignore_ascii_case("port"));
+                        commented.block.host_pattern.clone()
+                    };
+                    (name, changed)
+                }
+                ConfigElement::Include(include) => {
+                    for file in &mut include.resolved_files {
+                        Self::rename_references_in(&mut file.elements, old_alias, new_alias, touched);
                     }
-                    Self::upsert_directive(block, "IdentityFile", &entry.identity_file);
-                    Self::upsert_directive(block, "ProxyJump", &entry.proxy_jump);
-                    return;
+                    continue;
                 }
+                ConfigElement::GlobalLine(_) => continue,
+            };
+            if !changed.is_empty() {
+                touched.push(name);
             }
         }
     }
 
     /// Update a directive in-place, add it if missing, or remove it if value is empty.
-    fn upsert_directive(block: &mut HostBlock, key: &str, value: &str) {
+    pub(super) fn upsert_directive(block: &mut HostBlock, key: &str, value: &str) {
         if value.is_empty() {
             block
                 .directives
@@ -563,24 +1470,9 @@ impl SshConfigFile {
         for d in &mut block.directives {
             if !d.is_non_directive && d.key.eq_ignore_ascii_case(key) {
                 // Only rebuild raw_line when value actually changed (preserves inline comments)
-                if d.value != value {
+                if !same_value(&d.value, value) {
                     d.value = value.to_string();
-                    // Detect separator style from original raw_line and preserve it.
-                    // Handles: "Key value", "Key=value", "Key = value", "Key =value"
-                    // Only considers '=' as separator if it appears before any
-                    // non-whitespace content (avoids matching '=' inside values
-                    // like "IdentityFile ~/.ssh/id=prod").
-                    let trimmed = d.raw_line.trim_start();
-                    let after_key = &trimmed[d.key.len()..];
-                    let sep = if after_key.trim_start().starts_with('=') {
-                        let eq_pos = after_key.find('=').unwrap();
-                        let after_eq = &after_key[eq_pos + 1..];
-                        let trailing_ws = after_eq.len() - after_eq.trim_start().len();
-                        after_key[..eq_pos + 1 + trailing_ws].to_string()
-                    } else {
-                        " ".to_string()
-                    };
-                    d.raw_line = format!("{}{}{}{}", indent, d.key, sep, value);
+                    d.raw_line = rebuild_raw_line(d, &indent, value);
                 }
                 return;
             }
@@ -598,15 +1490,124 @@ impl SshConfigFile {
         );
     }
 
+    /// Directives of a host not covered by form fields or the tunnel editor.
+    pub fn host_extra_directives(&self, alias: &str) -> Vec<(String, String)> {
+        self.find_block(alias, &|b| b.is_alias(alias))
+            .map(|b| b.extra_directives())
+            .unwrap_or_default()
+    }
+
+    /// Replace a host's extra directives (see `HostBlock::extra_directives`) with `pairs`.
+    /// Single-valued keys go through `upsert_directive` so unchanged lines keep their
+    /// formatting; repeated keys (SendEnv, SetEnv) are merged positionally. The result
+    /// is then arranged in the order of `pairs`, reusing the original line slots.
+    pub fn set_host_directives(&mut self, alias: &str, pairs: &[(String, String)]) {
+        let Some(block) = self.find_block_mut(alias, &|b| b.is_alias(alias))
+        else {
+            return;
+        };
+
+        // Drop keys that are gone entirely
+        for (key, _) in block.extra_directives() {
+            if !pairs.iter().any(|(k, _)| k.eq_ignore_ascii_case(&key)) {
+                Self::upsert_directive(block, &key, "");
+            }
+        }
+
+        // Update values key by key
+        let mut done: Vec<String> = Vec::new();
+        for (key, _) in pairs {
+            if is_managed_key(key) || done.iter().any(|k| k.eq_ignore_ascii_case(key)) {
+                continue;
+            }
+            done.push(key.clone());
+            let values: Vec<&str> = pairs
+                .iter()
+                .filter(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.as_str())
+                .collect();
+            let existing = block
+                .directives
+                .iter()
+                .filter(|d| !d.is_non_directive && d.key.eq_ignore_ascii_case(key))
+                .count();
+            if values.len() == 1 && existing <= 1 {
+                Self::upsert_directive(block, key, values[0]);
+            } else {
+                Self::set_repeated_directive(block, key, &values);
+            }
+        }
+
+        // Arrange in requested order, reusing the slots the extra directives occupy
+        let slots: Vec<usize> = block
+            .directives
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| !d.is_non_directive && !is_managed_key(&d.key))
+            .map(|(i, _)| i)
+            .collect();
+        let mut used = vec![false; slots.len()];
+        let mut ordered = Vec::with_capacity(slots.len());
+        for (key, value) in pairs.iter().filter(|(k, _)| !is_managed_key(k)) {
+            let found = slots.iter().enumerate().find(|&(n, &i)| {
+                let d = &block.directives[i];
+                !used[n] && d.key.eq_ignore_ascii_case(key) && d.value == *value
+            });
+            if let Some((n, &i)) = found {
+                used[n] = true;
+                ordered.push(block.directives[i].clone());
+            }
+        }
+        if ordered.len() == slots.len() {
+            for (slot, directive) in slots.into_iter().zip(ordered) {
+                block.directives[slot] = directive;
+            }
+        }
+    }
+
+    /// Set all values of a repeatable directive, keeping existing lines where
+    /// the value is unchanged. Extra values are inserted after the last occurrence.
+    fn set_repeated_directive(block: &mut HostBlock, key: &str, values: &[&str]) {
+        let indent = block.detect_indent();
+        let positions: Vec<usize> = block
+            .directives
+            .iter()
+            .enumerate()
+            .filter(|(_, d)| !d.is_non_directive && d.key.eq_ignore_ascii_case(key))
+            .map(|(i, _)| i)
+            .collect();
+        for (&pos, value) in positions.iter().zip(values) {
+            let d = &mut block.directives[pos];
+            if !same_value(&d.value, value) {
+                d.value = value.to_string();
+                d.raw_line = rebuild_raw_line(d, &indent, value);
+            }
+        }
+        for &pos in positions.iter().skip(values.len()).rev() {
+            block.directives.remove(pos);
+        }
+        if values.len() > positions.len() {
+            let at = match positions.last() {
+                Some(&last) => last + 1,
+                None => block.content_end(),
+            };
+            let added: Vec<Directive> = values[positions.len()..]
+                .iter()
+                .map(|value| Directive {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                    raw_line: format!("{}{} {}", indent, key, value),
+                    is_non_directive: false,
+                })
+                .collect();
+            block.directives.splice(at..at, added);
+        }
+    }
+
     /// Set provider on a host block by alias.
     pub fn set_host_provider(&mut self, alias: &str, provider_name: &str, server_id: &str) {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern == alias {
-                    block.set_provider(provider_name, server_id);
-                    return;
-                }
-            }
+        if let Some(path) = self.block_path(alias, &|b| b.is_alias(alias)) {
+            self.edit_block(&path, |block| block.set_provider(provider_name, server_id));
         }
     }
 
@@ -614,6 +1615,15 @@ impl SshConfigFile {
     /// Searches both top-level elements and Include files so that provider hosts
     /// in included configs are recognized during sync (prevents duplicate additions).
     pub fn find_hosts_by_provider(&self, provider_name: &str) -> Vec<(String, String)> {
+        if let Some(found) = self.indexed_provider(provider_name) {
+            return found
+                .into_iter()
+                .filter_map(|(path, id)| {
+                    let block = index::element_at(&self.elements, path).and_then(index::host_block)?;
+                    Some((block.alias(), id.to_string()))
+                })
+                .collect();
+        }
         let mut results = Vec::new();
         Self::collect_provider_hosts(&self.elements, provider_name, &mut results);
         results
@@ -626,10 +1636,12 @@ impl SshConfigFile {
     ) {
         for element in elements {
             match element {
-                ConfigElement::HostBlock(block) => {
+                // Disabled hosts count too, so sync doesn't add them again
+                ConfigElement::HostBlock(block)
+                | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => {
                     if let Some((name, id)) = block.provider() {
                         if name == provider_name {
-                            results.push((block.host_pattern.clone(), id));
+                            results.push((block.alias(), id));
                         }
                     }
                 }
@@ -638,7 +1650,7 @@ impl SshConfigFile {
                         Self::collect_provider_hosts(&file.elements, provider_name, results);
                     }
                 }
-                ConfigElement::GlobalLine(_) => {}
+                ConfigElement::GlobalLine(_) | ConfigElement::MatchBlock(_) | ConfigElement::Template(_) => {}
             }
         }
     }
@@ -653,24 +1665,22 @@ impl SshConfigFile {
     /// Inserts at `content_end()` (before trailing blanks), using detected indentation.
     /// Uses split_whitespace matching for multi-pattern Host lines.
     pub fn add_forward(&mut self, alias: &str, directive_key: &str, value: &str) {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern.split_whitespace().any(|p| p == alias) {
-                    let indent = block.detect_indent();
-                    let pos = block.content_end();
-                    block.directives.insert(
-                        pos,
-                        Directive {
-                            key: directive_key.to_string(),
-                            value: value.to_string(),
-                            raw_line: format!("{}{} {}", indent, directive_key, value),
-                            is_non_directive: false,
-                        },
-                    );
-                    return;
-                }
-            }
-        }
+        let Some(block) = self.find_block_mut(alias, &|b| {
+            b.patterns().iter().any(|p| p == alias)
+        }) else {
+            return;
+        };
+        let indent = block.detect_indent();
+        let pos = block.content_end();
+        block.directives.insert(
+            pos,
+            Directive {
+                key: directive_key.to_string(),
+                value: value.to_string(),
+                raw_line: format!("{}{} {}", indent, directive_key, value),
+                is_non_directive: false,
+            },
+        );
     }
 
     /// Remove a specific forwarding directive from a host block.
@@ -678,20 +1688,18 @@ impl SshConfigFile {
     /// Uses split_whitespace matching for multi-pattern Host lines.
     /// Returns true if a directive was actually removed.
     pub fn remove_forward(&mut self, alias: &str, directive_key: &str, value: &str) -> bool {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern.split_whitespace().any(|p| p == alias) {
-                    if let Some(pos) = block.directives.iter().position(|d| {
-                        !d.is_non_directive
-                            && d.key.eq_ignore_ascii_case(directive_key)
-                            && Self::values_match(&d.value, value)
-                    }) {
-                        block.directives.remove(pos);
-                        return true;
-                    }
-                    return false;
-                }
-            }
+        let Some(block) = self.find_block_mut(alias, &|b| {
+            b.patterns().iter().any(|p| p == alias)
+        }) else {
+            return false;
+        };
+        if let Some(pos) = block.directives.iter().position(|d| {
+            !d.is_non_directive
+                && d.key.eq_ignore_ascii_case(directive_key)
+                && Self::values_match(&d.value, value)
+        }) {
+            block.directives.remove(pos);
+            return true;
         }
         false
     }
@@ -699,18 +1707,16 @@ impl SshConfigFile {
     /// Check if a host block has a specific forwarding directive.
     /// Uses whitespace-normalized value comparison and split_whitespace host matching.
     pub fn has_forward(&self, alias: &str, directive_key: &str, value: &str) -> bool {
-        for element in &self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern.split_whitespace().any(|p| p == alias) {
-                    return block.directives.iter().any(|d| {
-                        !d.is_non_directive
-                            && d.key.eq_ignore_ascii_case(directive_key)
-                            && Self::values_match(&d.value, value)
-                    });
-                }
-            }
-        }
-        false
+        self.find_block(alias, &|b| {
+            b.patterns().iter().any(|p| p == alias)
+        })
+        .is_some_and(|block| {
+            block.directives.iter().any(|d| {
+                !d.is_non_directive
+                    && d.key.eq_ignore_ascii_case(directive_key)
+                    && Self::values_match(&d.value, value)
+            })
+        })
     }
 
     /// Find tunnel directives for a host alias, searching all elements including
@@ -727,7 +1733,7 @@ impl SshConfigFile {
         for element in elements {
             match element {
                 ConfigElement::HostBlock(block) => {
-                    if block.host_pattern.split_whitespace().any(|p| p == alias) {
+                    if block.patterns().iter().any(|p| p == alias) {
                         return block.tunnel_directives();
                     }
                 }
@@ -739,7 +1745,10 @@ impl SshConfigFile {
                         }
                     }
                 }
-                ConfigElement::GlobalLine(_) => {}
+                ConfigElement::GlobalLine(_)
+                | ConfigElement::MatchBlock(_)
+                | ConfigElement::DisabledHost(_)
+                | ConfigElement::Template(_) => {}
             }
         }
         Vec::new()
@@ -772,71 +1781,214 @@ impl SshConfigFile {
         format!("{}-{}", base, std::process::id())
     }
 
-    /// Set tags on a host block by alias.
-    pub fn set_host_tags(&mut self, alias: &str, tags: &[String]) {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern == alias {
-                    block.set_tags(tags);
-                    return;
-                }
-            }
-        }
+    /// Set tags on a host block by alias.
+    pub fn set_host_tags(&mut self, alias: &str, tags: &[String]) {
+        if let Some(block) = self.find_block_mut(alias, &|b| b.is_alias(alias)) {
+            block.set_tags(tags);
+        }
+    }
+
+    /// Set note lines on a host block by alias.
+    pub fn set_host_notes(&mut self, alias: &str, notes: &[String]) {
+        if let Some(block) = self.find_block_mut(alias, &|b| b.is_alias(alias)) {
+            block.set_notes(notes);
+        }
+    }
+
+    /// Set askpass source on a host block by alias.
+    pub fn set_host_askpass(&mut self, alias: &str, source: &str) {
+        if let Some(block) = self.find_block_mut(alias, &|b| b.is_alias(alias)) {
+            block.set_askpass(source);
+        }
+    }
+
+    /// Comment out a host block behind `# purple:disabled`, in whichever file
+    /// defines it. Trailing blank lines and comments that head the next block
+    /// stay uncommented. Returns false if there is no enabled host `alias`.
+    pub fn disable_host(&mut self, alias: &str) -> bool {
+        let disabled = Self::disable_host_in(&mut self.elements, alias);
+        if disabled {
+            self.invalidate_index();
+        }
+        disabled
+    }
+
+    fn disable_host_in(elements: &mut Vec<ConfigElement>, alias: &str) -> bool {
+        let pos = elements.iter().position(|e| {
+            matches!(e, ConfigElement::HostBlock(b) if b.is_alias(alias))
+        });
+        let Some(pos) = pos else {
+            return elements.iter_mut().any(|e| match e {
+                ConfigElement::Include(include) => include
+                    .resolved_files
+                    .iter_mut()
+                    .any(|file| Self::disable_host_in(&mut file.elements, alias)),
+                _ => false,
+            });
+        };
+        let ConfigElement::HostBlock(mut block) = elements.remove(pos) else {
+            unreachable!("position matched a HostBlock");
+        };
+        // The separator tail stays between this block and the next
+        let tail_start = block.separator_start();
+        let tail: Vec<Directive> = block.directives.drain(tail_start..).collect();
+        let mut replacement = vec![ConfigElement::DisabledHost(CommentedBlock::new(DISABLED_MARKER, block))];
+        replacement.extend(tail.into_iter().map(|d| ConfigElement::GlobalLine(d.raw_line)));
+        elements.splice(pos..pos, replacement);
+        true
+    }
+
+    /// Copy a host block, directives, comments and tunnels included, under a
+    /// free alias derived from `alias`. The copy goes right after the source,
+    /// in the same file, without the provider marker so sync doesn't claim it.
+    /// Returns the new alias, or None when there is no host `alias`.
+    pub fn clone_host(&mut self, alias: &str) -> Option<String> {
+        let new_alias = self.deduplicate_alias(&format!("{}-copy", alias));
+        let cloned = Self::clone_host_in(&mut self.elements, alias, &new_alias);
+        self.invalidate_index();
+        cloned.then_some(new_alias)
+    }
+
+    fn clone_host_in(elements: &mut Vec<ConfigElement>, alias: &str, new_alias: &str) -> bool {
+        let pos = elements.iter().position(|e| {
+            matches!(e, ConfigElement::HostBlock(b) if b.is_alias(alias))
+        });
+        let Some(pos) = pos else {
+            return elements.iter_mut().any(|e| match e {
+                ConfigElement::Include(include) => include
+                    .resolved_files
+                    .iter_mut()
+                    .any(|file| Self::clone_host_in(&mut file.elements, alias, new_alias)),
+                _ => false,
+            });
+        };
+        let ConfigElement::HostBlock(source) = &mut elements[pos] else {
+            unreachable!("position matched a HostBlock");
+        };
+        // The source's separator tail moves below the copy
+        let tail_start = source.separator_start();
+        let tail: Vec<Directive> = source.directives.drain(tail_start..).collect();
+        let mut copy = source.clone();
+        source.ensure_trailing_blank();
+
+        copy.set_alias(new_alias);
+        copy.directives.retain(|d| {
+            !(d.is_non_directive && d.raw_line.trim().starts_with("# purple:provider"))
+        });
+        copy.directives.extend(tail);
+        elements.insert(pos + 1, ConfigElement::HostBlock(copy));
+        true
+    }
+
+    /// Uncomment a host disabled with `disable_host`. Returns false if there is
+    /// no disabled host `alias`.
+    pub fn enable_host(&mut self, alias: &str) -> bool {
+        Self::enable_host_in(&mut self.elements, alias)
     }
 
-    /// Set askpass source on a host block by alias.
-    pub fn set_host_askpass(&mut self, alias: &str, source: &str) {
-        for element in &mut self.elements {
-            if let ConfigElement::HostBlock(block) = element {
-                if block.host_pattern == alias {
-                    block.set_askpass(source);
-                    return;
+    fn enable_host_in(elements: &mut [ConfigElement], alias: &str) -> bool {
+        for e in elements.iter_mut() {
+            match e {
+                ConfigElement::DisabledHost(disabled) if disabled.block.is_alias(alias) => {
+                    *e = ConfigElement::HostBlock(disabled.block.clone());
+                    return true;
+                }
+                ConfigElement::Include(include) => {
+                    for file in &mut include.resolved_files {
+                        if Self::enable_host_in(&mut file.elements, alias) {
+                            return true;
+                        }
+                    }
                 }
+                _ => {}
             }
         }
+        false
     }
 
-    /// Delete a host entry by alias.
+    /// Delete a host entry by alias. The host is removed from whichever file
+    /// (main config or Include) defines it.
     #[allow(dead_code)]
     pub fn delete_host(&mut self, alias: &str) {
-        self.elements.retain(|e| match e {
-            ConfigElement::HostBlock(block) => block.host_pattern != alias,
+        if Self::delete_host_in(&mut self.elements, alias) {
+            self.invalidate_index();
+        }
+    }
+
+    fn delete_host_in(elements: &mut Vec<ConfigElement>, alias: &str) -> bool {
+        let before = elements.len();
+        elements.retain(|e| match e {
+            ConfigElement::HostBlock(block)
+            | ConfigElement::DisabledHost(CommentedBlock { block, .. }) => {
+                !block.is_alias(alias)
+            }
             _ => true,
         });
+        if elements.len() == before {
+            for e in elements.iter_mut() {
+                if let ConfigElement::Include(include) = e {
+                    for file in &mut include.resolved_files {
+                        if Self::delete_host_in(&mut file.elements, alias) {
+                            return true;
+                        }
+                    }
+                }
+            }
+            return false;
+        }
         // Collapse consecutive blank lines left by deletion
-        self.elements.dedup_by(|a, b| {
+        elements.dedup_by(|a, b| {
             matches!(
                 (&*a, &*b),
                 (ConfigElement::GlobalLine(x), ConfigElement::GlobalLine(y))
                 if x.trim().is_empty() && y.trim().is_empty()
             )
         });
+        true
     }
 
     /// Delete a host and return the removed element and its position for undo.
+    /// For included hosts the position is relative to the Include file's elements.
     /// Does NOT collapse blank lines so the position stays valid for re-insertion.
     pub fn delete_host_undoable(&mut self, alias: &str) -> Option<(ConfigElement, usize)> {
-        let pos = self.elements.iter().position(|e| {
-            matches!(e, ConfigElement::HostBlock(b) if b.host_pattern == alias)
-        })?;
-        let element = self.elements.remove(pos);
-        Some((element, pos))
+        let removed = Self::delete_host_undoable_in(&mut self.elements, alias)?;
+        self.invalidate_index();
+        Some(removed)
     }
 
-    /// Insert a host block at a specific position (for undo).
-    pub fn insert_host_at(&mut self, element: ConfigElement, position: usize) {
-        let pos = position.min(self.elements.len());
-        self.elements.insert(pos, element);
+    fn delete_host_undoable_in(
+        elements: &mut Vec<ConfigElement>,
+        alias: &str,
+    ) -> Option<(ConfigElement, usize)> {
+        if let Some(pos) = elements.iter().position(|e| match e {
+            ConfigElement::HostBlock(b) | ConfigElement::DisabledHost(CommentedBlock { block: b, .. }) => {
+                b.is_alias(alias)
+            }
+            _ => false,
+        }) {
+            return Some((elements.remove(pos), pos));
+        }
+        for e in elements.iter_mut() {
+            if let ConfigElement::Include(include) = e {
+                for file in &mut include.resolved_files {
+                    if let Some(removed) = Self::delete_host_undoable_in(&mut file.elements, alias)
+                    {
+                        return Some(removed);
+                    }
+                }
+            }
+        }
+        None
     }
 
     /// Swap two host blocks in the config by alias. Returns true if swap was performed.
     #[allow(dead_code)]
     pub fn swap_hosts(&mut self, alias_a: &str, alias_b: &str) -> bool {
         let pos_a = self.elements.iter().position(|e| {
-            matches!(e, ConfigElement::HostBlock(b) if b.host_pattern == alias_a)
+            matches!(e, ConfigElement::HostBlock(b) if b.is_alias(alias_a))
         });
         let pos_b = self.elements.iter().position(|e| {
-            matches!(e, ConfigElement::HostBlock(b) if b.host_pattern == alias_b)
+            matches!(e, ConfigElement::HostBlock(b) if b.is_alias(alias_b))
         });
         if let (Some(a), Some(b)) = (pos_a, pos_b) {
             if a == b {
@@ -854,6 +2006,7 @@ impl SshConfigFile {
 
             // Swap
             self.elements.swap(first, second);
+            self.invalidate_index();
 
             // Add trailing blank to first block (separator between the two)
             if let ConfigElement::HostBlock(block) = &mut self.elements[first] {
@@ -874,52 +2027,38 @@ impl SshConfigFile {
 
     /// Convert a HostEntry into a new HostBlock with clean formatting.
     pub(crate) fn entry_to_block(entry: &HostEntry) -> HostBlock {
-        let mut directives = Vec::new();
-
-        if !entry.hostname.is_empty() {
-            directives.push(Directive {
-                key: "HostName".to_string(),
-                value: entry.hostname.clone(),
-                raw_line: format!("  HostName {}", entry.hostname),
-                is_non_directive: false,
-            });
-        }
-        if !entry.user.is_empty() {
-            directives.push(Directive {
-                key: "User".to_string(),
-                value: entry.user.clone(),
-                raw_line: format!("  User {}", entry.user),
-                is_non_directive: false,
-            });
-        }
-        if entry.port != 22 {
-            directives.push(Directive {
-                key: "Port".to_string(),
-                value: entry.port.to_string(),
-                raw_line: format!("  Port {}", entry.port),
-                is_non_directive: false,
-            });
-        }
-        if !entry.identity_file.is_empty() {
-            directives.push(Directive {
-                key: "IdentityFile".to_string(),
-                value: entry.identity_file.clone(),
-                raw_line: format!("  IdentityFile {}", entry.identity_file),
-                is_non_directive: false,
-            });
+        let port = if entry.port != 22 { entry.port.to_string() } else { String::new() };
+        let mut pairs: Vec<(&str, &str)> = vec![
+            ("HostName", &entry.hostname),
+            ("User", &entry.user),
+            ("Port", &port),
+        ];
+        for file in &entry.identity_files {
+            pairs.push(("IdentityFile", file));
         }
-        if !entry.proxy_jump.is_empty() {
-            directives.push(Directive {
-                key: "ProxyJump".to_string(),
-                value: entry.proxy_jump.clone(),
-                raw_line: format!("  ProxyJump {}", entry.proxy_jump),
-                is_non_directive: false,
-            });
+        for file in &entry.certificate_files {
+            pairs.push(("CertificateFile", file));
         }
+        pairs.push(("ProxyJump", &entry.proxy_jump));
+
+        let directives = pairs
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| {
+                let value = quote_arg(value);
+                Directive {
+                    key: key.to_string(),
+                    raw_line: format!("  {} {}", key, value),
+                    value,
+                    is_non_directive: false,
+                }
+            })
+            .collect();
 
+        let host_pattern = quote_arg(&entry.alias);
         HostBlock {
-            host_pattern: entry.alias.clone(),
-            raw_host_line: format!("Host {}", entry.alias),
+            raw_host_line: format!("Host {}", host_pattern),
+            host_pattern,
             directives,
         }
     }
@@ -934,6 +2073,7 @@ mod tests {
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: false,
+            index: Default::default(),
         }
     }
 
@@ -960,7 +2100,7 @@ mod tests {
             "Host myserver\n  HostName 10.0.0.1\n  LocalForward 8080 localhost:80\n  RemoteForward 9090 localhost:3000\n",
         );
         if let Some(ConfigElement::HostBlock(block)) = config.elements.first() {
-            assert_eq!(block.tunnel_count(), 2);
+            assert_eq!(block.to_host_entry().tunnel_count, 2);
         } else {
             panic!("Expected HostBlock");
         }
@@ -970,7 +2110,7 @@ mod tests {
     fn tunnel_count_zero_for_no_forwards() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  User admin\n");
         if let Some(ConfigElement::HostBlock(block)) = config.elements.first() {
-            assert_eq!(block.tunnel_count(), 0);
+            assert_eq!(block.to_host_entry().tunnel_count, 0);
             assert!(!block.has_tunnels());
         } else {
             panic!("Expected HostBlock");
@@ -1057,6 +2197,27 @@ mod tests {
         assert_eq!(entries[0].tunnel_count, 2);
     }
 
+    #[test]
+    fn refresh_host_entries_overwrites_previous_entries() {
+        let before = parse_str(
+            "Host old\n  HostName 10.0.0.9\n  Port 2222\n  IdentityFile ~/.ssh/a\n  IdentityFile ~/.ssh/b\n  LocalForward 8080 localhost:80\n  # purple:tags x,y,z\n  # purple:provider do:1\n  # purple:askpass keychain\n  # purple:note hi\n\nHost gone\n  HostName gone.example.com\n",
+        );
+        let after = parse_str(
+            "Host web\n  HostName \"10.0.0.1\"\n  IdentityFile ~/.ssh/c\n  # purple:tags prod\n\n# purple:disabled\n# Host db\n#   User admin\n",
+        );
+        let mut entries = before.host_entries();
+        after.refresh_host_entries(&mut entries);
+        assert_eq!(format!("{:?}", entries), format!("{:?}", after.host_entries()));
+        assert_eq!(entries.len(), 2);
+        assert!(!entries[0].disabled && entries[1].disabled);
+        assert_eq!(entries[0].hostname, "10.0.0.1");
+        assert_eq!(entries[0].identity_files, ["~/.ssh/c"]);
+        assert_eq!(entries[0].port, 22);
+        assert_eq!(entries[0].tags, ["prod"]);
+        assert!(entries[0].provider.is_none() && entries[0].askpass.is_none());
+        assert!(entries[0].notes.is_empty() && entries[0].tunnel_count == 0);
+    }
+
     #[test]
     fn remove_forward_returns_true_on_match() {
         let mut config = parse_str(
@@ -1127,7 +2288,7 @@ mod tests {
             "Host myserver\n  localforward 8080 localhost:80\n  REMOTEFORWARD 9090 localhost:90\n  dynamicforward 1080\n",
         );
         if let Some(ConfigElement::HostBlock(block)) = config.elements.first() {
-            assert_eq!(block.tunnel_count(), 3);
+            assert_eq!(block.to_host_entry().tunnel_count, 3);
         } else {
             panic!("Expected HostBlock");
         }
@@ -1273,7 +2434,7 @@ mod tests {
         let entry = HostEntry {
             alias: "myserver".to_string(),
             hostname: "10.0.0.1".to_string(),
-            identity_file: "~/.ssh/id=staging".to_string(),
+            identity_files: vec!["~/.ssh/id=staging".to_string()],
             port: 22,
             ..Default::default()
         };
@@ -1292,7 +2453,7 @@ mod tests {
         let entry = HostEntry {
             alias: "myserver".to_string(),
             hostname: "10.0.0.1".to_string(),
-            identity_file: "~/.ssh/id_ed25519".to_string(),
+            identity_files: vec!["~/.ssh/id_ed25519".to_string()],
             port: 22,
             ..Default::default()
         };
@@ -1309,7 +2470,7 @@ mod tests {
         let entry = HostEntry {
             alias: "myserver".to_string(),
             hostname: "10.0.0.1".to_string(),
-            identity_file: "~/.ssh/id_ed25519".to_string(),
+            identity_files: vec!["~/.ssh/id_ed25519".to_string()],
             port: 22,
             ..Default::default()
         };
@@ -1318,6 +2479,215 @@ mod tests {
         assert!(output.contains("IdentityFile = ~/.ssh/id_ed25519"), "got: {}", output);
     }
 
+    fn config_with_include(main: &str, included: &str) -> SshConfigFile {
+        let mut config = parse_str(main);
+        config.push_element(ConfigElement::Include(IncludeDirective {
+            raw_line: "Include conf.d/*".to_string(),
+            pattern: "conf.d/*".to_string(),
+            resolved_files: vec![IncludedFile {
+                path: PathBuf::from("/tmp/conf.d/work"),
+                elements: SshConfigFile::parse_content(included),
+                crlf: false,
+            }],
+        }));
+        config
+    }
+
+    fn included_elements(config: &SshConfigFile) -> &[ConfigElement] {
+        for e in &config.elements {
+            if let ConfigElement::Include(include) = e {
+                return &include.resolved_files[0].elements;
+            }
+        }
+        panic!("no Include element");
+    }
+
+    #[test]
+    fn update_host_in_included_file() {
+        let mut config = config_with_include(
+            "Host main\n  HostName 10.0.0.1\n",
+            "Host work\n  HostName 10.0.0.2\n  User old\n",
+        );
+        let entry = HostEntry {
+            alias: "work-renamed".to_string(),
+            hostname: "10.0.0.3".to_string(),
+            user: "new".to_string(),
+            ..Default::default()
+        };
+        config.update_host("work", &entry);
+        let entries = config.host_entries();
+        let work = entries.iter().find(|e| e.alias == "work-renamed").unwrap();
+        assert_eq!(work.hostname, "10.0.0.3");
+        assert_eq!(work.user, "new");
+        assert_eq!(work.source_file, Some(PathBuf::from("/tmp/conf.d/work")));
+        // Main config untouched
+        assert_eq!(config.elements.len(), 2);
+        assert!(config.is_included_host("work-renamed"));
+    }
+
+    #[test]
+    fn tags_askpass_and_provider_in_included_file() {
+        let mut config = config_with_include("", "Host work\n  HostName 10.0.0.2\n");
+        config.set_host_tags("work", &["prod".to_string()]);
+        config.set_host_askpass("work", "keychain");
+        config.set_host_provider("work", "digitalocean", "42");
+        let entry = &config.host_entries()[0];
+        assert_eq!(entry.tags, vec!["prod"]);
+        assert_eq!(entry.askpass.as_deref(), Some("keychain"));
+        assert_eq!(
+            config.find_hosts_by_provider("digitalocean"),
+            vec![("work".to_string(), "42".to_string())]
+        );
+    }
+
+    #[test]
+    fn forwards_in_included_file() {
+        let mut config = config_with_include("", "Host work\n  HostName 10.0.0.2\n");
+        config.add_forward("work", "LocalForward", "8080 localhost:80");
+        assert!(config.has_forward("work", "LocalForward", "8080  localhost:80"));
+        assert_eq!(config.find_tunnel_directives("work").len(), 1);
+        assert!(config.remove_forward("work", "LocalForward", "8080 localhost:80"));
+        assert!(!config.has_forward("work", "LocalForward", "8080 localhost:80"));
+    }
+
+    #[test]
+    fn delete_host_in_included_file() {
+        let mut config = config_with_include(
+            "Host main\n  HostName 10.0.0.1\n",
+            "Host a\n  HostName 1.1.1.1\n\nHost b\n  HostName 2.2.2.2\n",
+        );
+        config.delete_host("a");
+        assert!(!config.has_host("a"));
+        assert!(config.has_host("b"));
+        assert!(config.has_host("main"));
+        assert_eq!(config.elements.len(), 2);
+    }
+
+    #[test]
+    fn delete_undoable_in_included_file() {
+        let mut config = config_with_include(
+            "Host main\n  HostName 10.0.0.1\n",
+            "Host a\n  HostName 1.1.1.1\n\nHost b\n  HostName 2.2.2.2\n",
+        );
+        let (_, position) = config.delete_host_undoable("b").unwrap();
+        assert_eq!(position, 1);
+        assert!(!config.has_host("b"));
+        let aliases: Vec<String> = config.host_entries().into_iter().map(|e| e.alias).collect();
+        assert_eq!(aliases, vec!["main", "a"]);
+    }
+
+    #[test]
+    fn add_host_to_file_appends_with_separator() {
+        let mut config = config_with_include("Host main\n  HostName 10.0.0.1\n", "Host a\n  HostName 1.1.1.1\n");
+        let entry = HostEntry {
+            alias: "new".to_string(),
+            hostname: "3.3.3.3".to_string(),
+            ..Default::default()
+        };
+        assert!(config.add_host_to_file(&entry, Path::new("/tmp/conf.d/work")));
+        let elements = included_elements(&config);
+        assert_eq!(elements.len(), 3);
+        assert!(matches!(&elements[1], ConfigElement::GlobalLine(l) if l.is_empty()));
+        assert!(matches!(&elements[2], ConfigElement::HostBlock(b) if b.host_pattern == "new"));
+        assert!(!config.add_host_to_file(&entry, Path::new("/tmp/conf.d/missing")));
+    }
+
+    #[test]
+    fn writable_files_lists_main_then_includes() {
+        let config = config_with_include("", "");
+        assert_eq!(
+            config.writable_files(),
+            vec![PathBuf::from("/tmp/test_config"), PathBuf::from("/tmp/conf.d/work")]
+        );
+    }
+
+    #[test]
+    fn extra_directives_skip_managed_keys() {
+        let config = parse_str(
+            "Host a\n  HostName 1.1.1.1\n  ForwardAgent yes\n  LocalForward 8080 localhost:80\n  # note\n  Compression yes\n",
+        );
+        assert_eq!(
+            config.host_extra_directives("a"),
+            pairs(&[("ForwardAgent", "yes"), ("Compression", "yes")])
+        );
+    }
+
+    #[test]
+    fn set_host_directives_keeps_unchanged_formatting() {
+        let mut config = parse_str(
+            "Host a\n    HostName 1.1.1.1\n    ForwardAgent=yes # agent\n    Compression yes\n",
+        );
+        config.set_host_directives(
+            "a",
+            &pairs(&[("ForwardAgent", "yes"), ("Compression", "no")]),
+        );
+        assert_eq!(
+            config.serialize(),
+            "Host a\n    HostName 1.1.1.1\n    ForwardAgent=yes # agent\n    Compression no\n"
+        );
+    }
+
+    #[test]
+    fn set_host_directives_adds_and_deletes() {
+        let mut config = parse_str(
+            "Host a\n  HostName 1.1.1.1\n  ForwardAgent yes\n\nHost b\n  HostName 2.2.2.2\n",
+        );
+        config.set_host_directives("a", &pairs(&[("ServerAliveInterval", "60")]));
+        assert_eq!(
+            config.serialize(),
+            "Host a\n  HostName 1.1.1.1\n  ServerAliveInterval 60\n\nHost b\n  HostName 2.2.2.2\n"
+        );
+    }
+
+    #[test]
+    fn set_host_directives_reorders_in_place() {
+        let mut config = parse_str(
+            "Host a\n  HostName 1.1.1.1\n  ForwardAgent yes\n  # keep me\n  Compression yes\n",
+        );
+        config.set_host_directives(
+            "a",
+            &pairs(&[("Compression", "yes"), ("ForwardAgent", "yes")]),
+        );
+        assert_eq!(
+            config.serialize(),
+            "Host a\n  HostName 1.1.1.1\n  Compression yes\n  # keep me\n  ForwardAgent yes\n"
+        );
+    }
+
+    #[test]
+    fn set_host_directives_repeated_keys() {
+        let mut config = parse_str(
+            "Host a\n  SendEnv LANG\n  SendEnv LC_*\n  User me\n",
+        );
+        config.set_host_directives(
+            "a",
+            &pairs(&[("SendEnv", "LANG"), ("SendEnv", "TERM"), ("SendEnv", "EDITOR")]),
+        );
+        assert_eq!(
+            config.serialize(),
+            "Host a\n  SendEnv LANG\n  SendEnv TERM\n  SendEnv EDITOR\n  User me\n"
+        );
+        config.set_host_directives("a", &pairs(&[("SendEnv", "TERM")]));
+        assert_eq!(config.serialize(), "Host a\n  SendEnv TERM\n  User me\n");
+    }
+
+    #[test]
+    fn set_host_directives_ignores_managed_keys() {
+        let mut config = parse_str("Host a\n  HostName 1.1.1.1\n");
+        config.set_host_directives("a", &pairs(&[("HostName", "9.9.9.9")]));
+        assert_eq!(config.serialize(), "Host a\n  HostName 1.1.1.1\n");
+    }
+
+    #[test]
+    fn set_host_directives_in_included_file() {
+        let mut config = config_with_include("", "Host work\n  HostName 10.0.0.2\n");
+        config.set_host_directives("work", &pairs(&[("IdentitiesOnly", "yes")]));
+        assert_eq!(
+            config.host_extra_directives("work"),
+            pairs(&[("IdentitiesOnly", "yes")])
+        );
+    }
+
     #[test]
     fn is_included_host_false_for_main_config() {
         let config = parse_str(
@@ -1370,83 +2740,83 @@ mod tests {
     #[test]
     fn askpass_returns_none_when_absent() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
     }
 
     #[test]
     fn askpass_returns_keychain() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass keychain\n");
-        assert_eq!(first_block(&config).askpass(), Some("keychain".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("keychain".to_string()));
     }
 
     #[test]
     fn askpass_returns_op_uri() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass op://Vault/Item/field\n");
-        assert_eq!(first_block(&config).askpass(), Some("op://Vault/Item/field".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("op://Vault/Item/field".to_string()));
     }
 
     #[test]
     fn askpass_returns_vault_with_field() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass vault:secret/ssh#password\n");
-        assert_eq!(first_block(&config).askpass(), Some("vault:secret/ssh#password".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("vault:secret/ssh#password".to_string()));
     }
 
     #[test]
     fn askpass_returns_bw_source() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass bw:my-item\n");
-        assert_eq!(first_block(&config).askpass(), Some("bw:my-item".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("bw:my-item".to_string()));
     }
 
     #[test]
     fn askpass_returns_pass_source() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass pass:ssh/prod\n");
-        assert_eq!(first_block(&config).askpass(), Some("pass:ssh/prod".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("pass:ssh/prod".to_string()));
     }
 
     #[test]
     fn askpass_returns_custom_command() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass get-pass %a %h\n");
-        assert_eq!(first_block(&config).askpass(), Some("get-pass %a %h".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("get-pass %a %h".to_string()));
     }
 
     #[test]
     fn askpass_ignores_empty_value() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass \n");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
     }
 
     #[test]
     fn askpass_ignores_non_askpass_purple_comments() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:tags prod\n");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
     }
 
     #[test]
     fn set_askpass_adds_comment() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", "keychain");
-        assert_eq!(first_block(&config).askpass(), Some("keychain".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("keychain".to_string()));
     }
 
     #[test]
     fn set_askpass_replaces_existing() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass keychain\n");
         config.set_host_askpass("myserver", "op://V/I/p");
-        assert_eq!(first_block(&config).askpass(), Some("op://V/I/p".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("op://V/I/p".to_string()));
     }
 
     #[test]
     fn set_askpass_empty_removes_comment() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass keychain\n");
         config.set_host_askpass("myserver", "");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
     }
 
     #[test]
     fn set_askpass_preserves_other_directives() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n  User admin\n  # purple:tags prod\n");
         config.set_host_askpass("myserver", "vault:secret/ssh");
-        assert_eq!(first_block(&config).askpass(), Some("vault:secret/ssh".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("vault:secret/ssh".to_string()));
         let entry = first_block(&config).to_host_entry();
         assert_eq!(entry.user, "admin");
         assert!(entry.tags.contains(&"prod".to_string()));
@@ -1466,7 +2836,7 @@ mod tests {
     fn set_askpass_on_nonexistent_host() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("nohost", "keychain");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
     }
 
     #[test]
@@ -1489,14 +2859,14 @@ mod tests {
     fn set_askpass_vault_with_hash_field() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", "vault:secret/data/team#api_key");
-        assert_eq!(first_block(&config).askpass(), Some("vault:secret/data/team#api_key".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("vault:secret/data/team#api_key".to_string()));
     }
 
     #[test]
     fn set_askpass_custom_command_with_percent() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", "get-pass %a %h");
-        assert_eq!(first_block(&config).askpass(), Some("get-pass %a %h".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("get-pass %a %h".to_string()));
     }
 
     #[test]
@@ -1504,19 +2874,19 @@ mod tests {
         let mut config = parse_str("Host alpha\n  HostName a.com\n\nHost beta\n  HostName b.com\n");
         config.set_host_askpass("alpha", "keychain");
         config.set_host_askpass("beta", "vault:secret/ssh");
-        assert_eq!(block_by_index(&config, 0).askpass(), Some("keychain".to_string()));
-        assert_eq!(block_by_index(&config, 1).askpass(), Some("vault:secret/ssh".to_string()));
+        assert_eq!(block_by_index(&config, 0).to_host_entry().askpass, Some("keychain".to_string()));
+        assert_eq!(block_by_index(&config, 1).to_host_entry().askpass, Some("vault:secret/ssh".to_string()));
     }
 
     #[test]
     fn set_askpass_then_clear_then_set_again() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", "keychain");
-        assert_eq!(first_block(&config).askpass(), Some("keychain".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("keychain".to_string()));
         config.set_host_askpass("myserver", "");
-        assert_eq!(first_block(&config).askpass(), None);
+        assert_eq!(first_block(&config).to_host_entry().askpass, None);
         config.set_host_askpass("myserver", "op://V/I/p");
-        assert_eq!(first_block(&config).askpass(), Some("op://V/I/p".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("op://V/I/p".to_string()));
     }
 
     #[test]
@@ -1533,7 +2903,7 @@ mod tests {
     fn askpass_coexists_with_provider_comment() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:provider do:123\n  # purple:askpass keychain\n");
         let block = first_block(&config);
-        assert_eq!(block.askpass(), Some("keychain".to_string()));
+        assert_eq!(block.to_host_entry().askpass, Some("keychain".to_string()));
         assert!(block.provider().is_some());
     }
 
@@ -1551,7 +2921,7 @@ mod tests {
     fn askpass_idempotent_set_same_value() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass keychain\n");
         config.set_host_askpass("myserver", "keychain");
-        assert_eq!(first_block(&config).askpass(), Some("keychain".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("keychain".to_string()));
         let serialized = config.serialize();
         assert_eq!(serialized.matches("purple:askpass").count(), 1, "Should have exactly one askpass comment");
     }
@@ -1560,13 +2930,13 @@ mod tests {
     fn askpass_with_value_containing_equals() {
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", "cmd --opt=val %h");
-        assert_eq!(first_block(&config).askpass(), Some("cmd --opt=val %h".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("cmd --opt=val %h".to_string()));
     }
 
     #[test]
     fn askpass_with_value_containing_hash() {
         let config = parse_str("Host myserver\n  HostName 10.0.0.1\n  # purple:askpass vault:a/b#c\n");
-        assert_eq!(first_block(&config).askpass(), Some("vault:a/b#c".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("vault:a/b#c".to_string()));
     }
 
     #[test]
@@ -1574,7 +2944,7 @@ mod tests {
         let uri = "op://My Personal Vault/SSH Production Server/password";
         let mut config = parse_str("Host myserver\n  HostName 10.0.0.1\n");
         config.set_host_askpass("myserver", uri);
-        assert_eq!(first_block(&config).askpass(), Some(uri.to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some(uri.to_string()));
     }
 
     #[test]
@@ -1610,7 +2980,7 @@ Host myserver
     #[test]
     fn askpass_with_crlf_line_endings() {
         let config = parse_str("Host myserver\r\n  HostName 10.0.0.1\r\n  # purple:askpass keychain\r\n");
-        assert_eq!(first_block(&config).askpass(), Some("keychain".to_string()));
+        assert_eq!(first_block(&config).to_host_entry().askpass, Some("keychain".to_string()));
     }
 
     #[test]
@@ -1728,4 +3098,340 @@ Host myserver
         assert_eq!(entry.askpass, Some("pass:ssh/prod".to_string()));
         assert!(entry.tags.contains(&"prod".to_string()));
     }
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn match_set_directives_keeps_unchanged_lines() {
+        let config_str = "\
+Match host *.example.com
+\tForwardAgent=yes # agent
+\t# keep me
+\tUser deploy
+";
+        let mut config = parse_str(config_str);
+        assert!(config.update_match_block(
+            0,
+            "host *.example.com",
+            &pairs(&[("ForwardAgent", "yes"), ("User", "admin")]),
+        ));
+        assert_eq!(
+            config.serialize(),
+            "Match host *.example.com\n\tForwardAgent=yes # agent\n\t# keep me\n\tUser admin\n"
+        );
+    }
+
+    #[test]
+    fn match_set_directives_removes_and_appends() {
+        let config_str = "\
+Match all
+  User deploy
+  Compression yes
+
+Host web
+  HostName 10.0.0.1
+";
+        let mut config = parse_str(config_str);
+        config.update_match_block(0, "all", &pairs(&[("User", "deploy")]));
+        let output = config.serialize();
+        assert!(!output.contains("Compression"));
+        config.update_match_block(0, "all", &pairs(&[("User", "deploy"), ("ServerAliveInterval", "30")]));
+        assert_eq!(
+            config.serialize(),
+            "Match all\n  User deploy\n  ServerAliveInterval 30\n\nHost web\n  HostName 10.0.0.1\n"
+        );
+    }
+
+    #[test]
+    fn match_set_criteria_rewrites_line_only_when_changed() {
+        let mut config = parse_str("Match   host  a  # note\n  User x\n");
+        config.update_match_block(0, "host  a", &pairs(&[("User", "x")]));
+        assert_eq!(config.serialize(), "Match   host  a  # note\n  User x\n");
+        config.update_match_block(0, "user root", &pairs(&[("User", "x")]));
+        let block = config.match_blocks()[0];
+        assert_eq!(block.raw_match_line, "Match user root");
+        assert_eq!(block.criteria[0].kind, MatchKind::User);
+    }
+
+    #[test]
+    fn add_match_block_appends_with_separator() {
+        let mut config = parse_str("Host web\n  HostName 10.0.0.1\n");
+        config.add_match_block("exec \"test -f /tmp/vpn\"", &pairs(&[("ProxyJump", "bastion")]));
+        assert_eq!(
+            config.serialize(),
+            "Host web\n  HostName 10.0.0.1\n\nMatch exec \"test -f /tmp/vpn\"\n  ProxyJump bastion\n"
+        );
+        assert_eq!(config.host_entries().len(), 1);
+    }
+
+    #[test]
+    fn delete_match_block_by_index() {
+        let config_str = "\
+Match host a
+  User one
+
+Match host b
+  User two
+
+Host web
+  HostName 10.0.0.1
+";
+        let mut config = parse_str(config_str);
+        assert!(config.delete_match_block(1));
+        assert!(!config.delete_match_block(1));
+        let output = config.serialize();
+        assert!(output.contains("Match host a"));
+        assert!(!output.contains("Match host b"));
+        assert!(!output.contains("User two"));
+        assert!(output.contains("Host web"));
+    }
+
+    #[test]
+    fn update_match_block_out_of_range() {
+        let mut config = parse_str("Host web\n  HostName 10.0.0.1\n");
+        assert!(!config.update_match_block(0, "all", &[]));
+    }
+
+    #[test]
+    fn rename_host_updates_jump_chains_and_proxy_commands() {
+        let mut config = config_with_include(
+            "Host bastion\n  HostName 1.1.1.1\n\nHost app\n  ProxyJump admin@bastion:2222,bastion-2 # via bastion\n\nMatch host db\n  ProxyCommand ssh -W %h:%p ops@bastion\n",
+            "Host work\n  ProxyJump ssh://bastion,other\n",
+        );
+        let touched = config.rename_host("bastion", "jump").unwrap();
+        assert_eq!(touched, vec!["app", "Match host db", "work"]);
+        let output = config.serialize();
+        assert!(output.starts_with("Host jump\n"));
+        // Inline comment kept, bastion-2 is a different host
+        assert!(output.contains("  ProxyJump admin@jump:2222,bastion-2 # via bastion\n"));
+        assert!(output.contains("  ProxyCommand ssh -W %h:%p ops@jump\n"));
+        let work = config.host_entries().into_iter().find(|h| h.alias == "work").unwrap();
+        assert_eq!(work.proxy_jump, "ssh://jump,other");
+    }
+
+    #[test]
+    fn rename_host_leaves_unrelated_values_alone() {
+        let mut config = parse_str(
+            "Host web\n  HostName web\n\nHost other\n  ProxyCommand nc web.example.com 22\n  ProxyJump webby\n",
+        );
+        assert!(config.rename_host("missing", "x").is_none());
+        assert!(config.rename_host("web", "www").unwrap().is_empty());
+        // HostName is not a reference
+        assert_eq!(config.host_entries()[0].hostname, "web");
+        assert!(config.serialize().contains("ProxyCommand nc web.example.com 22"));
+    }
+
+    #[test]
+    fn rename_host_only_rewrites_proxy_command_host_arguments() {
+        let mut config = parse_str(
+            "Host bastion\n  HostName 1.1.1.1\n\nHost a\n  ProxyCommand bastion --target %h\n\nHost b\n  ProxyCommand ssh -i bastion -o User=bastion -W %h:%p bastion\n\nHost c\n  ProxyCommand nc -x bastion:1080 bastion 22\n\nHost d\n  ProxyCommand exec ssh -J ops@bastion -W internal:22 gw bastion\n\nHost e\n  ProxyCommand ssh -Wbastion:22 gw\n",
+        );
+        let touched = config.rename_host("bastion", "jump").unwrap();
+        assert_eq!(touched, vec!["b", "c", "d", "e"]);
+        let output = config.serialize();
+        assert!(output.contains("  ProxyCommand bastion --target %h\n"));
+        assert!(output.contains("  ProxyCommand ssh -i bastion -o User=bastion -W %h:%p jump\n"));
+        assert!(output.contains("  ProxyCommand nc -x bastion:1080 jump 22\n"));
+        // The remote command after the destination is not a host argument
+        assert!(output.contains("  ProxyCommand exec ssh -J ops@jump -W internal:22 gw bastion\n"));
+        assert!(output.contains("  ProxyCommand ssh -Wjump:22 gw\n"));
+    }
+
+    #[test]
+    fn rename_host_updates_disabled_hosts_and_templates() {
+        let mut config = parse_str(
+            "Host bastion\n  HostName 1.1.1.1\n\n# purple:disabled\n# Host old\n#   ProxyJump bastion # via gw\n\n# purple:template\n# Host work\n#\tProxyJump admin@bastion\n",
+        );
+        let touched = config.rename_host("bastion", "jump").unwrap();
+        assert_eq!(touched, vec!["old", "template work"]);
+        let output = config.serialize();
+        assert!(output.contains("# Host old\n#   ProxyJump jump # via gw\n"));
+        assert!(output.contains("# Host work\n#\tProxyJump admin@jump\n"));
+        let reparsed = parse_str(&output);
+        let old = reparsed.host_entries().into_iter().find(|h| h.alias == "old").unwrap();
+        assert_eq!(old.proxy_jump, "jump");
+    }
+
+    #[test]
+    fn disable_and_enable_round_trip() {
+        let content = "Host web\n  HostName 10.0.0.1\n  User admin\n\n# db below\nHost db\n  HostName 10.0.0.2\n";
+        let mut config = parse_str(content);
+        assert!(config.disable_host("web"));
+        assert!(!config.disable_host("web"));
+        let output = config.serialize();
+        assert!(output.starts_with(
+            "# purple:disabled\n# Host web\n#   HostName 10.0.0.1\n#   User admin\n\n# db below\nHost db\n"
+        ));
+
+        // Reparsed, the host is still known but flagged
+        let mut reparsed = parse_str(&output);
+        let web = reparsed.host_entries().into_iter().find(|h| h.alias == "web").unwrap();
+        assert!(web.disabled);
+        assert_eq!(web.hostname, "10.0.0.1");
+        assert!(reparsed.has_host("web"));
+        assert_eq!(reparsed.serialize(), output);
+
+        assert!(reparsed.enable_host("web"));
+        assert!(!reparsed.enable_host("web"));
+        assert_eq!(reparsed.serialize(), content);
+    }
+
+    #[test]
+    fn disabled_marker_needs_a_host_line() {
+        let content = "# purple:disabled\n# just a note\nHost web\n  HostName 10.0.0.1\n";
+        let config = parse_str(content);
+        let entries = config.host_entries();
+        assert_eq!(entries.len(), 1);
+        assert!(!entries[0].disabled);
+        assert_eq!(config.serialize(), content);
+    }
+
+    #[test]
+    fn disabled_provider_host_stays_known() {
+        let mut config = parse_str(
+            "Host do-web\n  HostName 1.2.3.4\n  # purple:provider digitalocean:42\n",
+        );
+        assert!(config.disable_host("do-web"));
+        let reparsed = parse_str(&config.serialize());
+        assert_eq!(
+            reparsed.find_hosts_by_provider("digitalocean"),
+            vec![("do-web".to_string(), "42".to_string())]
+        );
+    }
+
+    #[test]
+    fn disable_host_in_included_file() {
+        let mut config = config_with_include("Host main\n  HostName 10.0.0.1\n", "Host a\n  HostName 1.1.1.1\n");
+        assert!(config.disable_host("a"));
+        assert!(matches!(&included_elements(&config)[0], ConfigElement::DisabledHost(d) if d.block.host_pattern == "a"));
+        assert!(config.enable_host("a"));
+        assert!(matches!(&included_elements(&config)[0], ConfigElement::HostBlock(b) if b.host_pattern == "a"));
+    }
+
+    #[test]
+    fn clone_host_copies_block_below_source_without_provider() {
+        let mut config = parse_str(
+            "Host web\n    HostName 10.0.0.1\n    # keep me\n    ServerAliveInterval 30\n    LocalForward 8080 localhost:80\n    # purple:tags prod\n    # purple:provider digitalocean:123\n\n# purple:group Other\n\nHost web-copy\n  HostName 10.0.0.2\n",
+        );
+        assert_eq!(config.clone_host("web").as_deref(), Some("web-copy-2"));
+        assert_eq!(
+            config.serialize(),
+            "Host web\n    HostName 10.0.0.1\n    # keep me\n    ServerAliveInterval 30\n    LocalForward 8080 localhost:80\n    # purple:tags prod\n    # purple:provider digitalocean:123\n\nHost web-copy-2\n    HostName 10.0.0.1\n    # keep me\n    ServerAliveInterval 30\n    LocalForward 8080 localhost:80\n    # purple:tags prod\n\n# purple:group Other\n\nHost web-copy\n  HostName 10.0.0.2\n"
+        );
+        assert_eq!(config.find_hosts_by_provider("digitalocean"), vec![("web".to_string(), "123".to_string())]);
+        assert!(config.clone_host("nope").is_none());
+    }
+
+    #[test]
+    fn clone_last_host_and_included_host() {
+        let mut config = parse_str("Host a\n  HostName 10.0.0.1\n");
+        assert_eq!(config.clone_host("a").as_deref(), Some("a-copy"));
+        assert_eq!(config.serialize(), "Host a\n  HostName 10.0.0.1\n\nHost a-copy\n  HostName 10.0.0.1\n");
+
+        let mut config = config_with_include("Host main\n  HostName 10.0.0.1\n", "Host b\n  HostName 1.1.1.1\n");
+        assert_eq!(config.clone_host("b").as_deref(), Some("b-copy"));
+        let aliases: Vec<&str> = included_elements(&config)
+            .iter()
+            .filter_map(|e| match e {
+                ConfigElement::HostBlock(b) => Some(b.host_pattern.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(aliases, vec!["b", "b-copy"]);
+    }
+
+    #[test]
+    fn notes_round_trip_and_survive_update_host() {
+        let mut config = parse_str("Host web\n  HostName 10.0.0.1\n  # purple:tags prod\n\nHost db\n  HostName 10.0.0.2\n");
+        let notes = vec!["owner: platform".to_string(), String::new(), "  patch window sun 02:00".to_string()];
+        config.set_host_notes("web", &notes);
+        let output = config.serialize();
+        assert!(output.contains(
+            "  # purple:tags prod\n  # purple:note owner: platform\n  # purple:note\n  # purple:note   patch window sun 02:00\n\nHost db"
+        ));
+        let mut reparsed = parse_str(&output);
+        assert_eq!(reparsed.host_entries()[0].notes, notes);
+
+        // Form edits go through update_host, which leaves comments alone
+        let mut entry = reparsed.host_entries()[0].clone();
+        entry.hostname = "10.0.0.9".to_string();
+        entry.user = "admin".to_string();
+        reparsed.update_host("web", &entry);
+        assert_eq!(reparsed.host_entries()[0].notes, notes);
+
+        // Replaced in place, removed when empty
+        reparsed.set_host_notes("web", &["moved".to_string()]);
+        assert_eq!(reparsed.host_entries()[0].notes, vec!["moved"]);
+        reparsed.set_host_notes("web", &[]);
+        assert!(reparsed.host_entries()[0].notes.is_empty());
+        assert!(!reparsed.serialize().contains("purple:note"));
+    }
+
+    #[test]
+    fn update_host_keeps_key_file_order_and_formatting() {
+        let mut config = parse_str(
+            "Host web\n  HostName 10.0.0.1\n  IdentityFile=~/.ssh/a\n  User deploy\n  IdentityFile ~/.ssh/b\n  CertificateFile ~/.ssh/a-cert.pub\n",
+        );
+        let mut entry = config.host_entries().remove(0);
+        assert_eq!(entry.identity_files, vec!["~/.ssh/a", "~/.ssh/b"]);
+        assert_eq!(entry.certificate_files, vec!["~/.ssh/a-cert.pub"]);
+
+        // Unchanged lists leave every line alone
+        entry.hostname = "10.0.0.2".to_string();
+        config.update_host("web", &entry);
+        assert_eq!(
+            config.serialize(),
+            "Host web\n  HostName 10.0.0.2\n  IdentityFile=~/.ssh/a\n  User deploy\n  IdentityFile ~/.ssh/b\n  CertificateFile ~/.ssh/a-cert.pub\n"
+        );
+
+        // Edits happen in place, extra keys land after the last one of their kind
+        entry.identity_files = vec!["~/.ssh/a".to_string(), "~/.ssh/c".to_string(), "~/.ssh/d".to_string()];
+        entry.certificate_files.clear();
+        config.update_host("web", &entry);
+        assert_eq!(
+            config.serialize(),
+            "Host web\n  HostName 10.0.0.2\n  IdentityFile=~/.ssh/a\n  User deploy\n  IdentityFile ~/.ssh/c\n  IdentityFile ~/.ssh/d\n"
+        );
+        let reparsed = parse_str(&config.serialize());
+        assert_eq!(reparsed.host_entries()[0].identity_files, entry.identity_files);
+    }
+
+    #[test]
+    fn argv_split_follows_openssh_quoting() {
+        let split = |s: &str| argv_split(s).unwrap();
+        assert_eq!(split("a  b\tc"), vec!["a", "b", "c"]);
+        assert_eq!(split("\"my box\" 'it''s' x\"y z\""), vec!["my box", "its", "xy z"]);
+        assert_eq!(split(r#"a\ b "q\"uote" back\\slash keep\n"#), vec!["a b", "q\"uote", "back\\slash", "keep\\n"]);
+        assert_eq!(split("a #comment"), vec!["a"]);
+        assert_eq!(split("a#b \"\""), vec!["a#b", ""]);
+        assert_eq!(argv_split("\"open"), None);
+    }
+
+    #[test]
+    fn quote_arg_round_trips_through_argv_split() {
+        for value in ["plain", "~/keys/with space", "say \"hi\"", "c:\\keys", "it's", "#hash", ""] {
+            let quoted = quote_arg(value);
+            assert_eq!(unquote(&quoted), value, "{}", quoted);
+        }
+        assert_eq!(quote_arg("~/.ssh/id_ed25519"), "~/.ssh/id_ed25519");
+        assert_eq!(quote_arg("~/keys/with space"), "\"~/keys/with space\"");
+        assert_eq!(unquote("two words"), "two words");
+    }
+
+    #[test]
+    fn quoted_alias_with_space_is_not_a_pattern() {
+        assert!(!is_host_pattern("\"my box\""));
+        assert!(is_host_pattern("my box"));
+        assert!(is_host_pattern("\"web *\""));
+        assert!(is_host_pattern("a \"b c\""));
+        let config = parse_str("Host \"my box\"\n  HostName 10.0.0.1\n");
+        assert_eq!(config.host_entries()[0].alias, "my box");
+        assert!(config.has_host("my box"));
+        assert!(!config.has_host("my"));
+    }
 }
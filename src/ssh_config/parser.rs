@@ -3,7 +3,8 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result};
 
 use super::model::{
-    ConfigElement, Directive, HostBlock, IncludeDirective, IncludedFile, SshConfigFile,
+    ConfigElement, DISABLED_MARKER, TEMPLATE_MARKER, Directive, CommentedBlock, HostBlock, IncludeDirective,
+    IncludedFile, MatchBlock, MatchCriterion, SshConfigFile,
 };
 
 const MAX_INCLUDE_DEPTH: usize = 5;
@@ -27,11 +28,14 @@ impl SshConfigFile {
         let config_dir = path.parent().map(|p| p.to_path_buf());
         let elements = Self::parse_content_with_includes(&content, config_dir.as_deref(), depth);
 
-        Ok(SshConfigFile {
+        let mut config = SshConfigFile {
             elements,
             path: path.to_path_buf(),
             crlf,
-        })
+            index: Default::default(),
+        };
+        config.refresh_index();
+        Ok(config)
     }
 
     /// Parse SSH config content from a string (without Include resolution).
@@ -41,6 +45,45 @@ impl SshConfigFile {
         Self::parse_content_with_includes(content, None, MAX_INCLUDE_DEPTH)
     }
 
+    /// Re-read a single resolved Include file and replace its elements in place.
+    /// Returns false when `path` isn't a resolved Include file or can't be read,
+    /// in which case the caller should fall back to a full parse.
+    pub fn reload_included_file(&mut self, path: &Path) -> bool {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return false;
+        };
+        if !Self::replace_included(&mut self.elements, path, &content, 0) {
+            return false;
+        }
+        self.invalidate_index();
+        self.refresh_index();
+        true
+    }
+
+    fn replace_included(
+        elements: &mut [ConfigElement],
+        path: &Path,
+        content: &str,
+        depth: usize,
+    ) -> bool {
+        for element in elements {
+            let ConfigElement::Include(include) = element else {
+                continue;
+            };
+            for file in &mut include.resolved_files {
+                if file.path == path {
+                    file.elements =
+                        Self::parse_content_with_includes(content, path.parent(), depth + 1);
+                    return true;
+                }
+                if Self::replace_included(&mut file.elements, path, content, depth + 1) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
     /// Parse SSH config content, optionally resolving Include directives.
     fn parse_content_with_includes(
         content: &str,
@@ -49,19 +92,39 @@ impl SshConfigFile {
     ) -> Vec<ConfigElement> {
         let mut elements = Vec::new();
         let mut current_block: Option<HostBlock> = None;
+        let mut current_match: Option<MatchBlock> = None;
 
-        for line in content.lines() {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut next = 0;
+        while next < lines.len() {
+            let line = lines[next];
+            next += 1;
             let trimmed = line.trim();
 
+            // A disabled host or template: the marker followed by a commented-out Host block
+            let marker = line.trim_end();
+            if marker == DISABLED_MARKER || marker == TEMPLATE_MARKER {
+                if let Some((commented, used)) = Self::parse_commented_block(&lines[next - 1..]) {
+                    Self::flush_block(&mut elements, &mut current_block, &mut current_match);
+                    elements.push(if marker == DISABLED_MARKER {
+                        ConfigElement::DisabledHost(commented)
+                    } else {
+                        ConfigElement::Template(commented)
+                    });
+                    next += used - 1;
+                    continue;
+                }
+            }
+
+            let in_block = current_block.is_some() || current_match.is_some();
+
             // Check for Include directive.
-            // An indented Include inside a Host block is preserved as a directive
-            // (not a top-level Include). A non-indented Include flushes the block.
+            // An indented Include inside a Host or Match block is preserved as a
+            // directive (not a top-level Include). A non-indented Include flushes the block.
             let is_indented = line.starts_with(' ') || line.starts_with('\t');
-            if !(current_block.is_some() && is_indented) {
+            if !(in_block && is_indented) {
                 if let Some(pattern) = Self::parse_include_line(trimmed) {
-                    if let Some(block) = current_block.take() {
-                        elements.push(ConfigElement::HostBlock(block));
-                    }
+                    Self::flush_block(&mut elements, &mut current_block, &mut current_match);
                     let resolved = if depth < MAX_INCLUDE_DEPTH {
                         Self::resolve_include(pattern, config_dir, depth)
                     } else {
@@ -76,22 +139,24 @@ impl SshConfigFile {
                 }
             }
 
-            // Non-indented Match line = block boundary (flush current Host block).
-            // Match blocks are stored as GlobalLines (inert, never edited/deleted).
-            if !is_indented && Self::is_match_line(trimmed) {
-                if let Some(block) = current_block.take() {
-                    elements.push(ConfigElement::HostBlock(block));
+            // Non-indented Match line starts a Match block (flushes the current block).
+            if !is_indented {
+                if let Some(criteria_text) = Self::parse_match_line(trimmed) {
+                    Self::flush_block(&mut elements, &mut current_block, &mut current_match);
+                    current_match = Some(MatchBlock {
+                        criteria: MatchCriterion::parse_list(&criteria_text),
+                        criteria_text,
+                        raw_match_line: line.to_string(),
+                        directives: Vec::new(),
+                    });
+                    continue;
                 }
-                elements.push(ConfigElement::GlobalLine(line.to_string()));
-                continue;
             }
 
             // Check if this line starts a new Host block
             if let Some(pattern) = Self::parse_host_line(trimmed) {
                 // Flush the previous block if any
-                if let Some(block) = current_block.take() {
-                    elements.push(ConfigElement::HostBlock(block));
-                }
+                Self::flush_block(&mut elements, &mut current_block, &mut current_match);
                 current_block = Some(HostBlock {
                     host_pattern: pattern,
                     raw_host_line: line.to_string(),
@@ -100,18 +165,23 @@ impl SshConfigFile {
                 continue;
             }
 
-            // If we're inside a Host block, add this line as a directive
-            if let Some(ref mut block) = current_block {
+            // If we're inside a Host or Match block, add this line as a directive
+            let directives = match (&mut current_block, &mut current_match) {
+                (Some(block), _) => Some(&mut block.directives),
+                (None, Some(block)) => Some(&mut block.directives),
+                (None, None) => None,
+            };
+            if let Some(directives) = directives {
                 if trimmed.is_empty() || trimmed.starts_with('#') {
-                    // Comment or blank line inside a host block
-                    block.directives.push(Directive {
+                    // Comment or blank line inside a block
+                    directives.push(Directive {
                         key: String::new(),
                         value: String::new(),
                         raw_line: line.to_string(),
                         is_non_directive: true,
                     });
                 } else if let Some((key, value)) = Self::parse_directive(trimmed) {
-                    block.directives.push(Directive {
+                    directives.push(Directive {
                         key,
                         value,
                         raw_line: line.to_string(),
@@ -119,7 +189,7 @@ impl SshConfigFile {
                     });
                 } else {
                     // Unrecognized line format — preserve verbatim
-                    block.directives.push(Directive {
+                    directives.push(Directive {
                         key: String::new(),
                         value: String::new(),
                         raw_line: line.to_string(),
@@ -133,13 +203,65 @@ impl SshConfigFile {
         }
 
         // Flush the last block
-        if let Some(block) = current_block {
-            elements.push(ConfigElement::HostBlock(block));
-        }
+        Self::flush_block(&mut elements, &mut current_block, &mut current_match);
 
         elements
     }
 
+    /// Parse a disabled host or template starting at the marker line. The block runs over
+    /// the commented lines after it, up to a line that isn't a comment, the
+    /// next marker or a commented Host/Match line. Returns the block and the
+    /// number of lines it spans, or None when no commented Host line follows.
+    fn parse_commented_block(lines: &[&str]) -> Option<(CommentedBlock, usize)> {
+        let uncomment = |line: &str| -> Option<String> {
+            let rest = line.strip_prefix('#')?;
+            Some(rest.strip_prefix(' ').unwrap_or(rest).to_string())
+        };
+        let host_line = uncomment(lines.get(1)?)?;
+        Self::parse_host_line(host_line.trim())?;
+        let mut uncommented = vec![host_line];
+        for line in &lines[2..] {
+            if line.trim_end() == DISABLED_MARKER
+                || line.trim_end() == TEMPLATE_MARKER
+                || super::groups::group_name(line).is_some()
+            {
+                break;
+            }
+            let Some(text) = uncomment(line) else {
+                break;
+            };
+            let trimmed = text.trim();
+            if Self::parse_host_line(trimmed).is_some() || Self::parse_match_line(trimmed).is_some() {
+                break;
+            }
+            uncommented.push(text);
+        }
+        let used = uncommented.len() + 1;
+        let mut parsed = Self::parse_content_with_includes(&uncommented.join("\n"), None, MAX_INCLUDE_DEPTH);
+        let Some(ConfigElement::HostBlock(block)) = parsed.pop() else {
+            return None;
+        };
+        if !parsed.is_empty() {
+            return None;
+        }
+        let raw_lines = lines[..used].iter().map(|l| l.to_string()).collect();
+        Some((CommentedBlock { raw_lines, block }, used))
+    }
+
+    /// Push the open Host or Match block (if any) onto the element list.
+    fn flush_block(
+        elements: &mut Vec<ConfigElement>,
+        current_block: &mut Option<HostBlock>,
+        current_match: &mut Option<MatchBlock>,
+    ) {
+        if let Some(block) = current_block.take() {
+            elements.push(ConfigElement::HostBlock(block));
+        }
+        if let Some(block) = current_match.take() {
+            elements.push(ConfigElement::MatchBlock(block));
+        }
+    }
+
     /// Parse an Include directive line. Returns the pattern if it matches.
     /// Handles both space and tab between keyword and value (SSH allows either).
     fn parse_include_line(trimmed: &str) -> Option<&str> {
@@ -195,6 +317,7 @@ impl SshConfigFile {
                                 files.push(IncludedFile {
                                     path: path.clone(),
                                     elements,
+                                    crlf: content.contains("\r\n"),
                                 });
                             }
                             Err(e) => {
@@ -243,17 +366,22 @@ impl SshConfigFile {
     }
 
     /// Check if a line is a "Match ..." line (block boundary).
-    fn is_match_line(trimmed: &str) -> bool {
+    /// Returns the criteria text (inline comment stripped, may be empty).
+    fn parse_match_line(trimmed: &str) -> Option<String> {
         let mut parts = trimmed.splitn(2, [' ', '\t']);
         let keyword = parts.next().unwrap_or("");
-        keyword.eq_ignore_ascii_case("match")
+        if !keyword.eq_ignore_ascii_case("match") {
+            return None;
+        }
+        let criteria = parts.next().unwrap_or("").trim();
+        Some(strip_inline_comment(criteria).to_string())
     }
 
     /// Parse a "Key Value" directive line.
     /// Matches OpenSSH behavior: keyword ends at first whitespace or `=`.
     /// An `=` in the value portion (e.g. `IdentityFile ~/.ssh/id=prod`) is
     /// NOT treated as a separator.
-    fn parse_directive(trimmed: &str) -> Option<(String, String)> {
+    pub(crate) fn parse_directive(trimmed: &str) -> Option<(String, String)> {
         // Find end of keyword: first whitespace or '='
         let key_end = trimmed.find(|c: char| c.is_whitespace() || c == '=')?;
         let key = &trimmed[..key_end];
@@ -275,19 +403,18 @@ impl SshConfigFile {
 }
 
 /// Strip an inline comment (`# ...` preceded by whitespace) from a parsed value,
-/// respecting double-quoted strings.
+/// respecting single- and double-quoted strings.
 fn strip_inline_comment(value: &str) -> &str {
     let bytes = value.as_bytes();
-    let mut in_quote = false;
+    let mut quote = None;
     for i in 0..bytes.len() {
-        if bytes[i] == b'"' {
-            in_quote = !in_quote;
-        } else if !in_quote
-            && bytes[i] == b'#'
-            && i > 0
-            && (bytes[i - 1] == b' ' || bytes[i - 1] == b'\t')
-        {
-            return value[..i].trim_end();
+        match (quote, bytes[i]) {
+            (None, b'"' | b'\'') => quote = Some(bytes[i]),
+            (Some(q), b) if b == q => quote = None,
+            (None, b'#') if i > 0 && (bytes[i - 1] == b' ' || bytes[i - 1] == b'\t') => {
+                return value[..i].trim_end();
+            }
+            _ => {}
         }
     }
     value
@@ -296,6 +423,7 @@ fn strip_inline_comment(value: &str) -> &str {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ssh_config::model::MatchKind;
     use std::path::PathBuf;
 
     fn parse_str(content: &str) -> SshConfigFile {
@@ -303,6 +431,7 @@ mod tests {
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: content.contains("\r\n"),
+            index: Default::default(),
         }
     }
 
@@ -392,7 +521,7 @@ Host bastion
 ";
         let config = parse_str(content);
         let entries = config.host_entries();
-        assert_eq!(entries[0].identity_file, "~/.ssh/id_ed25519");
+        assert_eq!(entries[0].identity_files, vec!["~/.ssh/id_ed25519"]);
         assert_eq!(entries[0].proxy_jump, "gateway");
     }
 
@@ -506,7 +635,7 @@ Host myserver
         let config = parse_str(content);
         let entries = config.host_entries();
         assert_eq!(entries.len(), 1);
-        assert_eq!(entries[0].identity_file, "~/.ssh/id=prod");
+        assert_eq!(entries[0].identity_files, vec!["~/.ssh/id=prod"]);
     }
 
     #[test]
@@ -559,7 +688,7 @@ Host myserver
     }
 
     #[test]
-    fn test_match_block_is_global_line() {
+    fn test_match_block_parsed() {
         let content = "\
 Host myserver
   HostName 10.0.0.1
@@ -568,13 +697,64 @@ Match host *.example.com
   ForwardAgent yes
 ";
         let config = parse_str(content);
-        // Match line should flush the Host block and become a GlobalLine
+        // Match line should flush the Host block and start a MatchBlock
         let host_count = config.elements.iter().filter(|e| matches!(e, ConfigElement::HostBlock(_))).count();
         assert_eq!(host_count, 1);
-        // Match line itself
-        assert!(config.elements.iter().any(|e| matches!(e, ConfigElement::GlobalLine(s) if s == "Match host *.example.com")));
-        // Indented lines after Match (no current_block) become GlobalLines
-        assert!(config.elements.iter().any(|e| matches!(e, ConfigElement::GlobalLine(s) if s.contains("ForwardAgent"))));
+        let block = config.match_blocks()[0];
+        assert_eq!(block.raw_match_line, "Match host *.example.com");
+        assert_eq!(block.criteria_text, "host *.example.com");
+        // Indented lines after Match become directives of the Match block
+        assert_eq!(block.directives[0].key, "ForwardAgent");
+        assert_eq!(block.directives[0].value, "yes");
+    }
+
+    #[test]
+    fn test_match_criteria_parsed() {
+        let config = parse_str(
+            "Match canonical !user root exec \"test -f ~/.vpn\" originalhost web*,db* # office\n  User deploy\n",
+        );
+        let block = config.match_blocks()[0];
+        assert_eq!(block.criteria.len(), 4);
+        assert_eq!(block.criteria[0].kind, MatchKind::Canonical);
+        assert_eq!(block.criteria[0].arg, None);
+        assert_eq!(block.criteria[1].kind, MatchKind::User);
+        assert!(block.criteria[1].negated);
+        assert_eq!(block.criteria[1].arg.as_deref(), Some("root"));
+        assert_eq!(block.criteria[2].kind, MatchKind::Exec);
+        assert_eq!(block.criteria[2].arg.as_deref(), Some("test -f ~/.vpn"));
+        assert_eq!(block.criteria[3].kind, MatchKind::OriginalHost);
+        assert_eq!(block.criteria[3].arg.as_deref(), Some("web*,db*"));
+    }
+
+    #[test]
+    fn test_match_criteria_all_and_final() {
+        let config = parse_str("Match final all\n  ServerAliveInterval 60\n");
+        let block = config.match_blocks()[0];
+        assert_eq!(block.criteria.len(), 2);
+        assert_eq!(block.criteria[0].kind, MatchKind::Final);
+        assert_eq!(block.criteria[1].kind, MatchKind::All);
+    }
+
+    #[test]
+    fn test_match_criteria_unknown_kept() {
+        let config = parse_str("Match localnetwork 10.0.0.0/8\n  ProxyJump none\n");
+        let block = config.match_blocks()[0];
+        assert_eq!(block.criteria[0].kind, MatchKind::Other("localnetwork".to_string()));
+        assert_eq!(block.criteria[0].arg.as_deref(), Some("10.0.0.0/8"));
+    }
+
+    #[test]
+    fn test_indented_include_inside_match_is_directive() {
+        let content = "\
+Match host *.internal
+  Include ~/.ssh/internal.conf
+  User admin
+";
+        let config = parse_str(content);
+        assert_eq!(config.elements.len(), 1);
+        let block = config.match_blocks()[0];
+        assert_eq!(block.directives[0].key, "Include");
+        assert_eq!(config.serialize(), content);
     }
 
     #[test]
@@ -621,8 +801,8 @@ Host myserver
   HostName 10.0.0.1
 ";
         let config = parse_str(content);
-        assert!(matches!(&config.elements[0], ConfigElement::GlobalLine(s) if s == "Match all"));
-        assert!(matches!(&config.elements[1], ConfigElement::GlobalLine(s) if s.contains("ServerAliveInterval")));
+        assert!(matches!(&config.elements[0], ConfigElement::MatchBlock(b) if b.raw_match_line == "Match all"));
+        assert!(matches!(&config.elements[1], ConfigElement::HostBlock(_)));
         let entries = config.host_entries();
         assert_eq!(entries.len(), 1);
         assert_eq!(entries[0].alias, "myserver");
@@ -643,4 +823,47 @@ Host myserver
         // Multi-pattern hosts are filtered out of host_entries
         assert_eq!(config.host_entries().len(), 0);
     }
+
+    #[test]
+    fn test_quoted_values_are_unquoted() {
+        let content = "\
+Host \"my box\"
+  HostName 10.0.0.1
+  User 'deploy'
+  IdentityFile \"~/keys/with space\" # work key
+  IdentityFile ~/keys/with\\ escape
+  CertificateFile \"~/keys/with space-cert.pub\"
+";
+        let config = parse_str(content);
+        let entry = &config.host_entries()[0];
+        assert_eq!(entry.alias, "my box");
+        assert_eq!(entry.user, "deploy");
+        assert_eq!(entry.identity_files, vec!["~/keys/with space", "~/keys/with escape"]);
+        assert_eq!(entry.certificate_files, vec!["~/keys/with space-cert.pub"]);
+        assert_eq!(config.serialize(), content);
+    }
+
+    #[test]
+    fn test_reload_included_file_replaces_only_that_file() {
+        let dir = std::env::temp_dir().join(format!("purple_test_reload_include_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        let main = dir.join("config");
+        let work = dir.join("conf.d/work");
+        std::fs::write(&main, "Include conf.d/*\n\nHost home\n  HostName 10.0.0.1\n").unwrap();
+        std::fs::write(&work, "Host old\n  HostName 10.0.0.2\n").unwrap();
+        let mut config = SshConfigFile::parse(&main).unwrap();
+
+        std::fs::write(&work, "Host new\n  HostName 10.0.0.3\n").unwrap();
+        // The main file changes too but must not be re-read
+        std::fs::write(&main, "Host other\n").unwrap();
+        assert!(config.reload_included_file(&work));
+        let aliases: Vec<String> = config.host_entries().into_iter().map(|h| h.alias).collect();
+        assert_eq!(aliases, vec!["new", "home"]);
+        assert!(config.has_host("new"));
+        assert!(!config.has_host("old"));
+
+        assert!(!config.reload_included_file(&dir.join("conf.d/missing")));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,524 @@
+use std::path::Path;
+
+use super::fuzzy::{FuzzyMatch, fuzzy_match};
+use super::model::HostEntry;
+use super::resolve::match_pattern;
+
+/// Field names accepted before a colon, for error messages.
+const FIELDS: &str = "alias, host, user, port, tag, provider, key, jump, file, note, has";
+
+/// Extra points when a bare word matches the alias rather than another field.
+const ALIAS_BONUS: i64 = 24;
+
+/// Points per character when a bare word is found in a note. Notes are prose,
+/// so they only match as a substring and rank below any fuzzy field match.
+const NOTE_SCORE_PER_CHAR: i64 = 4;
+
+/// A parsed search query: terms joined by `OR`, each side an implicit AND.
+///
+/// ```text
+/// web user:root -tag:prod OR host:10.0.* has:tunnel
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Query {
+    /// Alternatives (OR) of term lists (AND).
+    pub any: Vec<Vec<Term>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub negated: bool,
+    pub filter: Filter,
+}
+
+/// A single condition on a host. Values with `*` or `?` are globs matched
+/// against the whole field, others match as a case-insensitive substring.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// Bare word: fuzzy match on alias, hostname, user, tags or provider,
+    /// substring match on notes.
+    Text(String),
+    Alias(String),
+    Host(String),
+    User(String),
+    Port(u16),
+    /// Tag or provider name.
+    Tag(String),
+    /// Exact tag or provider name (`tag=web`, used by the tag picker).
+    TagExact(String),
+    Provider(String),
+    Key(String),
+    Jump(String),
+    File(String),
+    Note(String),
+    Has(Has),
+}
+
+/// The host field a bare word matched in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchField {
+    Alias,
+    Host,
+    User,
+    /// Index into `HostEntry::tags`.
+    Tag(usize),
+    Provider,
+    /// Index into `HostEntry::notes`.
+    Note(usize),
+}
+
+/// Where and how well a bare word matched a host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextMatch {
+    pub field: MatchField,
+    pub score: i64,
+    /// Character indices of the matched characters in that field.
+    pub positions: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Has {
+    Tunnel,
+    Password,
+    Key,
+    Jump,
+    Tag,
+    Note,
+}
+
+/// A whitespace-separated token with quotes removed.
+struct Token {
+    text: String,
+    /// Byte offset and character of the first unquoted `:` or `=`.
+    split: Option<(usize, char)>,
+    quoted: bool,
+}
+
+impl Query {
+    /// Parse a query. Errors are short sentences meant for the search bar.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut query = Query::default();
+        let mut current: Vec<Term> = Vec::new();
+        for token in tokenize(input)? {
+            if !token.quoted && token.text == "OR" {
+                if current.is_empty() {
+                    return Err("OR needs a term on both sides.".to_string());
+                }
+                query.any.push(std::mem::take(&mut current));
+                continue;
+            }
+            current.push(parse_term(token)?);
+        }
+        if current.is_empty() {
+            if !query.any.is_empty() {
+                return Err("OR needs a term on both sides.".to_string());
+            }
+        } else {
+            query.any.push(current);
+        }
+        Ok(query)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.any.is_empty()
+    }
+
+    /// True when `host` satisfies the query. Hosts from the main config are
+    /// matched against `config_path` for `file:`.
+    pub fn matches(&self, host: &HostEntry, config_path: &Path) -> bool {
+        self.is_empty()
+            || self.any.iter().any(|terms| {
+                terms
+                    .iter()
+                    .all(|term| term.filter.matches(host, config_path) != term.negated)
+            })
+    }
+
+    /// Relevance of `host`, or `None` when it doesn't match. The score adds
+    /// up the fuzzy scores of the bare words in the best matching alternative.
+    pub fn score(&self, host: &HostEntry, config_path: &Path) -> Option<i64> {
+        if self.is_empty() {
+            return Some(0);
+        }
+        self.any
+            .iter()
+            .filter_map(|terms| {
+                let mut total = 0;
+                for term in terms {
+                    if let (Filter::Text(text), false) = (&term.filter, term.negated) {
+                        if !is_glob(text) {
+                            total += text_match(host, text)?.score;
+                            continue;
+                        }
+                    }
+                    if term.filter.matches(host, config_path) == term.negated {
+                        return None;
+                    }
+                }
+                Some(total)
+            })
+            .max()
+    }
+
+    /// True when the query has bare words, so results can be ranked.
+    pub fn is_ranked(&self) -> bool {
+        !self.text_terms().is_empty()
+    }
+
+    /// Bare words that aren't negated or globs: the ones worth highlighting.
+    pub fn text_terms(&self) -> Vec<&str> {
+        self.any
+            .iter()
+            .flatten()
+            .filter(|term| !term.negated)
+            .filter_map(|term| match &term.filter {
+                Filter::Text(text) if !is_glob(text) => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Best fuzzy match of a bare word across alias, hostname, user, tags and
+/// provider. Alias matches get a bonus so `web` ranks `web-01` above a host
+/// that is merely tagged `web`.
+pub fn text_match(host: &HostEntry, text: &str) -> Option<TextMatch> {
+    let provider = host.provider.as_deref().map(|p| (MatchField::Provider, p));
+    let fields = [
+        (MatchField::Alias, host.alias.as_str()),
+        (MatchField::Host, host.hostname.as_str()),
+        (MatchField::User, host.user.as_str()),
+    ]
+    .into_iter()
+    .chain(host.tags.iter().enumerate().map(|(i, t)| (MatchField::Tag(i), t.as_str())))
+    .chain(provider);
+    let mut best: Option<TextMatch> = None;
+    for (field, value) in fields {
+        let Some(FuzzyMatch { score, positions }) = fuzzy_match(value, text) else {
+            continue;
+        };
+        let score = if field == MatchField::Alias { score + ALIAS_BONUS } else { score };
+        if best.as_ref().is_none_or(|b| score > b.score) {
+            best = Some(TextMatch { field, score, positions });
+        }
+    }
+    if best.is_none() {
+        best = note_match(host, text);
+    }
+    best
+}
+
+/// First note line containing `text` (case-insensitive).
+fn note_match(host: &HostEntry, text: &str) -> Option<TextMatch> {
+    let needle: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return None;
+    }
+    host.notes.iter().enumerate().find_map(|(i, note)| {
+        let hay: Vec<char> = note.chars().flat_map(char::to_lowercase).collect();
+        let start = hay.windows(needle.len()).position(|w| w == needle.as_slice())?;
+        Some(TextMatch {
+            field: MatchField::Note(i),
+            score: NOTE_SCORE_PER_CHAR * needle.len() as i64,
+            positions: (start..start + needle.len()).collect(),
+        })
+    })
+}
+
+impl Filter {
+    fn matches(&self, host: &HostEntry, config_path: &Path) -> bool {
+        let tag_or_provider = |value: &str| {
+            host.tags.iter().any(|t| value_matches(t, value))
+                || host.provider.as_deref().is_some_and(|p| value_matches(p, value))
+        };
+        match self {
+            Filter::Text(text) if is_glob(text) => {
+                value_matches(&host.alias, text)
+                    || value_matches(&host.hostname, text)
+                    || value_matches(&host.user, text)
+                    || tag_or_provider(text)
+                    || host.notes.iter().any(|n| value_matches(n, text))
+            }
+            Filter::Text(text) => text_match(host, text).is_some(),
+            Filter::Alias(value) => value_matches(&host.alias, value),
+            Filter::Host(value) => value_matches(&host.hostname, value),
+            Filter::User(value) => value_matches(&host.user, value),
+            Filter::Port(port) => host.port == *port,
+            Filter::Tag(value) => tag_or_provider(value),
+            Filter::TagExact(value) => {
+                host.tags.iter().any(|t| t.eq_ignore_ascii_case(value))
+                    || host
+                        .provider
+                        .as_deref()
+                        .is_some_and(|p| p.eq_ignore_ascii_case(value))
+            }
+            Filter::Provider(value) => host
+                .provider
+                .as_deref()
+                .is_some_and(|p| value_matches(p, value)),
+            Filter::Key(value) => host.identity_files.iter().any(|f| value_matches(f, value)),
+            Filter::Jump(value) => value_matches(&host.proxy_jump, value),
+            Filter::File(value) => {
+                let path = host.source_file.as_deref().unwrap_or(config_path);
+                value_matches(&path.to_string_lossy(), value)
+            }
+            Filter::Note(value) => host.notes.iter().any(|n| value_matches(n, value)),
+            Filter::Has(Has::Tunnel) => host.tunnel_count > 0,
+            Filter::Has(Has::Password) => host.askpass.is_some(),
+            Filter::Has(Has::Key) => !host.identity_files.is_empty(),
+            Filter::Has(Has::Jump) => !host.proxy_jump.is_empty(),
+            Filter::Has(Has::Tag) => !host.tags.is_empty(),
+            Filter::Has(Has::Note) => !host.notes.is_empty(),
+        }
+    }
+}
+
+fn is_glob(value: &str) -> bool {
+    value.contains(['*', '?'])
+}
+
+/// Glob (whole field) when the value has `*` or `?`, substring otherwise.
+fn value_matches(field: &str, value: &str) -> bool {
+    if is_glob(value) {
+        match_pattern(field, value)
+    } else {
+        field.to_lowercase().contains(&value.to_lowercase())
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut current: Option<Token> = None;
+    let mut in_quotes = false;
+    for c in input.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+            let token = current.get_or_insert_with(new_token);
+            token.quoted = true;
+            continue;
+        }
+        if c.is_whitespace() && !in_quotes {
+            tokens.extend(current.take());
+            continue;
+        }
+        let token = current.get_or_insert_with(new_token);
+        if !in_quotes && (c == ':' || c == '=') && token.split.is_none() {
+            token.split = Some((token.text.len(), c));
+        }
+        token.text.push(c);
+    }
+    if in_quotes {
+        return Err("Missing closing quote.".to_string());
+    }
+    tokens.extend(current);
+    Ok(tokens)
+}
+
+fn new_token() -> Token {
+    Token {
+        text: String::new(),
+        split: None,
+        quoted: false,
+    }
+}
+
+fn parse_term(token: Token) -> Result<Term, String> {
+    let (negated, offset) = match token.text.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, 1),
+        _ => (false, 0),
+    };
+    let text = &token.text[offset..];
+    let Some((at, sep)) = token.split.map(|(at, sep)| (at - offset, sep)) else {
+        return Ok(Term {
+            negated,
+            filter: Filter::Text(text.to_string()),
+        });
+    };
+    let field = &text[..at];
+    let value = &text[at + 1..];
+    // "10.0.0.1:22" or "fe80::1" is a search for text, not a field
+    if field.is_empty() || !field.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Ok(Term {
+            negated,
+            filter: Filter::Text(text.to_string()),
+        });
+    }
+    let field = field.to_ascii_lowercase();
+    if value.is_empty() {
+        return Err(format!("{}{} needs a value.", field, sep));
+    }
+    if sep == '=' && field != "tag" {
+        return Err(format!("Use {}:{} (only tag= matches exactly).", field, value));
+    }
+    let value = value.to_string();
+    let filter = match field.as_str() {
+        "tag" if sep == '=' => Filter::TagExact(value),
+        "tag" => Filter::Tag(value),
+        "alias" => Filter::Alias(value),
+        "host" => Filter::Host(value),
+        "user" => Filter::User(value),
+        "port" => match value.parse::<u16>() {
+            Ok(port) => Filter::Port(port),
+            Err(_) => return Err(format!("port:{} isn't a port number.", value)),
+        },
+        "provider" => Filter::Provider(value),
+        "key" => Filter::Key(value),
+        "jump" => Filter::Jump(value),
+        "file" => Filter::File(value),
+        "note" => Filter::Note(value),
+        "has" => match value.to_ascii_lowercase().as_str() {
+            "tunnel" | "tunnels" => Filter::Has(Has::Tunnel),
+            "password" => Filter::Has(Has::Password),
+            "key" => Filter::Has(Has::Key),
+            "jump" => Filter::Has(Has::Jump),
+            "tag" | "tags" => Filter::Has(Has::Tag),
+            "note" | "notes" => Filter::Has(Has::Note),
+            _ => {
+                return Err(format!(
+                    "has:{} isn't supported. Try tunnel, password, key, jump, tag or note.",
+                    value
+                ));
+            }
+        },
+        _ => return Err(format!("Unknown field '{}'. Try {}.", field, FIELDS)),
+    };
+    Ok(Term { negated, filter })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn host(alias: &str, hostname: &str, user: &str) -> HostEntry {
+        HostEntry {
+            alias: alias.to_string(),
+            hostname: hostname.to_string(),
+            user: user.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn hosts() -> Vec<HostEntry> {
+        let mut web = host("web", "10.0.1.5", "root");
+        web.tags = vec!["prod".to_string()];
+        web.tunnel_count = 1;
+        let mut db = host("db", "10.0.2.9", "postgres");
+        db.port = 2222;
+        db.tags = vec!["staging".to_string()];
+        db.askpass = Some("keychain".to_string());
+        db.identity_files = vec!["~/.ssh/id_ed25519".to_string()];
+        let mut work = host("work box", "work.example.com", "me");
+        work.provider = Some("hetzner".to_string());
+        work.proxy_jump = "bastion".to_string();
+        work.source_file = Some(PathBuf::from("/home/me/.ssh/config.d/work.conf"));
+        vec![web, db, work]
+    }
+
+    fn aliases(query: &str) -> Vec<String> {
+        let query = Query::parse(query).unwrap();
+        hosts()
+            .into_iter()
+            .filter(|h| query.matches(h, Path::new("/home/me/.ssh/config")))
+            .map(|h| h.alias)
+            .collect()
+    }
+
+    #[test]
+    fn field_filters() {
+        assert_eq!(aliases("user:root"), vec!["web"]);
+        assert_eq!(aliases("port:2222"), vec!["db"]);
+        assert_eq!(aliases("host:10.0.*"), vec!["web", "db"]);
+        assert_eq!(aliases("host:10.0"), vec!["web", "db"]);
+        assert_eq!(aliases("provider:hetzner"), vec!["work box"]);
+        assert_eq!(aliases("key:id_ed25519"), vec!["db"]);
+        assert_eq!(aliases("jump:bastion"), vec!["work box"]);
+        assert_eq!(aliases("has:tunnel"), vec!["web"]);
+        assert_eq!(aliases("has:password"), vec!["db"]);
+        assert_eq!(aliases("file:config.d/work.conf"), vec!["work box"]);
+        assert_eq!(aliases("file:*/config"), vec!["web", "db"]);
+        assert_eq!(aliases("tag=prod"), vec!["web"]);
+        assert_eq!(aliases("tag:hetz"), vec!["work box"]);
+    }
+
+    #[test]
+    fn negation_and_or() {
+        assert_eq!(aliases("-tag:prod"), vec!["db", "work box"]);
+        assert_eq!(aliases("10.0 -user:root"), vec!["db"]);
+        assert_eq!(aliases("user:root OR port:2222"), vec!["web", "db"]);
+        assert_eq!(aliases("host:10.* has:tunnel OR jump:bastion"), vec!["web", "work box"]);
+        assert!(aliases("user:root user:postgres").is_empty());
+    }
+
+    #[test]
+    fn notes_match_as_substring() {
+        let mut db = host("db", "10.0.2.9", "postgres");
+        db.notes = vec!["Owner: data team".to_string(), "Runbook https://wiki/db".to_string()];
+        let config = Path::new("/home/me/.ssh/config");
+        let query = Query::parse("runbook").unwrap();
+        assert!(query.matches(&db, config));
+        let m = text_match(&db, "data team").unwrap();
+        assert_eq!(m.field, MatchField::Note(0));
+        assert_eq!(m.positions, (7..16).collect::<Vec<_>>());
+        // Not fuzzy: scattered letters don't hit a note
+        assert!(text_match(&db, "rbkw").is_none());
+        assert!(Query::parse("note:wiki").unwrap().matches(&db, config));
+        assert!(Query::parse("has:note").unwrap().matches(&db, config));
+        assert!(!Query::parse("has:note").unwrap().matches(&hosts()[0], config));
+    }
+
+    #[test]
+    fn quoted_values() {
+        assert_eq!(aliases("\"work box\""), vec!["work box"]);
+        assert_eq!(aliases("alias:\"work box\""), vec!["work box"]);
+        // A quoted OR is a search term, not an operator
+        assert!(aliases("web \"OR\" db").is_empty());
+    }
+
+    #[test]
+    fn text_with_colons_is_not_a_field() {
+        let query = Query::parse("10.0.1.5:22 fe80::1").unwrap();
+        assert_eq!(query.any[0][0].filter, Filter::Text("10.0.1.5:22".to_string()));
+        assert_eq!(query.any[0][1].filter, Filter::Text("fe80::1".to_string()));
+    }
+
+    #[test]
+    fn bare_words_match_fuzzily_and_rank_alias_first() {
+        let mut prod = host("prod-web-01", "10.0.3.1", "deploy");
+        prod.tags = vec!["web".to_string()];
+        let mut tagged = host("cache", "10.0.3.2", "deploy");
+        tagged.tags = vec!["web".to_string()];
+        let path = Path::new("/home/me/.ssh/config");
+        let query = Query::parse("prdweb").unwrap();
+        assert!(query.matches(&prod, path));
+        assert!(!query.matches(&tagged, path));
+
+        let query = Query::parse("web").unwrap();
+        let alias = query.score(&prod, path).unwrap();
+        let tag = query.score(&tagged, path).unwrap();
+        assert!(alias > tag);
+        let m = text_match(&tagged, "web").unwrap();
+        assert_eq!(m.field, MatchField::Tag(0));
+        assert_eq!(m.positions, vec![0, 1, 2]);
+
+        // Field filters and negations don't add to the score
+        let query = Query::parse("web user:deploy -tag:db").unwrap();
+        assert_eq!(query.score(&prod, path), Some(alias));
+        assert_eq!(query.text_terms(), vec!["web"]);
+        assert!(!Query::parse("user:deploy").unwrap().is_ranked());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |q: &str| Query::parse(q).unwrap_err();
+        assert_eq!(err("usr:root"), format!("Unknown field 'usr'. Try {}.", FIELDS));
+        assert_eq!(err("user:"), "user: needs a value.");
+        assert_eq!(err("port:ssh"), "port:ssh isn't a port number.");
+        assert_eq!(err("OR web"), "OR needs a term on both sides.");
+        assert_eq!(err("web OR"), "OR needs a term on both sides.");
+        assert_eq!(err("alias:\"web"), "Missing closing quote.");
+        assert!(err("has:cake").starts_with("has:cake isn't supported."));
+        assert!(Query::parse("").unwrap().is_empty());
+        assert!(Query::parse("-").is_ok());
+    }
+}
@@ -0,0 +1,617 @@
+use std::path::{Path, PathBuf};
+
+use super::model::{ConfigElement, MatchBlock, MatchKind, SshConfigFile};
+
+/// Directives that accumulate across blocks instead of following first-value-wins.
+pub(super) const MULTI_VALUE_KEYS: &[&str] = &[
+    "identityfile",
+    "certificatefile",
+    "localforward",
+    "remoteforward",
+    "dynamicforward",
+    "sendenv",
+    "setenv",
+];
+
+/// Directives whose values support %-token expansion.
+const TOKEN_KEYS: &[&str] = &[
+    "certificatefile",
+    "controlpath",
+    "identityagent",
+    "identityfile",
+    "knownhostscommand",
+    "localcommand",
+    "proxycommand",
+    "remotecommand",
+    "userknownhostsfile",
+];
+
+/// Where an effective value came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueSource {
+    /// A Host or Match block. `header` is the block line (e.g. "Host *", "Match user root").
+    /// `own_block` is true for the host's own `Host <alias>` block.
+    Block {
+        header: String,
+        file: PathBuf,
+        own_block: bool,
+    },
+    /// OpenSSH's built-in default (HostName = alias, Port 22, User = local user).
+    Default,
+}
+
+/// A single resolved directive value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectiveValue {
+    pub key: String,
+    pub value: String,
+    pub source: ValueSource,
+}
+
+/// The effective configuration for one alias, the pure-Rust equivalent of `ssh -G`.
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig {
+    /// HostName, User and Port first, then everything else in order of first appearance.
+    pub values: Vec<EffectiveValue>,
+    /// Match blocks purple can't evaluate (e.g. `Match exec`). They are skipped.
+    pub unevaluated: Vec<String>,
+}
+
+impl EffectiveConfig {
+    /// All values for a key (case-insensitive), in order. The first one is
+    /// what ssh uses; multi-value keys like IdentityFile use them all.
+    pub fn get_all(&self, key: &str) -> Vec<&EffectiveValue> {
+        self.values
+            .iter()
+            .filter(|v| v.key.eq_ignore_ascii_case(key))
+            .collect()
+    }
+
+    /// Values inherited from other blocks (not the host's own block, not defaults).
+    pub fn inherited(&self) -> Vec<&EffectiveValue> {
+        self.values
+            .iter()
+            .filter(|v| matches!(v.source, ValueSource::Block { own_block: false, .. }))
+            .collect()
+    }
+}
+
+impl SshConfigFile {
+    /// Compute the effective directive set for an alias.
+    /// Walks Host and Match blocks (including Include files) in order with
+    /// OpenSSH's first-value-wins rule, then expands %h, %p, %r, %u, %n, %d and %%.
+    /// Runs a second (final) pass when a `Match canonical` or `Match final` block exists.
+    pub fn resolve_host(&self, alias: &str) -> EffectiveConfig {
+        let mut resolver = Resolver {
+            alias,
+            local_user: local_user(),
+            values: Vec::new(),
+            unevaluated: Vec::new(),
+            final_pass: false,
+            wants_final_pass: false,
+            host: alias.to_string(),
+        };
+        resolver.walk(&self.elements, &self.path);
+        if resolver.wants_final_pass {
+            // OpenSSH re-reads the config with the resolved HostName as target
+            resolver.final_pass = true;
+            resolver.host = resolver
+                .first("hostname")
+                .map(|h| expand_tokens(h, &[('h', alias)]))
+                .unwrap_or_else(|| alias.to_string());
+            resolver.walk(&self.elements, &self.path);
+        }
+        resolver.finish()
+    }
+}
+
+struct Resolver<'a> {
+    alias: &'a str,
+    local_user: String,
+    values: Vec<EffectiveValue>,
+    unevaluated: Vec<String>,
+    final_pass: bool,
+    wants_final_pass: bool,
+    /// Target host for `Match host` (alias on the first pass, HostName on the final pass).
+    host: String,
+}
+
+impl Resolver<'_> {
+    fn first(&self, key: &str) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|v| v.key.eq_ignore_ascii_case(key))
+            .map(|v| v.value.as_str())
+    }
+
+    fn walk(&mut self, elements: &[ConfigElement], file: &Path) {
+        for element in elements {
+            match element {
+                ConfigElement::HostBlock(block) => {
+                    let patterns = block.patterns();
+                    let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+                    if !match_pattern_list(self.alias, &patterns) {
+                        continue;
+                    }
+                    let header = format!("Host {}", block.host_pattern);
+                    let own_block = block.is_alias(self.alias);
+                    for d in block.directives.iter().filter(|d| !d.is_non_directive) {
+                        self.apply(&d.key, &d.value, &header, file, own_block);
+                    }
+                }
+                ConfigElement::MatchBlock(block) => {
+                    let header = format!("Match {}", block.criteria_text);
+                    match self.evaluate_match(block) {
+                        Some(true) => {
+                            for d in block.directives.iter().filter(|d| !d.is_non_directive) {
+                                self.apply(&d.key, &d.value, &header, file, false);
+                            }
+                        }
+                        Some(false) => {}
+                        None => {
+                            if !self.unevaluated.contains(&header) {
+                                self.unevaluated.push(header);
+                            }
+                        }
+                    }
+                }
+                ConfigElement::Include(include) => {
+                    for included in &include.resolved_files {
+                        self.walk(&included.elements, &included.path);
+                    }
+                }
+                // Directives above the first Host or Match block apply to every host
+                ConfigElement::GlobalLine(line) => {
+                    let trimmed = line.trim();
+                    if trimmed.is_empty() || trimmed.starts_with('#') {
+                        continue;
+                    }
+                    if let Some((key, value)) = SshConfigFile::parse_directive(trimmed) {
+                        self.apply(&key, &value, "global", file, false);
+                    }
+                }
+                ConfigElement::DisabledHost(_) | ConfigElement::Template(_) => {}
+            }
+        }
+    }
+
+    /// Evaluate Match criteria. All criteria must match.
+    /// Returns None when a criterion can't be evaluated without side effects.
+    fn evaluate_match(&mut self, block: &MatchBlock) -> Option<bool> {
+        let mut unknown = false;
+        for c in &block.criteria {
+            let matched = match &c.kind {
+                MatchKind::All => true,
+                MatchKind::Canonical | MatchKind::Final => {
+                    self.wants_final_pass = true;
+                    self.final_pass
+                }
+                MatchKind::Host => self.match_arg(&self.host, c.arg.as_deref()),
+                MatchKind::OriginalHost => self.match_arg(self.alias, c.arg.as_deref()),
+                MatchKind::User => {
+                    let user = self.first("user").unwrap_or(&self.local_user);
+                    self.match_arg(user, c.arg.as_deref())
+                }
+                MatchKind::LocalUser => self.match_arg(&self.local_user, c.arg.as_deref()),
+                MatchKind::Exec | MatchKind::Other(_) => {
+                    unknown = true;
+                    continue;
+                }
+            };
+            if matched == c.negated {
+                return Some(false);
+            }
+        }
+        if unknown { None } else { Some(true) }
+    }
+
+    fn match_arg(&self, value: &str, arg: Option<&str>) -> bool {
+        let patterns: Vec<&str> = arg.unwrap_or("").split(',').collect();
+        match_pattern_list(value, &patterns)
+    }
+
+    fn apply(&mut self, key: &str, value: &str, header: &str, file: &Path, own_block: bool) {
+        let lower = key.to_ascii_lowercase();
+        // Nested Include/Host/Match lines inside a block aren't settings
+        if matches!(lower.as_str(), "include" | "host" | "match") {
+            return;
+        }
+        let exists = if MULTI_VALUE_KEYS.contains(&lower.as_str()) {
+            self.values
+                .iter()
+                .any(|v| v.key.eq_ignore_ascii_case(key) && v.value == value)
+        } else {
+            self.values.iter().any(|v| v.key.eq_ignore_ascii_case(key))
+        };
+        if exists {
+            return;
+        }
+        self.values.push(EffectiveValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            source: ValueSource::Block {
+                header: header.to_string(),
+                file: file.to_path_buf(),
+                own_block,
+            },
+        });
+    }
+
+    fn finish(mut self) -> EffectiveConfig {
+        let alias = self.alias.to_string();
+        let mut take = |key: &str, default: String| -> EffectiveValue {
+            match self.values.iter().position(|v| v.key.eq_ignore_ascii_case(key)) {
+                Some(pos) => self.values.remove(pos),
+                None => EffectiveValue {
+                    key: key.to_string(),
+                    value: default,
+                    source: ValueSource::Default,
+                },
+            }
+        };
+        let mut hostname = take("HostName", alias.clone());
+        let mut user = take("User", self.local_user.clone());
+        let port = take("Port", "22".to_string());
+
+        hostname.value = expand_tokens(&hostname.value, &[('h', &alias), ('n', &alias)]);
+        user.value = expand_tokens(&user.value, &[('n', &alias), ('u', &self.local_user)]);
+
+        let home = dirs::home_dir()
+            .map(|h| h.display().to_string())
+            .unwrap_or_default();
+        let tokens = [
+            ('h', hostname.value.as_str()),
+            ('p', port.value.as_str()),
+            ('r', user.value.as_str()),
+            ('u', self.local_user.as_str()),
+            ('n', alias.as_str()),
+            ('d', home.as_str()),
+        ];
+        let mut values = Vec::with_capacity(self.values.len() + 3);
+        for mut v in std::mem::take(&mut self.values) {
+            if TOKEN_KEYS.contains(&v.key.to_ascii_lowercase().as_str()) {
+                v.value = expand_tokens(&v.value, &tokens);
+            }
+            values.push(v);
+        }
+        let mut result = vec![hostname, user, port];
+        result.extend(values);
+        EffectiveConfig {
+            values: result,
+            unevaluated: self.unevaluated,
+        }
+    }
+}
+
+/// The local user name (from $USER or $LOGNAME).
+fn local_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("LOGNAME"))
+        .unwrap_or_default()
+}
+
+/// Expand `%x` tokens. `%%` becomes a literal `%`; unknown tokens are left as-is.
+fn expand_tokens(value: &str, tokens: &[(char, &str)]) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('%') => result.push('%'),
+            Some(t) => match tokens.iter().find(|(k, _)| *k == t) {
+                Some((_, v)) => result.push_str(v),
+                None => {
+                    result.push('%');
+                    result.push(t);
+                }
+            },
+            None => result.push('%'),
+        }
+    }
+    result
+}
+
+/// OpenSSH pattern-list semantics: a negated match (`!pattern`) rejects outright,
+/// otherwise any positive match accepts.
+pub(super) fn match_pattern_list<S: AsRef<str>>(value: &str, patterns: &[S]) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        let pattern = pattern.as_ref();
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if match_pattern(value, negated) {
+                return false;
+            }
+        } else if match_pattern(value, pattern) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+/// Case-insensitive glob match supporting `*` and `?` (like OpenSSH's match_pattern).
+pub(super) fn match_pattern(value: &str, pattern: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    // Runs per host per wildcard block, so plain ASCII skips the lowercased copies
+    if value.is_ascii() && pattern.is_ascii() {
+        return glob(value.as_bytes(), pattern.as_bytes(), u8::eq_ignore_ascii_case);
+    }
+    let value: Vec<char> = value.to_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    glob(&value, &pattern, |a, b| a == b)
+}
+
+fn glob<T: Copy + PartialEq + From<u8>>(value: &[T], pattern: &[T], eq: impl Fn(&T, &T) -> bool) -> bool {
+    let (any, star_char) = (T::from(b'?'), T::from(b'*'));
+    let (mut v, mut p) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == any || eq(&pattern[p], &value[v])) {
+            v += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == star_char {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((sp, sv)) = star {
+            p = sp + 1;
+            v = sv + 1;
+            star = Some((sp, sv + 1));
+        } else {
+            return false;
+        }
+    }
+    while p < pattern.len() && pattern[p] == star_char {
+        p += 1;
+    }
+    p == pattern.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        }
+    }
+
+    fn value(config: &EffectiveConfig, key: &str) -> String {
+        config.get_all(key).first().map(|v| v.value.clone()).unwrap_or_default()
+    }
+
+    fn header(config: &EffectiveConfig, key: &str) -> String {
+        match config.get_all(key).first().map(|v| &v.source) {
+            Some(ValueSource::Block { header, .. }) => header.clone(),
+            Some(ValueSource::Default) => "default".to_string(),
+            None => String::new(),
+        }
+    }
+
+    #[test]
+    fn first_value_wins_across_blocks() {
+        let config = parse_str("\
+Host web
+  User deploy
+
+Host *
+  User root
+  ServerAliveInterval 60
+");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "user"), "deploy");
+        assert_eq!(header(&resolved, "user"), "Host web");
+        assert_eq!(value(&resolved, "ServerAliveInterval"), "60");
+        assert_eq!(header(&resolved, "ServerAliveInterval"), "Host *");
+    }
+
+    #[test]
+    fn host_star_above_specific_host_wins() {
+        let config = parse_str("\
+Host *
+  User root
+
+Host web
+  User deploy
+");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "User"), "root");
+        assert_eq!(header(&resolved, "User"), "Host *");
+    }
+
+    #[test]
+    fn top_level_directives_apply_to_every_host() {
+        let config = parse_str("\
+# defaults
+ServerAliveInterval=15
+
+Host web
+  ServerAliveInterval 60
+");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "ServerAliveInterval"), "15");
+        assert_eq!(header(&resolved, "ServerAliveInterval"), "global");
+    }
+
+    #[test]
+    fn wildcard_and_multi_pattern_blocks() {
+        let config = parse_str("\
+Host web-*
+  Port 2222
+
+Host db web-1 !web-2
+  ForwardAgent yes
+");
+        let web1 = config.resolve_host("web-1");
+        assert_eq!(value(&web1, "Port"), "2222");
+        assert_eq!(value(&web1, "ForwardAgent"), "yes");
+        let web2 = config.resolve_host("web-2");
+        assert_eq!(value(&web2, "Port"), "2222");
+        assert!(web2.get_all("ForwardAgent").is_empty());
+    }
+
+    #[test]
+    fn defaults_fill_hostname_user_port() {
+        let config = parse_str("Host web\n  ForwardAgent yes\n");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "HostName"), "web");
+        assert_eq!(header(&resolved, "HostName"), "default");
+        assert_eq!(value(&resolved, "Port"), "22");
+        assert_eq!(resolved.values[0].key, "HostName");
+        assert_eq!(resolved.values[1].key, "User");
+        assert_eq!(resolved.values[2].key, "Port");
+    }
+
+    #[test]
+    fn identity_files_accumulate() {
+        let config = parse_str("\
+Host web
+  IdentityFile ~/.ssh/web
+
+Host *
+  IdentityFile ~/.ssh/default
+  IdentityFile ~/.ssh/web
+");
+        let resolved = config.resolve_host("web");
+        let files: Vec<&str> = resolved
+            .get_all("identityfile")
+            .iter()
+            .map(|v| v.value.as_str())
+            .collect();
+        assert_eq!(files, vec!["~/.ssh/web", "~/.ssh/default"]);
+    }
+
+    #[test]
+    fn tokens_expanded() {
+        let config = parse_str("\
+Host web
+  HostName %h.example.com
+  User deploy
+  Port 2200
+  ControlPath ~/.ssh/cm-%r@%h:%p
+  IdentityFile ~/.ssh/%n_key
+  LocalCommand echo 100%%
+");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "HostName"), "web.example.com");
+        assert_eq!(value(&resolved, "ControlPath"), "~/.ssh/cm-deploy@web.example.com:2200");
+        assert_eq!(value(&resolved, "IdentityFile"), "~/.ssh/web_key");
+        assert_eq!(value(&resolved, "LocalCommand"), "echo 100%");
+    }
+
+    #[test]
+    fn match_blocks_evaluated() {
+        let config = parse_str("\
+Host web
+  User deploy
+
+Match originalhost web user deploy
+  ForwardAgent yes
+
+Match host db*,!db-prod
+  Compression yes
+
+Host db-prod db-test
+  HostName 10.0.0.5
+");
+        let web = config.resolve_host("web");
+        assert_eq!(value(&web, "ForwardAgent"), "yes");
+        let db_test = config.resolve_host("db-test");
+        assert!(db_test.get_all("ForwardAgent").is_empty());
+        assert_eq!(value(&db_test, "Compression"), "yes");
+        assert_eq!(header(&db_test, "Compression"), "Match host db*,!db-prod");
+        let db_prod = config.resolve_host("db-prod");
+        assert!(db_prod.get_all("Compression").is_empty());
+    }
+
+    #[test]
+    fn match_all_and_negation() {
+        let config = parse_str("\
+Match !host bastion
+  ProxyJump bastion
+
+Match all
+  ServerAliveInterval 30
+");
+        let web = config.resolve_host("web");
+        assert_eq!(value(&web, "ProxyJump"), "bastion");
+        assert_eq!(value(&web, "ServerAliveInterval"), "30");
+        let bastion = config.resolve_host("bastion");
+        assert!(bastion.get_all("ProxyJump").is_empty());
+    }
+
+    #[test]
+    fn match_exec_is_not_evaluated() {
+        let config = parse_str("\
+Match exec \"test -f /tmp/vpn\"
+  ProxyJump vpn
+");
+        let resolved = config.resolve_host("web");
+        assert!(resolved.get_all("ProxyJump").is_empty());
+        assert_eq!(resolved.unevaluated, vec!["Match exec \"test -f /tmp/vpn\""]);
+    }
+
+    #[test]
+    fn match_final_runs_second_pass_with_hostname() {
+        let config = parse_str("\
+Match final host *.internal
+  ProxyJump gateway
+
+Host web
+  HostName web.internal
+");
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "ProxyJump"), "gateway");
+    }
+
+    #[test]
+    fn include_files_resolved_with_source() {
+        use super::super::model::{IncludeDirective, IncludedFile};
+        let included = IncludedFile {
+            path: PathBuf::from("/tmp/included.conf"),
+            elements: SshConfigFile::parse_content("Host *\n  User included\n"),
+            crlf: false,
+        };
+        let mut config = parse_str("Host web\n  HostName 10.0.0.1\n");
+        config.push_element(ConfigElement::Include(IncludeDirective {
+            raw_line: "Include included.conf".to_string(),
+            pattern: "included.conf".to_string(),
+            resolved_files: vec![included],
+        }));
+        let resolved = config.resolve_host("web");
+        assert_eq!(value(&resolved, "User"), "included");
+        match &resolved.get_all("User")[0].source {
+            ValueSource::Block { file, own_block, .. } => {
+                assert_eq!(file, &PathBuf::from("/tmp/included.conf"));
+                assert!(!own_block);
+            }
+            ValueSource::Default => panic!("expected block source"),
+        }
+        assert_eq!(resolved.inherited().len(), 1);
+    }
+
+    #[test]
+    fn pattern_matching() {
+        assert!(match_pattern("web-1", "web-*"));
+        assert!(match_pattern("WEB-1", "web-?"));
+        assert!(!match_pattern("web-10", "web-?"));
+        assert!(match_pattern("a.b.example.com", "*.example.com"));
+        assert!(!match_pattern("example.com", "*.example.com"));
+        assert!(match_pattern("anything", "*"));
+        assert!(match_pattern_list("web", &["db", "web"]));
+        assert!(!match_pattern_list("web", &["*", "!web"]));
+        assert!(!match_pattern_list("web", &["!db"]));
+    }
+
+    #[test]
+    fn expand_tokens_unknown_left_alone() {
+        assert_eq!(expand_tokens("%x-%h-%", &[('h', "host")]), "%x-host-%");
+    }
+}
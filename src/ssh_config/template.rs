@@ -0,0 +1,272 @@
+use std::path::{Path, PathBuf};
+
+use super::model::{ConfigElement, HostBlock, HostEntry, SshConfigFile};
+
+/// Where a template is defined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TemplateSource {
+    /// A `# purple:template` block in the SSH config.
+    Config,
+    /// A Host block in ~/.purple/templates.
+    File,
+}
+
+/// A named preset for new hosts. The block holds the same directives and
+/// purple comments a host would, minus the HostName.
+#[derive(Debug, Clone)]
+pub struct HostTemplate {
+    pub name: String,
+    pub source: TemplateSource,
+    pub block: HostBlock,
+}
+
+impl HostTemplate {
+    /// The template's form fields: user, port, identity file, ProxyJump,
+    /// password source, tags and notes. Alias and hostname stay empty.
+    pub fn entry(&self) -> HostEntry {
+        HostEntry {
+            alias: String::new(),
+            hostname: String::new(),
+            ..self.block.to_host_entry()
+        }
+    }
+
+    /// Fill the fields `entry` leaves empty (or at port 22) from the template.
+    pub fn fill(&self, entry: &mut HostEntry) {
+        let defaults = self.entry();
+        if entry.user.is_empty() {
+            entry.user = defaults.user;
+        }
+        if entry.port == 22 {
+            entry.port = defaults.port;
+        }
+        if entry.identity_files.is_empty() {
+            entry.identity_files = defaults.identity_files;
+        }
+        if entry.certificate_files.is_empty() {
+            entry.certificate_files = defaults.certificate_files;
+        }
+        if entry.proxy_jump.is_empty() {
+            entry.proxy_jump = defaults.proxy_jump;
+        }
+    }
+
+    /// Directives without their own form field (ServerAliveInterval, ...).
+    pub fn directives(&self) -> Vec<(String, String)> {
+        self.block.extra_directives()
+    }
+
+    /// LocalForward, RemoteForward and DynamicForward lines as (key, value).
+    pub fn forwards(&self) -> Vec<(String, String)> {
+        self.block
+            .directives
+            .iter()
+            .filter(|d| {
+                !d.is_non_directive
+                    && ["localforward", "remoteforward", "dynamicforward"]
+                        .iter()
+                        .any(|k| d.key.eq_ignore_ascii_case(k))
+            })
+            .map(|d| (d.key.clone(), d.value.clone()))
+            .collect()
+    }
+}
+
+impl SshConfigFile {
+    /// Templates from `# purple:template` blocks, Include files included.
+    pub fn templates(&self) -> Vec<HostTemplate> {
+        fn collect(elements: &[ConfigElement], out: &mut Vec<HostTemplate>) {
+            for element in elements {
+                match element {
+                    ConfigElement::Template(commented) => out.push(HostTemplate {
+                        name: commented.block.alias(),
+                        source: TemplateSource::Config,
+                        block: commented.block.clone(),
+                    }),
+                    ConfigElement::Include(include) => {
+                        for file in &include.resolved_files {
+                            collect(&file.elements, out);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let mut templates = Vec::new();
+        collect(&self.elements, &mut templates);
+        templates
+    }
+
+    /// Give a newly added host the parts of a template that `HostTemplate::fill`
+    /// doesn't cover: extra directives, forwards, and the tags, password source
+    /// and notes the host doesn't have yet.
+    pub fn apply_template(&mut self, alias: &str, template: &HostTemplate) {
+        let Some(entry) = self.find_block(alias, &|b| b.is_alias(alias)).map(|b| b.to_host_entry()) else {
+            return;
+        };
+        let defaults = template.entry();
+        let mut tags = entry.tags.clone();
+        for tag in defaults.tags {
+            if !tags.iter().any(|t| t.eq_ignore_ascii_case(&tag)) {
+                tags.push(tag);
+            }
+        }
+        if tags != entry.tags {
+            self.set_host_tags(alias, &tags);
+        }
+        if entry.askpass.is_none() {
+            if let Some(ref source) = defaults.askpass {
+                self.set_host_askpass(alias, source);
+            }
+        }
+        if entry.notes.is_empty() && !defaults.notes.is_empty() {
+            self.set_host_notes(alias, &defaults.notes);
+        }
+        let mut directives = self.host_extra_directives(alias);
+        for (key, value) in template.directives() {
+            if !directives.iter().any(|(k, _)| k.eq_ignore_ascii_case(&key)) {
+                directives.push((key, value));
+            }
+        }
+        self.set_host_directives(alias, &directives);
+        for (key, value) in template.forwards() {
+            if !self.has_forward(alias, &key, &value) {
+                self.add_forward(alias, &key, &value);
+            }
+        }
+    }
+}
+
+/// Parse ~/.purple/templates content: ssh_config syntax, one Host block per
+/// template, the Host line naming it.
+pub fn parse_templates(content: &str) -> Vec<HostTemplate> {
+    SshConfigFile::parse_content(content)
+        .into_iter()
+        .filter_map(|element| match element {
+            ConfigElement::HostBlock(block) => Some(HostTemplate {
+                name: block.alias(),
+                source: TemplateSource::File,
+                block,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Templates from the SSH config, then ~/.purple/templates. A name defined in
+/// both comes from the SSH config.
+pub fn load_all(config: &SshConfigFile) -> Vec<HostTemplate> {
+    load_all_from(config, templates_path().as_deref())
+}
+
+/// Like `load_all`, reading file templates from `path`.
+pub fn load_all_from(config: &SshConfigFile, path: Option<&Path>) -> Vec<HostTemplate> {
+    let mut templates = config.templates();
+    let content = path
+        .and_then(|p| std::fs::read_to_string(p).ok())
+        .unwrap_or_default();
+    for template in parse_templates(&content) {
+        if find(&templates, &template.name).is_none() {
+            templates.push(template);
+        }
+    }
+    templates
+}
+
+/// Find a template by name (case-insensitive).
+pub fn find<'a>(templates: &'a [HostTemplate], name: &str) -> Option<&'a HostTemplate> {
+    templates.iter().find(|t| t.name.eq_ignore_ascii_case(name))
+}
+
+/// Path of the templates file (~/.purple/templates).
+pub fn templates_path() -> Option<PathBuf> {
+    // Tests never read the real ~/.purple
+    if cfg!(test) {
+        return None;
+    }
+    dirs::home_dir().map(|h| h.join(".purple/templates"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        }
+    }
+
+    const CONFIG: &str = "\
+# purple:template
+# Host work
+#   User deploy
+#   Port 2222
+#   IdentityFile ~/.ssh/work
+#   ProxyJump bastion
+#   ServerAliveInterval 30
+#   LocalForward 8080 localhost:80
+#   # purple:tags work
+#   # purple:askpass keychain
+
+Host web
+  HostName 10.0.0.1
+";
+
+    #[test]
+    fn config_templates_are_invisible_to_ssh_and_hosts() {
+        let config = parse_str(CONFIG);
+        assert_eq!(config.serialize(), CONFIG);
+        let aliases: Vec<String> = config.host_entries().into_iter().map(|h| h.alias).collect();
+        assert_eq!(aliases, vec!["web"]);
+
+        let templates = config.templates();
+        assert_eq!(templates.len(), 1);
+        let entry = templates[0].entry();
+        assert_eq!(entry.user, "deploy");
+        assert_eq!(entry.port, 2222);
+        assert_eq!(entry.proxy_jump, "bastion");
+        assert_eq!(entry.tags, vec!["work"]);
+        assert_eq!(entry.askpass.as_deref(), Some("keychain"));
+        assert_eq!(templates[0].directives(), vec![("ServerAliveInterval".to_string(), "30".to_string())]);
+        assert_eq!(templates[0].forwards(), vec![("LocalForward".to_string(), "8080 localhost:80".to_string())]);
+    }
+
+    #[test]
+    fn fill_and_apply_to_new_host() {
+        let mut config = parse_str(CONFIG);
+        let template = config.templates().remove(0);
+        let mut entry = HostEntry {
+            alias: "app".to_string(),
+            hostname: "10.0.0.2".to_string(),
+            user: "me".to_string(),
+            ..Default::default()
+        };
+        template.fill(&mut entry);
+        assert_eq!((entry.user.as_str(), entry.port), ("me", 2222));
+        config.add_host(&entry);
+        config.apply_template("app", &template);
+        let output = config.serialize();
+        assert!(output.ends_with(
+            "Host app\n  HostName 10.0.0.2\n  User me\n  Port 2222\n  IdentityFile ~/.ssh/work\n  ProxyJump bastion\n  # purple:tags work\n  # purple:askpass keychain\n  ServerAliveInterval 30\n  LocalForward 8080 localhost:80\n"
+        ));
+    }
+
+    #[test]
+    fn file_templates_yield_to_config_ones() {
+        let dir = std::env::temp_dir().join(format!("purple_test_templates_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("templates");
+        std::fs::write(&path, "Host WORK\n  User other\n\nHost lab\n  User pi\n  Port 2200\n").unwrap();
+        let templates = load_all_from(&parse_str(CONFIG), Some(&path));
+        let names: Vec<&str> = templates.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["work", "lab"]);
+        assert_eq!(find(&templates, "LAB").unwrap().source, TemplateSource::File);
+        assert_eq!(find(&templates, "work").unwrap().entry().user, "deploy");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
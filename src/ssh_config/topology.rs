@@ -0,0 +1,586 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use super::model::{ConfigElement, SshConfigFile, is_host_pattern, is_plain_word, unquote};
+use super::resolve::{match_pattern, match_pattern_list};
+
+/// ssh options that take an argument (`ssh -W %h:%p bastion`).
+pub(super) const SSH_ARG_FLAGS: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+/// Who jumps through whom, built from every Host block's ProxyJump and
+/// ProxyCommand. Match blocks are ignored: their criteria can't be evaluated
+/// without connecting.
+#[derive(Debug, Clone, Default)]
+pub struct JumpGraph {
+    /// Concrete host aliases in file order (Include files inlined). Shared
+    /// with `exact` when the alias is already lowercase.
+    aliases: Vec<Rc<str>>,
+    /// Hops set by each ProxyJump/ProxyCommand block, in block order.
+    rules: Vec<Vec<String>>,
+    /// Every lowercased wildcard-free pattern -> first rule naming it, None
+    /// when no block naming it sets hops.
+    exact: HashMap<Rc<str>, Option<usize>>,
+    /// Positive pattern tokens with `*` or `?`, except a bare `*`.
+    globs: Vec<String>,
+    /// Rules whose Host line has wildcards or negations: (rule, pattern tokens).
+    wildcard: Vec<(usize, Vec<String>)>,
+    /// Some wildcard-free pattern is on more than one Host line (or twice
+    /// on one).
+    repeats: bool,
+    /// Some rule's first hop has hops of its own. Without one no host can
+    /// be in a cycle.
+    loops: bool,
+}
+
+/// The way to a host: the hops ssh goes through, nearest to us first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Route {
+    pub hops: Vec<String>,
+    /// The chain comes back to a host it already passed through.
+    pub cycle: bool,
+}
+
+impl Route {
+    /// "edge → bastion → web".
+    pub fn describe(&self, host: &str) -> String {
+        let mut parts: Vec<&str> = self.hops.iter().map(String::as_str).collect();
+        parts.push(host);
+        parts.join(" \u{2192} ")
+    }
+}
+
+/// A row of the bastion tree.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub name: String,
+    pub depth: usize,
+    /// False for bastions that are only a hostname or a pattern match.
+    pub host: bool,
+    /// Hosts jumping through this one.
+    pub behind: usize,
+    pub cycle: bool,
+}
+
+impl SshConfigFile {
+    /// Build the jump graph for the whole config (including Include files).
+    pub fn jump_graph(&self) -> JumpGraph {
+        // Sized for the main file, Include files grow it as needed
+        let mut graph = JumpGraph {
+            aliases: Vec::with_capacity(self.elements.len()),
+            exact: HashMap::with_capacity(self.elements.len()),
+            ..Default::default()
+        };
+        collect(&self.elements, &mut graph);
+        graph.loops = graph
+            .rules
+            .iter()
+            .any(|hops| hops.first().is_some_and(|first| !graph.hops(first).is_empty()));
+        graph
+    }
+}
+
+fn collect(elements: &[ConfigElement], graph: &mut JumpGraph) {
+    for element in elements {
+        match element {
+            ConfigElement::HostBlock(block) => {
+                // Most Host lines are a single plain alias, no need to split them
+                let plain = !block.host_pattern.is_empty() && is_plain_word(&block.host_pattern);
+                let split;
+                let tokens = if plain {
+                    std::slice::from_ref(&block.host_pattern)
+                } else {
+                    split = block.patterns();
+                    split.as_slice()
+                };
+                let alias: Option<Rc<str>> = if is_host_pattern(&block.host_pattern) {
+                    None
+                } else if plain {
+                    Some(Rc::from(block.host_pattern.as_str()))
+                } else {
+                    Some(Rc::from(block.alias()))
+                };
+                if let Some(alias) = &alias {
+                    graph.aliases.push(Rc::clone(alias));
+                }
+                let proxy = block.directives.iter().find(|d| {
+                    !d.is_non_directive
+                        && (d.key.eq_ignore_ascii_case("proxyjump")
+                            || d.key.eq_ignore_ascii_case("proxycommand"))
+                });
+                // Rule for the plain tokens. Wildcard blocks are matched separately
+                let mut exact_rule = None;
+                if let Some(proxy) = proxy {
+                    let rule = if !proxy.key.eq_ignore_ascii_case("proxyjump") {
+                        graph.push_rule(proxy_command_hops(&proxy.value).iter().map(String::as_str))
+                    } else if is_plain_word(&proxy.value) {
+                        graph.push_rule(proxy.value.split(',').filter_map(hop_host))
+                    } else {
+                        graph.push_rule(unquote(&proxy.value).split(',').filter_map(hop_host))
+                    };
+                    if tokens
+                        .iter()
+                        .any(|t| t.starts_with('!') || t.contains(['*', '?']))
+                    {
+                        graph.wildcard.push((rule, tokens.to_vec()));
+                    } else {
+                        exact_rule = Some(rule);
+                    }
+                }
+                for token in tokens {
+                    if token.starts_with('!') || token == "*" {
+                        continue;
+                    }
+                    if token.contains(['*', '?']) {
+                        graph.globs.push(token.clone());
+                        continue;
+                    }
+                    let key = match &alias {
+                        Some(alias) if is_lowercase(token) && **alias == **token => Rc::clone(alias),
+                        _ if is_lowercase(token) => Rc::from(token.as_str()),
+                        _ => Rc::from(token.to_lowercase()),
+                    };
+                    match graph.exact.entry(key) {
+                        Entry::Occupied(mut first) => {
+                            graph.repeats = true;
+                            if first.get().is_none() {
+                                first.insert(exact_rule);
+                            }
+                        }
+                        Entry::Vacant(slot) => {
+                            slot.insert(exact_rule);
+                        }
+                    }
+                }
+            }
+            ConfigElement::Include(include) => {
+                for file in &include.resolved_files {
+                    collect(&file.elements, graph);
+                }
+            }
+            ConfigElement::GlobalLine(_)
+            | ConfigElement::MatchBlock(_)
+            | ConfigElement::DisabledHost(_)
+            | ConfigElement::Template(_) => {}
+        }
+    }
+}
+
+impl JumpGraph {
+    /// Add a rule with `hops`, or reuse the last one when it has the same
+    /// hops: synced hosts tend to share a jump host.
+    fn push_rule<'h>(&mut self, hops: impl Iterator<Item = &'h str> + Clone) -> usize {
+        let same = self
+            .rules
+            .last()
+            .is_some_and(|last| last.iter().map(String::as_str).eq(hops.clone()));
+        if !same {
+            self.rules.push(hops.map(str::to_string).collect());
+        }
+        self.rules.len() - 1
+    }
+
+    /// The hops `host` is configured with, first value wins across blocks.
+    /// Empty for direct connections (including `ProxyJump none`).
+    pub fn hops(&self, host: &str) -> &[String] {
+        let exact = if is_lowercase(host) {
+            self.exact.get(host)
+        } else {
+            self.exact.get(host.to_lowercase().as_str())
+        };
+        let exact = exact.copied().flatten();
+        let limit = exact.unwrap_or(usize::MAX);
+        let wildcard = self
+            .wildcard
+            .iter()
+            .take_while(|(rule, _)| *rule < limit)
+            .find(|(_, tokens)| match_pattern_list(host, tokens))
+            .map(|(rule, _)| *rule);
+        wildcard
+            .or(exact)
+            .map_or(&[], |rule| self.rules[rule].as_slice())
+    }
+
+    /// The host right in front of `host`: the last hop.
+    pub fn bastion(&self, host: &str) -> Option<&str> {
+        self.hops(host).last().map(String::as_str)
+    }
+
+    /// Every hop on the way to `host`. ssh reaches the first hop with that
+    /// hop's own config, so the chain is followed through first hops.
+    pub fn route(&self, host: &str) -> Route {
+        let mut route = Route::default();
+        route.cycle = self.follow(host, |hops| {
+            route.hops.splice(0..0, hops.iter().cloned());
+        });
+        route
+    }
+
+    /// `route(host).cycle` without building the route.
+    pub fn is_cyclic(&self, host: &str) -> bool {
+        self.loops && self.follow(host, |_| {})
+    }
+
+    /// Walk the chain from `host` through first hops, passing each host's
+    /// hops to `visit`. True when a first hop leads back to a host already
+    /// on the chain. A host showing up again as a later hop is fine: ssh
+    /// reaches later hops through the ones before them and ignores their
+    /// own ProxyJump.
+    fn follow<'a>(&'a self, host: &'a str, mut visit: impl FnMut(&'a [String])) -> bool {
+        // First hops followed so far. Most chains are one or two levels
+        // deep, so this rarely allocates.
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = host;
+        loop {
+            let hops = self.hops(current);
+            let Some(first) = hops.first() else {
+                return false;
+            };
+            visit(hops);
+            if same_host(host, first) || chain.iter().any(|h| same_host(h, first)) {
+                return true;
+            }
+            chain.push(first);
+            current = first;
+        }
+    }
+
+    /// A hop that looks like an alias but matches no Host pattern.
+    pub fn is_dangling(&self, hop: &str) -> bool {
+        looks_like_alias(hop) && !self.is_known(hop)
+    }
+
+    /// Whether two Host lines name the same host (ignoring case). False
+    /// means there are no duplicate aliases.
+    pub fn has_repeats(&self) -> bool {
+        self.repeats
+    }
+
+    /// Whether `host` matches a positive Host pattern.
+    pub fn is_known(&self, host: &str) -> bool {
+        let exact = if is_lowercase(host) {
+            self.exact.contains_key(host)
+        } else {
+            self.exact.contains_key(host.to_lowercase().as_str())
+        };
+        exact || self.globs.iter().any(|p| match_pattern(host, p))
+    }
+
+    /// Aliases whose hops include `bastion`.
+    pub fn behind(&self, bastion: &str) -> Vec<&str> {
+        self.aliases
+            .iter()
+            .filter(|alias| {
+                self.hops(alias)
+                    .iter()
+                    .any(|hop| hop.eq_ignore_ascii_case(bastion))
+            })
+            .map(|alias| &**alias)
+            .collect()
+    }
+
+    /// Hosts grouped under their bastion, depth first. Bastions that aren't
+    /// Host aliases (hostnames, pattern matches) become top-level nodes.
+    /// Hosts stuck in a cycle are listed at the end.
+    pub fn tree(&self) -> Vec<TreeNode> {
+        let known: HashSet<String> = self.aliases.iter().map(|a| a.to_lowercase()).collect();
+        let mut children: HashMap<String, Vec<&str>> = HashMap::new();
+        let mut roots: Vec<(&str, bool)> = Vec::new();
+        let mut external: HashSet<String> = HashSet::new();
+        for alias in &self.aliases {
+            match self.bastion(alias) {
+                Some(bastion) => {
+                    children
+                        .entry(bastion.to_lowercase())
+                        .or_default()
+                        .push(alias);
+                    if !known.contains(&bastion.to_lowercase())
+                        && external.insert(bastion.to_lowercase())
+                    {
+                        roots.push((bastion, false));
+                    }
+                }
+                None => roots.push((alias, true)),
+            }
+        }
+
+        let mut nodes = Vec::new();
+        let mut visited: HashSet<String> = HashSet::new();
+        for (name, host) in roots {
+            self.push_subtree(name, host, 0, &children, &mut visited, &mut nodes);
+        }
+        for alias in &self.aliases {
+            if !visited.contains(&alias.to_lowercase()) {
+                self.push_subtree(alias, true, 0, &children, &mut visited, &mut nodes);
+            }
+        }
+        nodes
+    }
+
+    fn push_subtree(
+        &self,
+        name: &str,
+        host: bool,
+        depth: usize,
+        children: &HashMap<String, Vec<&str>>,
+        visited: &mut HashSet<String>,
+        nodes: &mut Vec<TreeNode>,
+    ) {
+        let key = name.to_lowercase();
+        if !visited.insert(key.clone()) {
+            return;
+        }
+        let kids = children.get(&key).map_or(&[][..], Vec::as_slice);
+        nodes.push(TreeNode {
+            name: name.to_string(),
+            depth,
+            host,
+            behind: kids.len(),
+            cycle: host && self.is_cyclic(name),
+        });
+        for kid in kids {
+            self.push_subtree(kid, true, depth + 1, children, visited, nodes);
+        }
+    }
+}
+
+/// Hops of a ProxyJump value (`a,user@b:2222`). `none` means no hops.
+pub(super) fn jump_hops(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter_map(hop_host)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Jump hosts in a ProxyCommand that runs ssh: its `-J` hops followed by the
+/// destination. Other commands (nc, cloudflared, ...) yield nothing.
+pub(super) fn proxy_command_hops(command: &str) -> Vec<String> {
+    let mut args = command.split_whitespace();
+    let mut program = args.next();
+    if program == Some("exec") {
+        program = args.next();
+    }
+    if program.and_then(|p| p.rsplit('/').next()) != Some("ssh") {
+        return Vec::new();
+    }
+    let mut hops = Vec::new();
+    while let Some(arg) = args.next() {
+        let Some(flags) = arg.strip_prefix('-') else {
+            hops.extend(hop_host(arg).map(str::to_string));
+            break;
+        };
+        if let Some(i) = flags.find(|c| SSH_ARG_FLAGS.contains(c)) {
+            let rest = &flags[i + 1..];
+            let value = if rest.is_empty() { args.next().unwrap_or("") } else { rest };
+            if flags[i..].starts_with('J') {
+                hops.extend(jump_hops(value));
+            }
+        }
+    }
+    hops
+}
+
+/// The host part of a hop (`[user@]host[:port]` or `ssh://...`). None for
+/// `none`, empty hops and unexpanded `%` tokens.
+fn hop_host(hop: &str) -> Option<&str> {
+    let hop = hop.trim();
+    let hop = hop.strip_prefix("ssh://").unwrap_or(hop);
+    let host = hop.rsplit_once('@').map_or(hop, |(_, h)| h);
+    let host = match host.rsplit_once(':') {
+        Some((h, port)) if port.chars().all(|c| c.is_ascii_digit()) => h,
+        _ => host,
+    };
+    if host.is_empty() || host.eq_ignore_ascii_case("none") || host.contains('%') {
+        return None;
+    }
+    Some(host)
+}
+
+/// ASCII with no uppercase letters, so `to_lowercase` would return it unchanged.
+fn is_lowercase(s: &str) -> bool {
+    s.bytes().all(|b| b.is_ascii() && !b.is_ascii_uppercase())
+}
+
+/// Host names compared the way the graph keys them (lowercased).
+fn same_host(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b) || (!(a.is_ascii() && b.is_ascii()) && a.to_lowercase() == b.to_lowercase())
+}
+
+/// Hostnames and IPs contain dots, colons or brackets. Aliases usually don't.
+fn looks_like_alias(host: &str) -> bool {
+    !host.contains(['.', ':', '['])
+}
+
+/// The alias part of a ProxyJump hop. None for `none` and for hops that look
+/// like real hostnames or IPs.
+pub(super) fn jump_alias(hop: &str) -> Option<&str> {
+    hop_host(hop).filter(|host| looks_like_alias(host))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn parse_str(content: &str) -> SshConfigFile {
+        SshConfigFile {
+            elements: SshConfigFile::parse_content(content),
+            path: PathBuf::from("/tmp/test_config"),
+            crlf: false,
+            index: Default::default(),
+        }
+    }
+
+    #[test]
+    fn route_follows_first_hops() {
+        let config = parse_str(
+            "Host edge\n  HostName edge.example.com\n\nHost bastion\n  ProxyJump edge\n\nHost web\n  ProxyJump bastion\n\nHost db\n  ProxyJump ops@bastion:2222,web\n",
+        );
+        let graph = config.jump_graph();
+        assert!(graph.hops("edge").is_empty());
+        assert_eq!(graph.route("web").describe("web"), "edge \u{2192} bastion \u{2192} web");
+        assert_eq!(graph.route("db").hops, vec!["edge", "bastion", "web"]);
+        assert_eq!(graph.bastion("db"), Some("web"));
+        assert_eq!(graph.behind("bastion"), vec!["web", "db"]);
+        assert!(["edge", "bastion", "web", "db"].iter().all(|a| !graph.route(a).cycle));
+    }
+
+    #[test]
+    fn first_matching_block_wins() {
+        let config = parse_str(
+            "Host direct\n  ProxyJump none\n\nHost *.internal !gw.internal\n  ProxyJump gw.internal\n\nHost app.internal\n  ProxyJump other\n\nHost *\n  ProxyJump jump\n",
+        );
+        let graph = config.jump_graph();
+        assert!(graph.hops("direct").is_empty());
+        assert_eq!(graph.hops("app.internal"), ["gw.internal"]);
+        assert_eq!(graph.hops("gw.internal"), ["jump"]);
+        assert_eq!(graph.hops("anything"), ["jump"]);
+    }
+
+    #[test]
+    fn later_block_sets_hops_for_alias_seen_without_them() {
+        let config = parse_str(
+            "Host Web\n  User root\n\nHost web\n  ProxyJump bastion\n\nHost db\n  ProxyJump bastion\n\nHost db\n  ProxyJump other\n",
+        );
+        let graph = config.jump_graph();
+        assert_eq!(graph.hops("WEB"), ["bastion"]);
+        assert_eq!(graph.hops("db"), ["bastion"]);
+        assert!(graph.is_known("web"));
+        assert!(!graph.is_known("bastion"));
+    }
+
+    #[test]
+    fn proxy_command_hops_from_ssh() {
+        assert_eq!(proxy_command_hops("ssh -W %h:%p bastion"), vec!["bastion"]);
+        assert_eq!(
+            proxy_command_hops("exec /usr/bin/ssh -q -J edge admin@bastion nc %h %p"),
+            vec!["edge", "bastion"]
+        );
+        assert_eq!(proxy_command_hops("ssh -oStrictHostKeyChecking=no gw:22 -W %h:%p"), vec!["gw"]);
+        assert!(proxy_command_hops("nc -X 5 -x proxy:1080 %h %p").is_empty());
+        assert!(proxy_command_hops("none").is_empty());
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let config = parse_str(
+            "Host a\n  ProxyJump b\n\nHost b\n  ProxyCommand ssh -W %h:%p a\n\nHost c\n  ProxyJump a\n\nHost *\n  ProxyJump jump\n\nHost jump\n  HostName 10.0.0.1\n",
+        );
+        let graph = config.jump_graph();
+        // jump matches Host * and jumps through itself
+        for alias in ["a", "b", "c", "jump"] {
+            assert!(graph.route(alias).cycle, "{} should loop", alias);
+        }
+        let route = graph.route("a");
+        assert!(route.cycle);
+        assert_eq!(route.describe("a"), "a \u{2192} b \u{2192} a");
+    }
+
+    #[test]
+    fn is_cyclic_matches_route() {
+        let config = parse_str(
+            "Host web\n  ProxyJump edge,web\n\nHost db\n  ProxyJump edge,edge\n\nHost app\n  ProxyJump web\n\nHost edge\n  ProxyJump app\n",
+        );
+        let graph = config.jump_graph();
+        for alias in ["web", "db", "app", "edge"] {
+            assert_eq!(graph.is_cyclic(alias), graph.route(alias).cycle, "{}", alias);
+        }
+        // web -> edge -> app -> web through first hops
+        assert!(graph.is_cyclic("web") && graph.is_cyclic("app"));
+        let graph = parse_str("Host web\n  ProxyJump edge,web\n\nHost db\n  ProxyJump edge,edge\n").jump_graph();
+        assert!(!graph.is_cyclic("web") && !graph.is_cyclic("db"));
+
+        let graph = parse_str("Host app\n  ProxyJump edge\n\nHost db\n  ProxyJump edge\n").jump_graph();
+        assert!(!graph.is_cyclic("app") && !graph.route("db").cycle);
+    }
+
+    #[test]
+    fn repeated_later_hop_is_not_a_cycle() {
+        // ssh -J web-01,bastion reaches bastion through web-01, whatever
+        // bastion's own config says
+        let config = parse_str(
+            "Host work1\n  ProxyJump web-01,bastion\n\nHost web-01\n  ProxyJump bastion\n\nHost bastion\n  HostName 10.0.0.1\n",
+        );
+        let graph = config.jump_graph();
+        let route = graph.route("work1");
+        assert!(!route.cycle && !graph.is_cyclic("work1"));
+        assert_eq!(route.describe("work1"), "bastion \u{2192} web-01 \u{2192} bastion \u{2192} work1");
+    }
+
+    #[test]
+    fn dangling_references() {
+        let config = parse_str(
+            "Host jump-*\n  User admin\n\nHost web\n  ProxyJump bastion,jump-eu\n\nHost db\n  ProxyJump gw.example.com\n\nHost cache\n  ProxyCommand ssh -W %h:%p missing\n",
+        );
+        let graph = config.jump_graph();
+        assert!(graph.is_dangling("bastion"));
+        assert!(graph.is_dangling("missing"));
+        assert!(!graph.is_dangling("jump-eu"));
+        assert!(!graph.is_dangling("gw.example.com"));
+    }
+
+    #[test]
+    fn tree_groups_hosts_under_bastion() {
+        let config = parse_str(
+            "Host bastion\n  HostName 1.2.3.4\n\nHost web\n  ProxyJump bastion\n\nHost db\n  ProxyJump edge,bastion\n\nHost solo\n  HostName 5.6.7.8\n\nHost ext\n  ProxyJump gw.example.com\n\nHost x\n  ProxyJump y\n\nHost y\n  ProxyJump x\n",
+        );
+        let rows: Vec<(String, usize, bool, usize, bool)> = config
+            .jump_graph()
+            .tree()
+            .into_iter()
+            .map(|n| (n.name, n.depth, n.host, n.behind, n.cycle))
+            .collect();
+        let expected = vec![
+            ("bastion", 0, true, 2, false),
+            ("web", 1, true, 0, false),
+            ("db", 1, true, 0, false),
+            ("solo", 0, true, 0, false),
+            ("gw.example.com", 0, false, 1, false),
+            ("ext", 1, true, 0, false),
+            ("x", 0, true, 1, true),
+            ("y", 1, true, 1, true),
+        ];
+        let expected: Vec<(String, usize, bool, usize, bool)> = expected
+            .into_iter()
+            .map(|(n, d, h, b, c)| (n.to_string(), d, h, b, c))
+            .collect();
+        assert_eq!(rows, expected);
+    }
+
+    #[test]
+    fn included_files_are_part_of_the_graph() {
+        let mut config = parse_str("Include conf.d/*\n\nHost web\n  ProxyJump bastion\n");
+        let included = SshConfigFile::parse_content("Host bastion\n  ProxyJump edge\n");
+        if let ConfigElement::Include(include) = &mut config.elements[0] {
+            include.resolved_files.push(super::super::model::IncludedFile {
+                path: PathBuf::from("/tmp/conf.d/jump"),
+                elements: included,
+                crlf: false,
+            });
+        }
+        let graph = config.jump_graph();
+        assert_eq!(graph.route("web").hops, vec!["edge", "bastion"]);
+        assert_eq!(graph.behind("bastion"), vec!["web"]);
+    }
+}
@@ -1,4 +1,6 @@
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::SystemTime;
 
 use anyhow::{Context, Result};
@@ -6,35 +8,145 @@ use anyhow::{Context, Result};
 use super::model::{ConfigElement, SshConfigFile};
 use crate::fs_util;
 
+/// Number of `.bak.<millis>` backups kept per file.
+static BACKUP_RETENTION: AtomicUsize = AtomicUsize::new(5);
+
 impl SshConfigFile {
+    /// Set how many backups `write` keeps per file (at least 1).
+    pub fn set_backup_retention(keep: usize) {
+        BACKUP_RETENTION.store(keep.max(1), Ordering::Relaxed);
+    }
+
+    pub fn backup_retention() -> usize {
+        BACKUP_RETENTION.load(Ordering::Relaxed)
+    }
+
     /// Write the config back to disk.
     /// Creates a backup before writing and uses atomic write (temp file + rename).
     /// Resolves symlinks so the rename targets the real file, not the link.
+    /// Included files that were modified are written too. Every file is backed
+    /// up before any is replaced, so a failed backup leaves them all untouched.
     pub fn write(&self) -> Result<()> {
-        // Resolve symlinks so we write through to the real file
-        let target_path = fs::canonicalize(&self.path).unwrap_or_else(|_| self.path.clone());
-
-        // Create backup if the file exists, keep only last 5
-        if self.path.exists() {
-            self.create_backup()
-                .context("Failed to create backup of SSH config")?;
-            self.prune_backups(5).ok();
+        let mut pending = vec![(self.path.clone(), self.serialize())];
+        Self::changed_included_files(&self.elements, &mut pending);
+        for (path, _) in &pending {
+            Self::back_up(path, &self.path)?;
         }
+        for (path, content) in &pending {
+            Self::replace(path, content)?;
+        }
+        Ok(())
+    }
 
-        let content = self.serialize();
+    /// Every resolved Include file whose content differs from disk, with its new content.
+    /// Untouched files are left out so their mtime and backups don't churn.
+    fn changed_included_files(elements: &[ConfigElement], pending: &mut Vec<(PathBuf, String)>) {
+        for element in elements {
+            let ConfigElement::Include(include) = element else {
+                continue;
+            };
+            for file in &include.resolved_files {
+                Self::changed_included_files(&file.elements, pending);
+                let content = Self::serialize_elements(&file.elements, file.crlf);
+                if let Ok(existing) = fs::read_to_string(&file.path) {
+                    let unchanged =
+                        Self::serialize_elements(&Self::parse_content(&existing), file.crlf);
+                    if unchanged == content {
+                        continue;
+                    }
+                }
+                pending.push((file.path.clone(), content));
+            }
+        }
+    }
 
-        fs_util::atomic_write(&target_path, content.as_bytes())
-            .with_context(|| format!("Failed to write SSH config to {}", target_path.display()))?;
+    /// Back up `path` (keeping the last `backup_retention()` backups) and atomically
+    /// replace it. `config` is the main config the file belongs to.
+    pub(super) fn write_file(path: &Path, content: &str, config: &Path) -> Result<()> {
+        Self::back_up(path, config)?;
+        Self::replace(path, content)
+    }
 
+    /// Directory and file name prefix of `path`'s backups. They all go next to
+    /// the main `config`. Include files are named after their whole path, so
+    /// files with the same name in different directories don't mix and a glob
+    /// like `Include config.d/*` never picks a backup up.
+    pub(super) fn backup_location(path: &Path, config: &Path) -> Option<(PathBuf, String)> {
+        let dir = config.parent()?.to_path_buf();
+        if path == config {
+            return Some((dir, format!("{}.bak.", path.file_name()?.to_string_lossy())));
+        }
+        let full = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let name = full.to_string_lossy().trim_start_matches('/').replace(['/', '\\'], "%");
+        Some((dir, format!("{}.bak.", name)))
+    }
+
+    /// Copy `path` to a new backup if it exists, and prune old ones.
+    fn back_up(path: &Path, config: &Path) -> Result<()> {
+        if path.exists() {
+            let (dir, prefix) =
+                Self::backup_location(path, config).context("No parent directory")?;
+            Self::create_backup(path, &dir, &prefix).context("Failed to create backup of SSH config")?;
+            Self::prune_backups(&dir, &prefix, Self::backup_retention()).ok();
+        }
         Ok(())
     }
 
+    /// Atomically replace `path`, writing through symlinks to the real file.
+    fn replace(path: &Path, content: &str) -> Result<()> {
+        let target_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        fs_util::atomic_write(&target_path, content.as_bytes())
+            .with_context(|| format!("Failed to write SSH config to {}", target_path.display()))
+    }
+
     /// Serialize the config to a string.
     /// Collapses consecutive blank lines to prevent accumulation after deletions.
     pub fn serialize(&self) -> String {
+        Self::serialize_elements(&self.elements, self.crlf)
+    }
+
+    /// Serialized content of the main file and every resolved Include file.
+    /// Two configs with equal snapshots write identical files.
+    pub fn snapshot(&self) -> Vec<(PathBuf, String)> {
+        let mut files = vec![(self.path.clone(), self.serialize())];
+        Self::snapshot_included(&self.elements, &mut files);
+        files
+    }
+
+    /// Paths of every resolved Include file, nested ones included.
+    pub fn included_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        Self::included_paths_in(&self.elements, &mut paths);
+        paths
+    }
+
+    fn included_paths_in(elements: &[ConfigElement], paths: &mut Vec<PathBuf>) {
+        for element in elements {
+            if let ConfigElement::Include(include) = element {
+                for file in &include.resolved_files {
+                    paths.push(file.path.clone());
+                    Self::included_paths_in(&file.elements, paths);
+                }
+            }
+        }
+    }
+
+    fn snapshot_included(elements: &[ConfigElement], files: &mut Vec<(PathBuf, String)>) {
+        for element in elements {
+            if let ConfigElement::Include(include) = element {
+                for file in &include.resolved_files {
+                    files.push((file.path.clone(), Self::serialize_elements(&file.elements, file.crlf)));
+                    Self::snapshot_included(&file.elements, files);
+                }
+            }
+        }
+    }
+
+    /// Serialize a list of elements (the main config or an included file).
+    fn serialize_elements(elements: &[ConfigElement], crlf: bool) -> String {
         let mut lines = Vec::new();
 
-        for element in &self.elements {
+        for element in elements {
             match element {
                 ConfigElement::GlobalLine(line) => {
                     lines.push(line.clone());
@@ -45,9 +157,18 @@ impl SshConfigFile {
                         lines.push(directive.raw_line.clone());
                     }
                 }
+                ConfigElement::MatchBlock(block) => {
+                    lines.push(block.raw_match_line.clone());
+                    for directive in &block.directives {
+                        lines.push(directive.raw_line.clone());
+                    }
+                }
                 ConfigElement::Include(include) => {
                     lines.push(include.raw_line.clone());
                 }
+                ConfigElement::DisabledHost(commented) | ConfigElement::Template(commented) => {
+                    lines.extend(commented.raw_lines.iter().cloned());
+                }
             }
         }
 
@@ -63,7 +184,7 @@ impl SshConfigFile {
             collapsed.push(line);
         }
 
-        let line_ending = if self.crlf { "\r\n" } else { "\n" };
+        let line_ending = if crlf { "\r\n" } else { "\n" };
         let mut result = String::new();
         for line in &collapsed {
             result.push_str(line);
@@ -76,23 +197,19 @@ impl SshConfigFile {
         result
     }
 
-    /// Create a timestamped backup of the current config file.
+    /// Create a timestamped backup of a config file.
     /// Backup files are created with chmod 600 to match the source file's sensitivity.
-    fn create_backup(&self) -> Result<()> {
+    fn create_backup(path: &Path, dir: &Path, prefix: &str) -> Result<()> {
         let timestamp = SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap_or_default()
             .as_millis();
-        let backup_name = format!(
-            "{}.bak.{}",
-            self.path.file_name().unwrap_or_default().to_string_lossy(),
-            timestamp
-        );
-        let backup_path = self.path.with_file_name(backup_name);
-        fs::copy(&self.path, &backup_path).with_context(|| {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let backup_path = dir.join(format!("{}{}", prefix, timestamp));
+        fs::copy(path, &backup_path).with_context(|| {
             format!(
                 "Failed to copy {} to {}",
-                self.path.display(),
+                path.display(),
                 backup_path.display()
             )
         })?;
@@ -108,15 +225,10 @@ impl SshConfigFile {
     }
 
     /// Remove old backups, keeping only the most recent `keep` files.
-    fn prune_backups(&self, keep: usize) -> Result<()> {
-        let parent = self.path.parent().context("No parent directory")?;
-        let prefix = format!(
-            "{}.bak.",
-            self.path.file_name().unwrap_or_default().to_string_lossy()
-        );
-        let mut backups: Vec<_> = fs::read_dir(parent)?
+    fn prune_backups(dir: &Path, prefix: &str, keep: usize) -> Result<()> {
+        let mut backups: Vec<_> = fs::read_dir(dir)?
             .filter_map(|e| e.ok())
-            .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+            .filter(|e| e.file_name().to_string_lossy().starts_with(prefix))
             .collect();
         backups.sort_by_key(|e| e.file_name());
         if backups.len() > keep {
@@ -139,6 +251,7 @@ mod tests {
             elements: SshConfigFile::parse_content(content),
             path: PathBuf::from("/tmp/test_config"),
             crlf: content.contains("\r\n"),
+            index: Default::default(),
         }
     }
 
@@ -275,4 +388,120 @@ Host myserver
         assert!(output.contains("LocalForward 8080 localhost:80"));
         assert!(output.contains("Compression yes"));
     }
+
+    #[test]
+    fn test_write_updates_changed_included_file_only() {
+        use crate::ssh_config::model::{IncludeDirective, IncludedFile};
+        let dir = std::env::temp_dir().join(format!("purple_test_write_include_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let main_path = dir.join("config");
+        let work_path = dir.join("work.conf");
+        let home_path = dir.join("home.conf");
+        fs::write(&main_path, "Include *.conf\n").unwrap();
+        fs::write(&work_path, "Host work\r\n  HostName 10.0.0.1\r\n").unwrap();
+        fs::write(&home_path, "Host home\n  HostName 10.0.0.2\n").unwrap();
+
+        let included = |path: &std::path::Path| {
+            let content = fs::read_to_string(path).unwrap();
+            IncludedFile {
+                path: path.to_path_buf(),
+                elements: SshConfigFile::parse_content(&content),
+                crlf: content.contains("\r\n"),
+            }
+        };
+        let mut config = SshConfigFile {
+            elements: vec![ConfigElement::Include(IncludeDirective {
+                raw_line: "Include *.conf".to_string(),
+                pattern: "*.conf".to_string(),
+                resolved_files: vec![included(&home_path), included(&work_path)],
+            })],
+            path: main_path.clone(),
+            crlf: false,
+            index: Default::default(),
+        };
+        config.update_host(
+            "work",
+            &HostEntry {
+                alias: "work".to_string(),
+                hostname: "10.0.0.9".to_string(),
+                ..Default::default()
+            },
+        );
+        config.write().unwrap();
+
+        // Changed include rewritten with its own line endings and a backup
+        assert_eq!(
+            fs::read_to_string(&work_path).unwrap(),
+            "Host work\r\n  HostName 10.0.0.9\r\n"
+        );
+        let backups = |path: &std::path::Path| {
+            let (backup_dir, prefix) =
+                SshConfigFile::backup_location(path, &main_path).unwrap();
+            fs::read_dir(backup_dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(|e| e.ok())
+                        .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+                        .count()
+                })
+                .unwrap_or(0)
+        };
+        assert_eq!(backups(&work_path), 1);
+        // Untouched include left alone
+        assert_eq!(backups(&home_path), 0);
+        // Include backups are named after the whole path, so *.conf doesn't match them
+        assert!(
+            fs::read_dir(&dir)
+                .unwrap()
+                .filter_map(|e| e.ok())
+                .all(|e| !e.file_name().to_string_lossy().starts_with("work.conf.bak."))
+        );
+        assert_eq!(fs::read_to_string(&main_path).unwrap(), "Include *.conf\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_include_backups_do_not_become_hosts() {
+        let dir = std::env::temp_dir().join(format!("purple_test_include_glob_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("config.d")).unwrap();
+        let main_path = dir.join("config");
+        fs::write(&main_path, "Include config.d/*
+
+Host main
+  HostName 10.0.0.1
+").unwrap();
+        fs::write(dir.join("config.d/work"), "Host work
+  HostName 10.0.0.2
+").unwrap();
+
+        for hostname in ["10.0.0.3", "10.0.0.4"] {
+            let mut config = SshConfigFile::parse(&main_path).unwrap();
+            config.update_host(
+                "work",
+                &HostEntry {
+                    alias: "work".to_string(),
+                    hostname: hostname.to_string(),
+                    ..Default::default()
+                },
+            );
+            config.write().unwrap();
+        }
+
+        let names: Vec<String> = fs::read_dir(dir.join("config.d"))
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["work"]);
+        let aliases: Vec<String> = SshConfigFile::parse(&main_path)
+            .unwrap()
+            .host_entries()
+            .into_iter()
+            .map(|h| h.alias)
+            .collect();
+        assert_eq!(aliases, vec!["work", "main"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -149,19 +149,20 @@ fn parse_keygen_output(line: &str) -> Option<(String, String, String, String)> {
     Some((bits, fingerprint, comment, key_type))
 }
 
-/// Find host aliases that reference a given key path via IdentityFile.
+/// Find host aliases that reference a given key path via any IdentityFile.
 /// Hosts without an explicit IdentityFile are linked to all keys (SSH tries them all).
 fn find_linked_hosts(full_path: &Path, display_path: &str, hosts: &[HostEntry]) -> Vec<String> {
     hosts
         .iter()
         .filter(|h| {
-            if h.identity_file.is_empty() {
+            if h.identity_files.is_empty() {
                 // No explicit IdentityFile — SSH tries all available keys
                 return true;
             }
             // Match against both the display path (~/.ssh/...) and the full path
-            h.identity_file == display_path
-                || Path::new(&h.identity_file) == full_path
+            h.identity_files
+                .iter()
+                .any(|f| f == display_path || Path::new(f) == full_path)
         })
         .map(|h| h.alias.clone())
         .collect()
@@ -232,12 +233,12 @@ mod tests {
         let hosts = vec![
             HostEntry {
                 alias: "prod".to_string(),
-                identity_file: "~/.ssh/id_ed25519".to_string(),
+                identity_files: vec!["~/.ssh/id_ed25519".to_string()],
                 ..Default::default()
             },
             HostEntry {
                 alias: "staging".to_string(),
-                identity_file: "~/.ssh/other_key".to_string(),
+                identity_files: vec!["~/.ssh/other_key".to_string()],
                 ..Default::default()
             },
         ];
@@ -253,7 +254,7 @@ mod tests {
     fn test_find_linked_hosts_full_path() {
         let hosts = vec![HostEntry {
             alias: "server".to_string(),
-            identity_file: "/home/user/.ssh/deploy_key".to_string(),
+            identity_files: vec!["/home/user/.ssh/deploy_key".to_string()],
             ..Default::default()
         }];
         let linked = find_linked_hosts(
@@ -268,7 +269,7 @@ mod tests {
     fn test_find_linked_hosts_no_identity_file_links_to_all() {
         let hosts = vec![HostEntry {
             alias: "server".to_string(),
-            identity_file: String::new(),
+            identity_files: Vec::new(),
             ..Default::default()
         }];
         let linked = find_linked_hosts(
@@ -283,7 +284,7 @@ mod tests {
     fn test_find_linked_hosts_wrong_identity_file() {
         let hosts = vec![HostEntry {
             alias: "server".to_string(),
-            identity_file: "~/.ssh/other_key".to_string(),
+            identity_files: vec!["~/.ssh/other_key".to_string()],
             ..Default::default()
         }];
         let linked = find_linked_hosts(
@@ -294,6 +295,21 @@ mod tests {
         assert!(linked.is_empty());
     }
 
+    #[test]
+    fn test_find_linked_hosts_any_identity_file() {
+        let hosts = vec![HostEntry {
+            alias: "server".to_string(),
+            identity_files: vec!["~/.ssh/other_key".to_string(), "~/.ssh/id_rsa".to_string()],
+            ..Default::default()
+        }];
+        let linked = find_linked_hosts(
+            Path::new("/home/user/.ssh/id_rsa"),
+            "~/.ssh/id_rsa",
+            &hosts,
+        );
+        assert_eq!(linked, vec!["server"]);
+    }
+
     #[test]
     fn test_type_display() {
         let key = SshKeyInfo {
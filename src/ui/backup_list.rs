@@ -0,0 +1,164 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::App;
+use crate::history::ConnectionHistory;
+use crate::ssh_config::{backup, diff};
+use crate::ssh_config::model::SshConfigFile;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let area = super::centered_rect(80, 85, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = format!(
+        " Backups (keeping {}) ",
+        SshConfigFile::backup_retention()
+    );
+    let block = Block::default()
+        .title(Span::styled(title, theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let list_height = (app.backups.len().max(1) as u16).min(8);
+    let chunks = Layout::vertical([
+        Constraint::Length(list_height),
+        Constraint::Length(1),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    if app.backups.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No backups yet. purple makes one before every write.")
+                .style(theme::muted()),
+            chunks[0],
+        );
+    } else {
+        let items: Vec<ListItem> = app
+            .backups
+            .iter()
+            .map(|item| {
+                let summary = match &item.comparison {
+                    Some(c) if c.is_identical() => "identical".to_string(),
+                    Some(c) => c.changes.summary(),
+                    None => "unreadable".to_string(),
+                };
+                let mut spans = vec![
+                    Span::styled(
+                        format!(" {}", backup::format_utc(item.backup.created)),
+                        theme::bold(),
+                    ),
+                    Span::styled(
+                        format!("  {:<8}", ConnectionHistory::format_time_ago(item.backup.created)),
+                        theme::muted(),
+                    ),
+                    Span::raw(format!("  {}", summary)),
+                ];
+                if item.backup.file != app.reload.config_path {
+                    spans.push(Span::styled(
+                        format!("  {}", item.backup.file.display()),
+                        theme::muted(),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(theme::selected())
+            .highlight_symbol("  ");
+        frame.render_stateful_widget(list, chunks[0], &mut app.ui.backup_list_state);
+    }
+
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            format!("  {}", "─".repeat((inner.width as usize).saturating_sub(4))),
+            theme::muted(),
+        )),
+        chunks[1],
+    );
+
+    let selected = app
+        .ui
+        .backup_list_state
+        .selected()
+        .and_then(|i| app.backups.get(i));
+    if let Some(item) = selected {
+        let width = (inner.width as usize).saturating_sub(3);
+        let mut lines: Vec<Line> = Vec::new();
+        match &item.comparison {
+            None => lines.push(Line::from(Span::styled(
+                "  Can't read this backup.",
+                theme::error(),
+            ))),
+            Some(c) => {
+                lines.push(Line::from(Span::styled(
+                    "  Changes since this backup",
+                    theme::section_header(),
+                )));
+                let hosts = [
+                    ("+", &c.changes.added, theme::success()),
+                    ("-", &c.changes.removed, theme::error()),
+                    ("~", &c.changes.changed, theme::bold()),
+                ];
+                for (sign, aliases, style) in hosts {
+                    if !aliases.is_empty() {
+                        lines.push(Line::from(vec![
+                            Span::styled(format!("  {} ", sign), style),
+                            Span::raw(super::truncate(
+                                &aliases.join(", "),
+                                width.saturating_sub(4),
+                            )),
+                        ]));
+                    }
+                }
+                if c.changes.is_empty() {
+                    lines.push(Line::from(Span::styled("  No host changes.", theme::muted())));
+                }
+                lines.push(Line::from(""));
+                for line in diff::unified(&c.diff, 3) {
+                    let style = if line.starts_with("@@") {
+                        theme::muted()
+                    } else if line.starts_with('+') {
+                        theme::success()
+                    } else if line.starts_with('-') {
+                        theme::error()
+                    } else {
+                        theme::muted()
+                    };
+                    lines.push(Line::from(Span::styled(
+                        format!("  {}", super::truncate(&line, width)),
+                        style,
+                    )));
+                }
+            }
+        }
+        let max_scroll = (lines.len() as u16).saturating_sub(chunks[2].height);
+        app.ui.backup_diff_scroll = app.ui.backup_diff_scroll.min(max_scroll);
+        frame.render_widget(
+            Paragraph::new(lines).scroll((app.ui.backup_diff_scroll, 0)),
+            chunks[2],
+        );
+    }
+
+    let mut spans = Vec::new();
+    if !app.backups.is_empty() {
+        spans.push(Span::styled(" Enter", theme::primary_action()));
+        spans.push(Span::styled(" restore ", theme::muted()));
+        spans.push(Span::styled("\u{2502} ", theme::muted()));
+        spans.push(Span::styled("PgUp/PgDn", theme::accent_bold()));
+        spans.push(Span::styled(" scroll  ", theme::muted()));
+    } else {
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled("+/-", theme::accent_bold()));
+    spans.push(Span::styled(" keep  ", theme::muted()));
+    spans.push(Span::styled("Esc", theme::accent_bold()));
+    spans.push(Span::styled(" back", theme::muted()));
+    super::render_footer_with_status(frame, chunks[3], spans, app);
+}
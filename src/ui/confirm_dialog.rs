@@ -36,6 +36,33 @@ pub fn render(frame: &mut Frame, _app: &App, alias: &str) {
     frame.render_widget(paragraph, area);
 }
 
+pub fn render_bulk_delete(frame: &mut Frame, count: usize) {
+    let area = super::centered_rect_fixed(48, 7, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Confirm Delete ", theme::danger()))
+        .borders(Borders::ALL)
+        .border_style(theme::border_danger());
+
+    let text = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("  Delete {} marked hosts?", count),
+            theme::bold(),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("    y", theme::danger()),
+            Span::styled(" yes   ", theme::muted()),
+            Span::styled("Esc", theme::accent_bold()),
+            Span::styled(" no", theme::muted()),
+        ]),
+    ];
+
+    frame.render_widget(Paragraph::new(text).block(block), area);
+}
+
 pub fn render_host_key_reset(frame: &mut Frame, _app: &App, hostname: &str) {
     let display = super::truncate(hostname, 40);
     let area = super::centered_rect_fixed(52, 9, frame.area());
@@ -0,0 +1,142 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use unicode_width::UnicodeWidthStr;
+
+use super::theme;
+use crate::app::App;
+use crate::ssh_config::defaults::{DEFAULT_KEYS, DefaultScope};
+
+/// Label column width: longest key plus a gap.
+const LABEL_WIDTH: usize = 21;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+    let warnings = warning_lines(app);
+    let other = other_globals(app);
+
+    // borders + rows + blank + notes + footer
+    let notes = warnings.len() + usize::from(other.is_some());
+    let notes_height = if notes == 0 { 0 } else { notes as u16 * 2 + 1 };
+    let height = 2 + DEFAULT_KEYS.len() as u16 + 1 + notes_height + 1;
+    let form_area = super::centered_rect(70, 80, area);
+    let form_area = super::centered_rect_fixed(form_area.width, height, area);
+
+    frame.render_widget(Clear, form_area);
+
+    let outer_block = Block::default()
+        .title(Span::styled(" Defaults ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::border());
+
+    let inner = outer_block.inner(form_area);
+    frame.render_widget(outer_block, form_area);
+
+    let chunks = Layout::vertical([
+        Constraint::Length(DEFAULT_KEYS.len() as u16), // Fields
+        Constraint::Length(1),                         // Spacer
+        Constraint::Min(0),                            // Warnings
+        Constraint::Length(1),                         // Footer
+    ])
+    .split(inner);
+
+    render_fields(frame, chunks[0], app);
+
+    let mut lines: Vec<Line> = warnings
+        .into_iter()
+        .map(|text| {
+            Line::from(vec![
+                Span::styled(" \u{26A0} ", theme::error()),
+                Span::raw(text),
+            ])
+        })
+        .collect();
+    if let Some(text) = other {
+        lines.push(Line::from(Span::styled(format!(" {}", text), theme::muted())));
+    }
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), chunks[2]);
+
+    super::render_footer_with_status(frame, chunks[3], vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" save ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Tab", theme::accent_bold()),
+        Span::styled(" next ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" cancel", theme::muted()),
+    ], app);
+}
+
+fn render_fields(frame: &mut Frame, area: Rect, app: &App) {
+    let form = &app.defaults_form;
+    let lines: Vec<Line> = DEFAULT_KEYS
+        .iter()
+        .zip(&form.values)
+        .enumerate()
+        .map(|(i, (key, value))| {
+            let focused = form.focused == i;
+            let label_style = if focused { theme::accent_bold() } else { theme::muted() };
+            let mut spans = vec![Span::styled(format!(" {:<width$}", key, width = LABEL_WIDTH - 1), label_style)];
+            if value.is_empty() && !focused {
+                spans.push(Span::styled("not set", theme::muted()));
+            } else {
+                spans.push(Span::styled(value.as_str(), if focused { theme::bold() } else { theme::muted() }));
+            }
+            // Where the saved value lives
+            let source = match app.config.default_value(key).map(|(_, scope)| scope) {
+                Some(DefaultScope::Global) => "  top of file",
+                Some(DefaultScope::HostStar) => "  Host *",
+                None => "",
+            };
+            spans.push(Span::styled(source, theme::muted()));
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), area);
+
+    let prefix: String = form.focused_value().chars().take(form.cursor_pos).collect();
+    let cursor_x = area
+        .x
+        .saturating_add(LABEL_WIDTH as u16)
+        .saturating_add(prefix.width().min(u16::MAX as usize) as u16);
+    let cursor_y = area.y + form.focused as u16;
+    if cursor_x < area.x.saturating_add(area.width) {
+        frame.set_cursor_position((cursor_x, cursor_y));
+    }
+}
+
+/// One line per `Host *` block that overrides hosts below it.
+fn warning_lines(app: &App) -> Vec<String> {
+    let overridden = app.config.host_star_overrides();
+    if overridden.is_empty() {
+        return Vec::new();
+    }
+    let aliases: Vec<&str> = overridden.iter().map(|o| o.alias.as_str()).collect();
+    let shown = if aliases.len() > 3 {
+        format!("{} and {} more", aliases[..3].join(", "), aliases.len() - 3)
+    } else {
+        aliases.join(", ")
+    };
+    vec![format!(
+        "Host * sits above {} and wins for the keys they set (first value wins). Move it to the end of the file.",
+        shown
+    )]
+}
+
+/// Top-level directives the form doesn't cover, so they aren't a surprise.
+fn other_globals(app: &App) -> Option<String> {
+    let other: Vec<String> = app
+        .config
+        .global_directives()
+        .into_iter()
+        .filter(|(k, _)| !DEFAULT_KEYS.iter().any(|d| d.eq_ignore_ascii_case(k)))
+        .map(|(k, v)| format!("{} {}", k, v))
+        .collect();
+    if other.is_empty() {
+        None
+    } else {
+        Some(format!("Also at the top of the file: {}", other.join(", ")))
+    }
+}
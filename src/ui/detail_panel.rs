@@ -7,6 +7,7 @@ use super::theme;
 use crate::app::{App, PingStatus};
 use crate::history::ConnectionHistory;
 use crate::ssh_config::model::ConfigElement;
+use crate::ssh_config::resolve::ValueSource;
 
 const LABEL_WIDTH: usize = 14;
 
@@ -55,23 +56,55 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
         push_field(&mut lines, "Port", &host.port.to_string(), max_value_width);
     }
 
-    if !host.proxy_jump.is_empty() {
-        push_field(&mut lines, "ProxyJump", &host.proxy_jump, max_value_width);
+    let route = app.jump_graph.route(&host.alias);
+    if !route.hops.is_empty() {
+        let mut via = route.hops.join(" \u{2192} ");
+        if route.cycle {
+            via.push_str(" (loops)");
+        }
+        push_field(&mut lines, "Via", &via, max_value_width);
     }
 
-    if !host.identity_file.is_empty() {
-        let key_display = host
-            .identity_file
-            .rsplit('/')
-            .next()
-            .unwrap_or(&host.identity_file);
-        push_field(&mut lines, "Key", key_display, max_value_width);
+    for (label, files) in [("Key", &host.identity_files), ("Cert", &host.certificate_files)] {
+        if !files.is_empty() {
+            let names: Vec<&str> = files
+                .iter()
+                .map(|f| f.rsplit('/').next().unwrap_or(f))
+                .collect();
+            push_field(&mut lines, label, &names.join(", "), max_value_width);
+        }
     }
 
     if let Some(ref askpass) = host.askpass {
         push_field(&mut lines, "Password", askpass, max_value_width);
     }
 
+    // Warnings section (purple lint findings)
+    if let Some(warnings) = app.lint_warnings.get(&host.alias) {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Warnings",
+            theme::section_header(),
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", separator),
+            theme::muted(),
+        )));
+        let width = inner_width.saturating_sub(4);
+        for warning in warnings.iter().take(5) {
+            lines.push(Line::from(vec![
+                Span::styled("  ! ", theme::error()),
+                Span::raw(super::truncate(warning, width)),
+            ]));
+        }
+        if warnings.len() > 5 {
+            lines.push(Line::from(Span::styled(
+                format!("  (and {} more...)", warnings.len() - 5),
+                theme::muted(),
+            )));
+        }
+    }
+
     // Activity section
     let history_entry = app.history.entries.get(&host.alias);
     let ping = app.ping_status.get(&host.alias);
@@ -132,6 +165,23 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
         lines.push(Line::from(tag_spans));
     }
 
+    // Notes section
+    if !host.notes.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("  Notes", theme::section_header())));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", separator),
+            theme::muted(),
+        )));
+        let note_width = inner_width.saturating_sub(2);
+        for note in &host.notes {
+            lines.push(Line::from(Span::raw(format!(
+                "  {}",
+                super::truncate(note, note_width)
+            ))));
+        }
+    }
+
     // Tunnels section
     let tunnel_active = app.active_tunnels.contains_key(&host.alias);
     if host.tunnel_count > 0 {
@@ -167,6 +217,56 @@ pub fn render(frame: &mut Frame, app: &App, area: Rect) {
         }
     }
 
+    // Inherited section (values from Host *, wildcard, Match and Include blocks)
+    let resolved = app.config.resolve_host(&host.alias);
+    let inherited = resolved.inherited();
+    if !inherited.is_empty() || !resolved.unevaluated.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "  Inherited",
+            theme::section_header(),
+        )));
+        lines.push(Line::from(Span::styled(
+            format!("  {}", separator),
+            theme::muted(),
+        )));
+        for v in inherited.iter().take(8) {
+            let header = match &v.source {
+                ValueSource::Block { header, .. } => header.as_str(),
+                ValueSource::Default => "",
+            };
+            let label = super::truncate(&v.key, LABEL_WIDTH - 1);
+            let value = super::truncate(&v.value, max_value_width);
+            let source_width = max_value_width.saturating_sub(value.chars().count() + 1);
+            let mut spans = vec![
+                Span::styled(
+                    format!("  {:<width$}", label, width = LABEL_WIDTH),
+                    theme::muted(),
+                ),
+                Span::styled(value, theme::bold()),
+            ];
+            if source_width > 3 {
+                spans.push(Span::styled(
+                    format!(" {}", super::truncate(header, source_width)),
+                    theme::muted(),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        if inherited.len() > 8 {
+            lines.push(Line::from(Span::styled(
+                format!("  (and {} more...)", inherited.len() - 8),
+                theme::muted(),
+            )));
+        }
+        for header in &resolved.unevaluated {
+            lines.push(Line::from(Span::styled(
+                format!("  {} (skipped)", super::truncate(header, max_value_width)),
+                theme::muted(),
+            )));
+        }
+    }
+
     // Source section (for included hosts)
     if let Some(ref source) = host.source_file {
         lines.push(Line::from(""));
@@ -203,7 +303,7 @@ fn push_field(lines: &mut Vec<Line<'static>>, label: &'static str, value: &str,
 fn find_tunnel_rules(elements: &[ConfigElement], alias: &str) -> Vec<String> {
     for element in elements {
         match element {
-            ConfigElement::HostBlock(block) if block.host_pattern == alias => {
+            ConfigElement::HostBlock(block) if block.is_alias(alias) => {
                 return block
                     .directives
                     .iter()
@@ -0,0 +1,187 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use super::theme;
+use crate::app::App;
+use crate::ssh_config::keywords;
+
+/// Render the host form's advanced directive editor overlay.
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let editing = app.directive_edit.is_some();
+    let row_count = app.form.directives.len().max(1) as u16;
+    let list_height = row_count.min(12);
+    // borders + list + (key input + suggestions + value input) + footer
+    let edit_height = if editing { 7 } else { 0 };
+    let height = (2 + list_height + edit_height + 1).min(frame.area().height);
+    let area = {
+        let r = super::centered_rect(70, 80, frame.area());
+        super::centered_rect_fixed(r.width, height, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Advanced Directives ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(edit_height),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    if app.form.directives.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No directives. Press 'a' to add one.").style(theme::muted()),
+            chunks[0],
+        );
+    } else {
+        let items: Vec<ListItem> = app
+            .form
+            .directives
+            .iter()
+            .map(|(key, value)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!(" {:<26}", key), theme::bold()),
+                    Span::styled(value.clone(), theme::muted()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(theme::selected())
+            .highlight_symbol("  ");
+        frame.render_stateful_widget(list, chunks[0], &mut app.ui.directive_list_state);
+    }
+
+    if editing {
+        render_edit(frame, chunks[1], app);
+    }
+
+    let spans = if editing {
+        vec![
+            Span::styled(" Enter", theme::primary_action()),
+            Span::styled(" save ", theme::muted()),
+            Span::styled("\u{2502} ", theme::muted()),
+            Span::styled("Tab", theme::accent_bold()),
+            Span::styled(" complete / switch  ", theme::muted()),
+            Span::styled("Esc", theme::accent_bold()),
+            Span::styled(" cancel", theme::muted()),
+        ]
+    } else {
+        let mut spans = vec![
+            Span::styled(" a", theme::accent_bold()),
+            Span::styled(" add  ", theme::muted()),
+        ];
+        if !app.form.directives.is_empty() {
+            spans.extend([
+                Span::styled("e", theme::accent_bold()),
+                Span::styled(" edit  ", theme::muted()),
+                Span::styled("d", theme::accent_bold()),
+                Span::styled(" delete  ", theme::muted()),
+                Span::styled("J/K", theme::accent_bold()),
+                Span::styled(" move  ", theme::muted()),
+            ]);
+        }
+        spans.push(Span::styled("Esc", theme::accent_bold()));
+        spans.push(Span::styled(" back", theme::muted()));
+        spans
+    };
+    super::render_footer_with_status(frame, chunks[2], spans, app);
+}
+
+fn render_edit(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(edit) = &app.directive_edit else {
+        return;
+    };
+    let chunks = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(1),
+        Constraint::Length(3),
+    ])
+    .split(area);
+
+    let focus_styles = |focused: bool| {
+        if focused {
+            (theme::border_focused(), theme::accent_bold())
+        } else {
+            (theme::border(), theme::muted())
+        }
+    };
+
+    // Keyword input
+    let (border, label) = focus_styles(!edit.on_value);
+    let key_block = Block::default()
+        .title(Span::styled(" Keyword ", label))
+        .borders(Borders::ALL)
+        .border_style(border);
+    let key_line = if edit.key.is_empty() && edit.on_value {
+        Line::from(Span::styled("ServerAliveInterval", theme::muted()))
+    } else {
+        Line::from(Span::raw(edit.key.as_str()))
+    };
+    frame.render_widget(Paragraph::new(key_line).block(key_block), chunks[0]);
+
+    // Completions while typing the keyword, value hint otherwise
+    let hint = if edit.on_value {
+        keywords::lookup(edit.key.trim())
+            .map(|k| format!("  {}", k.kind.hint()))
+            .unwrap_or_default()
+    } else {
+        let suggestions = edit.suggestions();
+        if suggestions.is_empty() {
+            String::new()
+        } else {
+            let shown: Vec<&str> = suggestions.iter().take(4).copied().collect();
+            let more = suggestions.len().saturating_sub(shown.len());
+            let mut text = format!("  {}", shown.join("  "));
+            if more > 0 {
+                text.push_str(&format!("  (+{})", more));
+            }
+            text
+        }
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::styled(
+            super::truncate(&hint, area.width as usize),
+            theme::muted(),
+        ))),
+        chunks[1],
+    );
+
+    // Value input
+    let (border, label) = focus_styles(edit.on_value);
+    let value_block = Block::default()
+        .title(Span::styled(" Value ", label))
+        .borders(Borders::ALL)
+        .border_style(border);
+    frame.render_widget(
+        Paragraph::new(Line::from(Span::raw(edit.value.as_str()))).block(value_block),
+        chunks[2],
+    );
+
+    let (input_area, value) = if edit.on_value {
+        (chunks[2], edit.value.as_str())
+    } else {
+        (chunks[0], edit.key.as_str())
+    };
+    let prefix: String = value.chars().take(edit.cursor_pos).collect();
+    let cursor_x = input_area
+        .x
+        .saturating_add(1)
+        .saturating_add(prefix.width().min(u16::MAX as usize) as u16);
+    if input_area.width > 1
+        && cursor_x
+            < input_area
+                .x
+                .saturating_add(input_area.width)
+                .saturating_sub(1)
+    {
+        frame.set_cursor_position((cursor_x, input_area.y + 1));
+    }
+}
@@ -0,0 +1,100 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::App;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let rows = app.group_rows();
+    let height = (rows.len() as u16 + 6).min(frame.area().height.saturating_sub(4));
+    let area = super::centered_rect_fixed(60, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = rows
+        .iter()
+        .map(|(group, count)| {
+            let line = match group {
+                None => Line::from(vec![
+                    Span::styled("   (no group)", theme::muted()),
+                    Span::styled(format!(" ({})", count), theme::muted()),
+                ]),
+                Some(name) => {
+                    let marker = if app.collapsed_groups.contains(name) {
+                        " \u{25B8} "
+                    } else {
+                        "   "
+                    };
+                    Line::from(vec![
+                        Span::styled(marker, theme::muted()),
+                        Span::styled(name.clone(), theme::bold()),
+                        Span::styled(format!(" ({})", count), theme::muted()),
+                    ])
+                }
+            };
+            ListItem::new(line)
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(Span::styled(" Groups ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let list = List::new(items)
+        .highlight_style(theme::selected())
+        .highlight_symbol("  ");
+
+    frame.render_stateful_widget(list, chunks[0], &mut app.ui.group_list_state);
+
+    if let Some(ref input) = app.group_input {
+        let label = if input.renaming.is_some() { " Rename: " } else { " New group: " };
+        let line = Line::from(vec![
+            Span::styled(label, theme::accent_bold()),
+            Span::raw(input.name.clone()),
+            Span::styled("_", theme::accent()),
+        ]);
+        frame.render_widget(Paragraph::new(line), chunks[1]);
+        let footer = Line::from(vec![
+            Span::styled(" Enter", theme::primary_action()),
+            Span::styled(" save  ", theme::muted()),
+            Span::styled("Esc", theme::accent_bold()),
+            Span::styled(" cancel", theme::muted()),
+        ]);
+        frame.render_widget(Paragraph::new(footer), chunks[2]);
+        return;
+    }
+
+    let host = app
+        .selected_host()
+        .map(|h| format!(" Enter moves {} here", h.alias))
+        .unwrap_or_default();
+    frame.render_widget(Paragraph::new(Span::styled(host, theme::muted())), chunks[1]);
+
+    let footer = Line::from(vec![
+        Span::styled(" n", theme::accent_bold()),
+        Span::styled(" new  ", theme::muted()),
+        Span::styled("r", theme::accent_bold()),
+        Span::styled(" rename  ", theme::muted()),
+        Span::styled("d", theme::accent_bold()),
+        Span::styled(" delete  ", theme::muted()),
+        Span::styled("s", theme::accent_bold()),
+        Span::styled(" sort  ", theme::muted()),
+        Span::styled("Space", theme::accent_bold()),
+        Span::styled(" fold  ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ]);
+    frame.render_widget(Paragraph::new(footer), chunks[2]);
+}
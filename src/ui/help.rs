@@ -43,18 +43,30 @@ fn help_text() -> Vec<Line<'static>> {
         help_line(" j/k      ", "up / down"),
         help_line(" /        ", "search"),
         help_line(" #        ", "filter by tag"),
+        help_line(" F        ", "saved searches"),
         help_line(" s        ", "cycle sort"),
         help_line(" g        ", "group by provider"),
+        help_line(" z / Z    ", "collapse group / expand all"),
         Line::from(""),
         Line::from(Span::styled(" Manage", theme::section_header())),
         help_line(" Enter    ", "connect"),
         help_line(" a e d c  ", "add / edit / delete / clone"),
         help_line(" t        ", "tag host"),
-        help_line(" u        ", "undo delete"),
+        help_line(" o        ", "groups (move host)"),
+        help_line(" m        ", "move host (J/K, Enter)"),
+        help_line(" D        ", "disable / enable host"),
+        help_line(" Space *  ", "mark host / mark all"),
+        help_line(" u / ^R   ", "undo / redo"),
+        help_line(" U        ", "undo history"),
+        help_line(" B        ", "backups"),
+        help_line(" W        ", "confirm writes on/off"),
         Line::from(""),
         Line::from(Span::styled(" Tools", theme::section_header())),
         help_line(" i        ", "inspect directives"),
         help_line(" T        ", "tunnels"),
+        help_line(" M        ", "match blocks"),
+        help_line(" G        ", "global defaults (Host *)"),
+        help_line(" H        ", "jump hosts"),
         help_line(" S        ", "cloud providers"),
         help_line(" K        ", "SSH keys"),
         help_line(" p / P    ", "ping / ping all"),
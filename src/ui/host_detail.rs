@@ -19,8 +19,11 @@ pub fn render(frame: &mut Frame, app: &App, index: usize) {
     // 2 (border) + 1 (blank) + 1 (header) + 1 (separator) + directives + 1 (overflow) + source + 1 (blank)
     let askpass_lines = if host.askpass.is_some() { 2 } else { 0 };
     let source_lines = if host.source_file.is_some() { 2 } else { 0 };
+    let max_notes = 8;
+    let note_lines = if host.notes.is_empty() { 0 } else { 3 + host.notes.len().min(max_notes) };
     let overflow_line = if directive_count > max_visible { 1 } else { 0 };
-    let height = (6 + visible.max(1) + overflow_line + askpass_lines + source_lines) as u16;
+    let height =
+        (6 + visible.max(1) + overflow_line + askpass_lines + source_lines + note_lines) as u16;
     let width = frame.area().width.clamp(58, 80);
     let area = super::centered_rect_fixed(width, height, frame.area());
 
@@ -66,6 +69,19 @@ pub fn render(frame: &mut Frame, app: &App, index: usize) {
         ]));
     }
 
+    if !host.notes.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("  Notes", theme::section_header())));
+        lines.push(Line::from(Span::styled(
+            "  ────────────────────────",
+            theme::muted(),
+        )));
+        for note in host.notes.iter().take(max_notes) {
+            let note_width = (width as usize).saturating_sub(6);
+            lines.push(Line::from(Span::raw(format!("  {}", super::truncate(note, note_width)))));
+        }
+    }
+
     if let Some(ref source) = host.source_file {
         lines.push(Line::from(""));
         lines.push(Line::from(vec![
@@ -84,7 +100,7 @@ pub fn render(frame: &mut Frame, app: &App, index: usize) {
 fn find_host_directives(elements: &[ConfigElement], alias: &str) -> Vec<(String, String)> {
     for element in elements {
         match element {
-            ConfigElement::HostBlock(block) if block.host_pattern == alias => {
+            ConfigElement::HostBlock(block) if block.is_alias(alias) => {
                 return block
                     .directives
                     .iter()
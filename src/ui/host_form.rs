@@ -30,10 +30,21 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
 
     let title = match &app.screen {
-        Screen::AddHost => " Add New Host ",
-        Screen::EditHost { .. } => " Edit Host ",
-        _ => " Host ",
+        Screen::AddHost => {
+            let from = match &app.form.template {
+                Some(name) => format!(" ({})", name),
+                None => String::new(),
+            };
+            match &app.form.target_file {
+                Some(file) => format!(" Add New Host{} \u{2192} {} ", from, file_label(file)),
+                None => format!(" Add New Host{} ", from),
+            }
+        }
+        Screen::EditHost { .. } => " Edit Host ".to_string(),
+        _ => " Host ".to_string(),
     };
+    let can_pick_file =
+        matches!(app.screen, Screen::AddHost) && app.config.writable_files().len() > 1;
 
     // Center the form
     let form_area = super::centered_rect(70, 80, area);
@@ -61,7 +72,7 @@ pub fn render(frame: &mut Frame, app: &mut App) {
         Constraint::Length(3), // 7: AskPass
         Constraint::Length(1), // 8: Spacer (Security -> Meta)
         Constraint::Length(3), // 9: Tags
-        Constraint::Min(1),   // 10: Spacer
+        Constraint::Min(2),   // 10: Advanced and notes summary
         Constraint::Length(1), // 11: Footer or status
     ])
     .split(inner);
@@ -77,17 +88,35 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     render_field(frame, chunks[7], FormField::AskPass, &app.form);
     // Meta
     render_field(frame, chunks[9], FormField::Tags, &app.form);
+    render_advanced_summary(frame, chunks[10], &app.form);
+    render_notes_summary(frame, chunks[10], &app.form);
 
     // Footer with status right-aligned
-    super::render_footer_with_status(frame, chunks[11], vec![
+    let mut footer = vec![
         Span::styled(" Enter", theme::primary_action()),
         Span::styled(" save ", theme::muted()),
         Span::styled("\u{2502} ", theme::muted()),
         Span::styled("Tab", theme::accent_bold()),
         Span::styled(" next  ", theme::muted()),
-        Span::styled("Esc", theme::accent_bold()),
-        Span::styled(" cancel", theme::muted()),
-    ], app);
+        Span::styled("^A", theme::accent_bold()),
+        Span::styled(" advanced  ", theme::muted()),
+        Span::styled("^K", theme::accent_bold()),
+        Span::styled(" keys  ", theme::muted()),
+        Span::styled("^N", theme::accent_bold()),
+        Span::styled(" notes  ", theme::muted()),
+    ];
+    if can_pick_file {
+        footer.push(Span::styled("^F", theme::accent_bold()));
+        footer.push(Span::styled(" file  ", theme::muted()));
+    }
+    footer.push(Span::styled("Esc", theme::accent_bold()));
+    footer.push(Span::styled(" cancel", theme::muted()));
+    super::render_footer_with_status(frame, chunks[11], footer, app);
+
+    // Key files editor overlay (below the key picker it can open)
+    if app.ui.show_key_files_editor {
+        super::key_files_editor::render(frame, app);
+    }
 
     // Key picker popup overlay
     if app.ui.show_key_picker {
@@ -98,6 +127,126 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     if app.ui.show_password_picker {
         render_password_picker_overlay(frame, app);
     }
+
+    // Target file picker popup overlay
+    if app.ui.show_file_picker {
+        render_file_picker_overlay(frame, app);
+    }
+
+    // Advanced directive editor overlay
+    if app.ui.show_directive_editor {
+        super::directive_editor::render(frame, app);
+    }
+
+    // Note editor overlay
+    if app.note_edit.is_some() {
+        super::note_editor::render(frame, app);
+    }
+}
+
+/// One-line summary of the note below the advanced summary.
+fn render_notes_summary(frame: &mut Frame, area: Rect, form: &crate::app::HostForm) {
+    if area.height < 2 {
+        return;
+    }
+    let area = Rect { y: area.y + 1, height: 1, ..area };
+    let label = Span::styled(" Notes     ", theme::muted());
+    let line = match form.notes.first() {
+        None => Line::from(vec![label, Span::styled("none (^N to write)", theme::muted())]),
+        Some(first) => {
+            let max = (area.width as usize).saturating_sub(12);
+            let more = if form.notes.len() > 1 {
+                format!(" (+{} lines)", form.notes.len() - 1)
+            } else {
+                String::new()
+            };
+            Line::from(vec![
+                label,
+                Span::styled(super::truncate(first, max.saturating_sub(more.len())), theme::bold()),
+                Span::styled(more, theme::muted()),
+            ])
+        }
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// One-line summary of the advanced directives below the Tags field.
+fn render_advanced_summary(frame: &mut Frame, area: Rect, form: &crate::app::HostForm) {
+    if area.height == 0 {
+        return;
+    }
+    let area = Rect { height: 1, ..area };
+    let label = Span::styled(" Advanced  ", theme::muted());
+    let line = if form.directives.is_empty() {
+        Line::from(vec![label, Span::styled("none (^A to add)", theme::muted())])
+    } else {
+        let keys: Vec<&str> = form.directives.iter().map(|(k, _)| k.as_str()).collect();
+        let max = (area.width as usize).saturating_sub(12);
+        Line::from(vec![
+            label,
+            Span::styled(super::truncate(&keys.join(", "), max), theme::bold()),
+        ])
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// Extra key files behind the Identity File field, e.g. " (+1 key, 1 cert)".
+fn key_files_hint(form: &crate::app::HostForm) -> String {
+    let mut parts = Vec::new();
+    if !form.extra_identity_files.is_empty() {
+        let n = form.extra_identity_files.len();
+        parts.push(format!("+{} key{}", n, if n == 1 { "" } else { "s" }));
+    }
+    if !form.certificate_files.is_empty() {
+        let n = form.certificate_files.len();
+        parts.push(format!("{} cert{}", n, if n == 1 { "" } else { "s" }));
+    }
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", parts.join(", "))
+    }
+}
+
+/// Short display label for a config file: `~/`-relative when under home.
+fn file_label(path: &std::path::Path) -> String {
+    if let Some(home) = dirs::home_dir() {
+        if let Ok(rest) = path.strip_prefix(&home) {
+            return format!("~/{}", rest.display());
+        }
+    }
+    path.display().to_string()
+}
+
+fn render_file_picker_overlay(frame: &mut Frame, app: &mut App) {
+    let files = app.config.writable_files();
+    let height = (files.len() as u16 + 2).min(16);
+    let width = frame.area().width.clamp(58, 72);
+    let area = super::centered_rect_fixed(width, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let label_max = (width as usize).saturating_sub(2 + 2 + 1);
+    let items: Vec<ListItem> = files
+        .iter()
+        .map(|path| {
+            ListItem::new(Line::from(Span::styled(
+                format!(" {}", super::truncate(&file_label(path), label_max)),
+                theme::bold(),
+            )))
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(Span::styled(" Write To ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(theme::selected())
+        .highlight_symbol("  ");
+
+    frame.render_stateful_widget(list, area, &mut app.ui.file_picker_state);
 }
 
 /// Render the key picker popup overlay. Public for reuse from provider form.
@@ -240,6 +389,8 @@ fn render_field(frame: &mut Frame, area: Rect, field: FormField, form: &crate::a
     let is_required = matches!(field, FormField::Alias | FormField::Hostname);
     let label = if is_required {
         format!(" {}* ", field.label())
+    } else if field == FormField::IdentityFile {
+        format!(" {}{} ", field.label(), key_files_hint(form))
     } else {
         format!(" {} ", field.label())
     };
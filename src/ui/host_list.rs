@@ -7,6 +7,7 @@ use unicode_width::UnicodeWidthStr;
 
 use super::theme;
 use crate::app::{self, App, HostListItem, PingStatus, SortMode, ViewMode};
+use crate::ssh_config::query::{MatchField, Query, TextMatch, text_match};
 
 /// Minimum terminal width to show the detail panel in detailed view mode.
 const DETAIL_MIN_WIDTH: u16 = 90;
@@ -15,7 +16,7 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     let area = frame.area();
 
     let is_searching = app.search.query.is_some();
-    let is_tagging = app.tag_input.is_some();
+    let is_tagging = app.tag_input.is_some() || app.bulk_input.is_some();
 
     // Layout: host list + optional input bar + footer/status
     let chunks = if is_searching || is_tagging {
@@ -52,11 +53,26 @@ pub fn render(frame: &mut Frame, app: &mut App) {
     if is_searching {
         render_search_list(frame, app, list_area);
         render_search_bar(frame, app, chunks[1]);
-        super::render_footer_with_status(frame, chunks[2], search_footer_spans(), app);
+        let footer = if app.search.save_name.is_some() {
+            save_search_footer_spans()
+        } else {
+            search_footer_spans()
+        };
+        super::render_footer_with_status(frame, chunks[2], footer, app);
+    } else if app.bulk_input.is_some() {
+        render_display_list(frame, app, list_area);
+        render_bulk_bar(frame, app, chunks[1]);
+        super::render_footer_with_status(frame, chunks[2], bulk_footer_spans(app), app);
     } else if is_tagging {
         render_display_list(frame, app, list_area);
         render_tag_bar(frame, app, chunks[1]);
         super::render_footer_with_status(frame, chunks[2], tag_footer_spans(), app);
+    } else if app.mover.is_some() {
+        render_display_list(frame, app, list_area);
+        super::render_footer_with_status(frame, chunks[1], move_footer_spans(), app);
+    } else if !app.marked.is_empty() {
+        render_display_list(frame, app, list_area);
+        super::render_footer_with_status(frame, chunks[1], marked_footer_spans(app.marked.len()), app);
     } else {
         render_display_list(frame, app, list_area);
         super::render_footer_with_status(frame, chunks[1], footer_spans(use_detail), app);
@@ -97,6 +113,15 @@ fn render_display_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::
             }
             spans.push(Span::raw(format!("({}) ", label)));
         }
+        if !app.marked.is_empty() {
+            spans.push(Span::styled(
+                format!("{} marked ", app.marked.len()),
+                theme::accent_bold(),
+            ));
+        }
+        if let Some(ref mover) = app.mover {
+            spans.push(Span::styled(format!("moving {} ", mover.alias), theme::accent_bold()));
+        }
         Line::from(spans)
     };
 
@@ -147,6 +172,7 @@ fn render_display_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::
                         *counts.entry(group).or_insert(0) += 1;
                     }
                 }
+                HostListItem::SavedSearch { .. } => {}
             }
         }
         counts
@@ -156,14 +182,33 @@ fn render_display_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::
         .display_list
         .iter()
         .map(|item| match item {
+            HostListItem::SavedSearch { index, count } => {
+                let name = app
+                    .saved_searches
+                    .entries
+                    .get(*index)
+                    .map_or("", |s| s.name.as_str());
+                let label = format!("{} ({})", name, count);
+                let label_width = label.width() + 5; // "── /" + label + " "
+                let fill = content_width.saturating_sub(label_width);
+                let line = Line::from(vec![
+                    Span::styled("── ", theme::muted()),
+                    Span::styled("/", theme::accent_bold()),
+                    Span::styled(label, theme::section_header()),
+                    Span::styled(format!(" {}", "─".repeat(fill)), theme::muted()),
+                ]);
+                ListItem::new(line)
+            }
             HostListItem::GroupHeader(text) => {
                 let upper = text.to_uppercase();
-                let count = group_counts.get(text.as_str()).copied().unwrap_or(0);
+                let hidden = app.hidden_counts.get(text).copied().unwrap_or(0);
+                let count = group_counts.get(text.as_str()).copied().unwrap_or(0) + hidden;
                 let label = format!("{} ({})", upper, count);
                 let label_width = label.width() + 4; // "── " + label + " "
                 let fill = content_width.saturating_sub(label_width);
+                let lead = if app.collapsed_groups.contains(text) { "\u{25B8}─ " } else { "── " };
                 let line = Line::from(vec![
-                    Span::styled("── ", theme::muted()),
+                    Span::styled(lead, theme::muted()),
                     Span::styled(label, theme::section_header()),
                     Span::styled(format!(" {}", "─".repeat(fill)), theme::muted()),
                 ]);
@@ -171,13 +216,17 @@ fn render_display_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::
             }
             HostListItem::Host { index } => {
                 if let Some(host) = app.hosts.get(*index) {
-                    let tunnel_active = app.active_tunnels.contains_key(&host.alias);
+                    let flags = RowFlags {
+                        tunnel_active: app.active_tunnels.contains_key(&host.alias),
+                        has_warnings: app.lint_warnings.contains_key(&host.alias),
+                        marked: app.marked.contains(&host.alias),
+                    };
                     build_host_item(
                         host,
                         &app.ping_status,
                         &app.history,
-                        tunnel_active,
-                        None,
+                        flags,
+                        &[],
                         alias_col,
                         content_width,
                     )
@@ -237,19 +286,32 @@ fn render_search_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::R
         .clamp(8, 20);
     let content_width = (area.width as usize).saturating_sub(4);
 
-    let query = app.search.query.as_deref();
+    // A query that doesn't parse keeps the old results, without highlights
+    let query = app
+        .search
+        .query
+        .as_deref()
+        .and_then(|q| Query::parse(q).ok())
+        .unwrap_or_default();
+    let terms = query.text_terms();
     let items: Vec<ListItem> = app
         .search.filtered_indices
         .iter()
         .filter_map(|&idx| {
             let host = app.hosts.get(idx)?;
-            let tunnel_active = app.active_tunnels.contains_key(&host.alias);
+            let flags = RowFlags {
+                tunnel_active: app.active_tunnels.contains_key(&host.alias),
+                has_warnings: app.lint_warnings.contains_key(&host.alias),
+                marked: app.marked.contains(&host.alias),
+            };
+            let matches: Vec<TextMatch> =
+                terms.iter().filter_map(|t| text_match(host, t)).collect();
             Some(build_host_item(
                 host,
                 &app.ping_status,
                 &app.history,
-                tunnel_active,
-                query,
+                flags,
+                &matches,
                 alias_col,
                 content_width,
             ))
@@ -269,51 +331,85 @@ fn render_search_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::R
     frame.render_stateful_widget(list, area, &mut app.ui.list_state);
 }
 
+/// Split `text` into spans, styling the characters at `positions` as matches.
+fn highlight<'a>(text: &str, positions: &[usize], base: Style) -> Vec<Span<'a>> {
+    if positions.is_empty() {
+        return vec![Span::styled(text.to_string(), base)];
+    }
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let matched = positions.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let style = if run_matched { theme::highlight_bold() } else { base };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+    if !run.is_empty() {
+        let style = if run_matched { theme::highlight_bold() } else { base };
+        spans.push(Span::styled(run, style));
+    }
+    spans
+}
+
+/// Per-row state that doesn't live on the HostEntry.
+#[derive(Clone, Copy)]
+struct RowFlags {
+    tunnel_active: bool,
+    has_warnings: bool,
+    marked: bool,
+}
+
 fn build_host_item<'a>(
     host: &'a crate::ssh_config::model::HostEntry,
     ping_status: &'a std::collections::HashMap<String, PingStatus>,
     history: &'a crate::history::ConnectionHistory,
-    tunnel_active: bool,
-    query: Option<&str>,
+    flags: RowFlags,
+    matches: &[TextMatch],
     alias_col: usize,
     content_width: usize,
 ) -> ListItem<'a> {
-    let q = query.unwrap_or("");
-
-    // Determine which field matches for search highlighting
-    let alias_matches = !q.is_empty() && app::contains_ci(&host.alias, q);
-    let host_matches = !alias_matches && !q.is_empty() && app::contains_ci(&host.hostname, q);
-    let user_matches =
-        !alias_matches && !host_matches && !q.is_empty() && app::contains_ci(&host.user, q);
+    // Matched character positions per field, for search highlighting
+    let positions = |field: MatchField| -> Vec<usize> {
+        matches
+            .iter()
+            .filter(|m| m.field == field)
+            .flat_map(|m| m.positions.iter().copied())
+            .collect()
+    };
 
     // === LEFT: alias (fixed column) + user@hostname:port ===
-    let alias_style = if alias_matches {
-        theme::highlight_bold()
+    let marker = if flags.marked {
+        Span::styled("*", theme::accent_bold())
     } else {
-        theme::bold()
+        Span::raw(" ")
     };
-    let alias_display = format!(" {:<width$} ", host.alias, width = alias_col);
-    let mut left_len = alias_display.width();
-    let mut left_spans = vec![Span::styled(alias_display, alias_style)];
+    let mut left_len = 1 + host.alias.width().max(alias_col) + 1;
+    let mut left_spans = vec![marker];
+    // Disabled hosts are dimmed
+    let (alias_style, host_style) = if host.disabled {
+        (theme::muted(), theme::muted())
+    } else {
+        (theme::bold(), Style::default())
+    };
+    left_spans.extend(highlight(&host.alias, &positions(MatchField::Alias), alias_style));
+    left_spans.push(Span::raw(format!(
+        "{:<pad$} ",
+        "",
+        pad = alias_col.saturating_sub(host.alias.width())
+    )));
 
     if !host.user.is_empty() {
-        let user_style = if user_matches {
-            theme::highlight_bold()
-        } else {
-            theme::muted()
-        };
         let s = format!("{}@", host.user);
         left_len += s.width();
-        left_spans.push(Span::styled(s, user_style));
+        left_spans.extend(highlight(&s, &positions(MatchField::User), theme::muted()));
     }
 
-    let hostname_style = if host_matches {
-        theme::highlight_bold()
-    } else {
-        Style::default()
-    };
     left_len += host.hostname.width();
-    left_spans.push(Span::styled(host.hostname.as_str(), hostname_style));
+    left_spans.extend(highlight(&host.hostname, &positions(MatchField::Host), host_style));
 
     if host.port != 22 {
         let s = format!(":{}", host.port);
@@ -325,27 +421,20 @@ fn build_host_item<'a>(
     let mut right_spans: Vec<Span> = Vec::new();
     let mut right_len: usize = 0;
 
-    let tag_matches = !q.is_empty() && !alias_matches && !host_matches && !user_matches;
-    for tag in &host.tags {
-        let style = if tag_matches && app::contains_ci(tag, q) {
-            theme::highlight_bold()
-        } else {
-            theme::accent()
-        };
+    // Positions shift by two for the " #" prefix
+    let shifted = |field: MatchField| -> Vec<usize> {
+        positions(field).into_iter().map(|p| p + 2).collect()
+    };
+    for (i, tag) in host.tags.iter().enumerate() {
         let s = format!(" #{}", tag);
         right_len += s.width();
-        right_spans.push(Span::styled(s, style));
+        right_spans.extend(highlight(&s, &shifted(MatchField::Tag(i)), theme::accent()));
     }
 
     if let Some(ref label) = host.provider {
-        let style = if tag_matches && app::contains_ci(label, q) {
-            theme::highlight_bold()
-        } else {
-            theme::accent()
-        };
         let s = format!(" #{}", label);
         right_len += s.width();
-        right_spans.push(Span::styled(s, style));
+        right_spans.extend(highlight(&s, &shifted(MatchField::Provider), theme::accent()));
     }
 
     if let Some(ref source) = host.source_file {
@@ -369,7 +458,7 @@ fn build_host_item<'a>(
 
     // Tunnel indicator
     if host.tunnel_count > 0 {
-        let (indicator, style) = if tunnel_active {
+        let (indicator, style) = if flags.tunnel_active {
             (" [T]", theme::bold())
         } else {
             (" [T]", theme::muted())
@@ -378,6 +467,20 @@ fn build_host_item<'a>(
         right_spans.push(Span::styled(indicator, style));
     }
 
+    // Disabled indicator (block is commented out, ssh skips it)
+    if host.disabled {
+        let indicator = " [off]";
+        right_len += indicator.width();
+        right_spans.push(Span::styled(indicator, theme::muted()));
+    }
+
+    // Lint warning indicator (details in the detail panel / purple lint)
+    if flags.has_warnings {
+        let indicator = " [!]";
+        right_len += indicator.width();
+        right_spans.push(Span::styled(indicator, theme::error()));
+    }
+
     if let Some(status) = ping_status.get(&host.alias) {
         let (indicator, style) = match status {
             PingStatus::Checking => ("[..]", theme::muted()),
@@ -413,6 +516,16 @@ fn build_host_item<'a>(
 
 fn render_search_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let query = app.search.query.as_deref().unwrap_or("");
+    if let Some(ref name) = app.search.save_name {
+        let save_line = Line::from(vec![
+            Span::styled(" save as: ", theme::accent_bold()),
+            Span::raw(name.as_str()),
+            Span::styled("_", theme::accent()),
+            Span::styled(format!("  /{}", query), theme::muted()),
+        ]);
+        frame.render_widget(Paragraph::new(save_line), area);
+        return;
+    }
     let match_info = if query.is_empty() {
         String::new()
     } else {
@@ -423,12 +536,16 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect)
             n => format!(" ({} matches)", n),
         }
     };
-    let search_line = Line::from(vec![
+    let mut spans = vec![
         Span::styled(" / ", theme::accent_bold()),
         Span::raw(query),
         Span::styled("_", theme::accent()),
-        Span::styled(match_info, theme::muted()),
-    ]);
+    ];
+    match &app.search.error {
+        Some(error) => spans.push(Span::styled(format!("  {}", error), theme::error())),
+        None => spans.push(Span::styled(match_info, theme::muted())),
+    }
+    let search_line = Line::from(spans);
     frame.render_widget(Paragraph::new(search_line), area);
 }
 
@@ -462,11 +579,23 @@ fn search_footer_spans<'a>() -> Vec<Span<'a>> {
         Span::styled(" Enter", theme::primary_action()),
         Span::styled(" connect ", theme::muted()),
         Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("^S", theme::accent_bold()),
+        Span::styled(" save ", theme::muted()),
         Span::styled("Esc", theme::accent_bold()),
         Span::styled(" cancel", theme::muted()),
     ]
 }
 
+fn save_search_footer_spans<'a>() -> Vec<Span<'a>> {
+    vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" save search ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ]
+}
+
 fn render_tag_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     let input = app.tag_input.as_deref().unwrap_or("");
     let tag_line = Line::from(vec![
@@ -477,6 +606,76 @@ fn render_tag_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
     frame.render_widget(Paragraph::new(tag_line), area);
 }
 
+fn marked_footer_spans(count: usize) -> Vec<Span<'static>> {
+    vec![
+        Span::styled(format!(" {} marked ", count), theme::accent_bold()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("d", theme::accent_bold()),
+        Span::styled(" del ", theme::muted()),
+        Span::styled("t", theme::accent_bold()),
+        Span::styled(" tag ", theme::muted()),
+        Span::styled("e", theme::accent_bold()),
+        Span::styled(" set ", theme::muted()),
+        Span::styled("p", theme::accent_bold()),
+        Span::styled(" ping ", theme::muted()),
+        Span::styled("T", theme::accent_bold()),
+        Span::styled(" tunnels ", theme::muted()),
+        Span::styled("y/x", theme::accent_bold()),
+        Span::styled(" copy/export ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" clear", theme::muted()),
+    ]
+}
+
+fn move_footer_spans() -> Vec<Span<'static>> {
+    vec![
+        Span::styled(" J/K", theme::accent_bold()),
+        Span::styled(" move ", theme::muted()),
+        Span::styled("g/G", theme::accent_bold()),
+        Span::styled(" top/bottom of group ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Enter", theme::primary_action()),
+        Span::styled(" drop ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" cancel", theme::muted()),
+    ]
+}
+
+fn render_bulk_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+    let Some(input) = app.bulk_input.as_ref() else {
+        return;
+    };
+    let line = Line::from(vec![
+        Span::styled(
+            format!(" {} ({} hosts): ", input.field.label(), app.marked.len()),
+            theme::accent_bold(),
+        ),
+        Span::raw(input.value.as_str()),
+        Span::styled("_", theme::accent()),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn bulk_footer_spans(app: &App) -> Vec<Span<'static>> {
+    let hint = match app.bulk_input.as_ref().map(|i| i.field) {
+        Some(app::BulkField::Tags) => "tag adds, -tag removes",
+        _ => "empty clears",
+    };
+    let mut spans = vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" apply  ", theme::muted()),
+    ];
+    if !matches!(app.bulk_input.as_ref().map(|i| i.field), Some(app::BulkField::Tags)) {
+        spans.push(Span::styled("Tab", theme::accent_bold()));
+        spans.push(Span::styled(" field  ", theme::muted()));
+    }
+    spans.push(Span::styled("Esc", theme::accent_bold()));
+    spans.push(Span::styled(" cancel  ", theme::muted()));
+    spans.push(Span::styled(hint, theme::muted()));
+    spans
+}
+
 fn tag_footer_spans<'a>() -> Vec<Span<'a>> {
     vec![
         Span::styled(" Enter", theme::primary_action()),
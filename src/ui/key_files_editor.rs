@@ -0,0 +1,120 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use super::theme;
+use crate::app::App;
+
+/// Render the host form's key files editor overlay.
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let rows = app.form.key_rows();
+    let editing = app.key_file_edit.is_some();
+    let list_height = (rows.len().max(1) as u16).min(12);
+    // borders + list + path input + footer
+    let edit_height = if editing { 3 } else { 0 };
+    let height = (2 + list_height + edit_height + 1).min(frame.area().height);
+    let area = {
+        let r = super::centered_rect(70, 80, frame.area());
+        super::centered_rect_fixed(r.width, height, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Key Files ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(edit_height),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    if rows.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No key files. Press 'a' to pick a key.").style(theme::muted()),
+            chunks[0],
+        );
+    } else {
+        let items: Vec<ListItem> = rows
+            .iter()
+            .map(|(kind, path)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!(" {:<17}", kind.directive()), theme::bold()),
+                    Span::styled(path.clone(), theme::muted()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(theme::selected())
+            .highlight_symbol("  ");
+        frame.render_stateful_widget(list, chunks[0], &mut app.ui.key_files_list_state);
+    }
+
+    if editing {
+        render_edit(frame, chunks[1], app);
+    }
+
+    let spans = if editing {
+        vec![
+            Span::styled(" Enter", theme::primary_action()),
+            Span::styled(" save ", theme::muted()),
+            Span::styled("\u{2502} ", theme::muted()),
+            Span::styled("Esc", theme::accent_bold()),
+            Span::styled(" cancel", theme::muted()),
+        ]
+    } else {
+        let mut spans = vec![
+            Span::styled(" a", theme::accent_bold()),
+            Span::styled(" pick key  ", theme::muted()),
+            Span::styled("i", theme::accent_bold()),
+            Span::styled(" identity  ", theme::muted()),
+            Span::styled("c", theme::accent_bold()),
+            Span::styled(" cert  ", theme::muted()),
+        ];
+        if !rows.is_empty() {
+            spans.extend([
+                Span::styled("e", theme::accent_bold()),
+                Span::styled(" edit  ", theme::muted()),
+                Span::styled("d", theme::accent_bold()),
+                Span::styled(" delete  ", theme::muted()),
+                Span::styled("J/K", theme::accent_bold()),
+                Span::styled(" move  ", theme::muted()),
+            ]);
+        }
+        spans.push(Span::styled("Esc", theme::accent_bold()));
+        spans.push(Span::styled(" back", theme::muted()));
+        spans
+    };
+    super::render_footer_with_status(frame, chunks[2], spans, app);
+}
+
+fn render_edit(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(edit) = &app.key_file_edit else {
+        return;
+    };
+    let block = Block::default()
+        .title(Span::styled(format!(" {} ", edit.kind.directive()), theme::accent_bold()))
+        .borders(Borders::ALL)
+        .border_style(theme::border_focused());
+    let line = if edit.path.is_empty() {
+        Line::from(Span::styled("~/.ssh/id_ed25519", theme::muted()))
+    } else {
+        Line::from(Span::raw(edit.path.as_str()))
+    };
+    frame.render_widget(Paragraph::new(line).block(block), area);
+
+    let prefix: String = edit.path.chars().take(edit.cursor_pos).collect();
+    let cursor_x = area
+        .x
+        .saturating_add(1)
+        .saturating_add(prefix.width().min(u16::MAX as usize) as u16);
+    if area.width > 1 && cursor_x < area.x.saturating_add(area.width).saturating_sub(1) {
+        frame.set_cursor_position((cursor_x, area.y + 1));
+    }
+}
@@ -0,0 +1,147 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use super::theme;
+use crate::app::{App, Screen};
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let area = frame.area();
+
+    let title = match &app.screen {
+        Screen::MatchForm { editing: Some(_) } => " Edit Match Block ",
+        _ => " Add Match Block ",
+    };
+
+    let form = &app.match_form;
+
+    // Overlay: percentage-based width, height fits the directive rows
+    let rows_height = (form.rows.len() as u16 + 2).min(area.height.saturating_sub(12).max(3));
+    let height = 3 + rows_height + 3;
+    let form_area = super::centered_rect(70, 80, area);
+    let form_area = super::centered_rect_fixed(form_area.width, height, area);
+
+    frame.render_widget(Clear, form_area);
+
+    let outer_block = Block::default()
+        .title(Span::styled(title, theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::border());
+
+    let inner = outer_block.inner(form_area);
+    frame.render_widget(outer_block, form_area);
+
+    let chunks = Layout::vertical([
+        Constraint::Length(3),           // Criteria
+        Constraint::Length(rows_height), // Directives
+        Constraint::Min(0),              // Spacer
+        Constraint::Length(1),           // Footer
+    ])
+    .split(inner);
+
+    render_criteria(frame, chunks[0], app);
+    render_rows(frame, chunks[1], app);
+
+    super::render_footer_with_status(frame, chunks[3], vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" save ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Tab", theme::accent_bold()),
+        Span::styled(" next ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" cancel", theme::muted()),
+    ], app);
+}
+
+fn render_criteria(frame: &mut Frame, area: Rect, app: &App) {
+    let form = &app.match_form;
+    let is_focused = form.focused == 0;
+
+    let (border_style, label_style) = if is_focused {
+        (theme::border_focused(), theme::accent_bold())
+    } else {
+        (theme::border(), theme::muted())
+    };
+
+    let block = Block::default()
+        .title(Span::styled(" Criteria* ", label_style))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    let display: Span = if form.criteria.is_empty() && !is_focused {
+        Span::styled("host *.example.com", theme::muted())
+    } else {
+        Span::raw(form.criteria.as_str())
+    };
+
+    frame.render_widget(Paragraph::new(display).block(block), area);
+
+    if is_focused {
+        set_cursor(frame, area, 0, &form.criteria, form.cursor_pos);
+    }
+}
+
+fn render_rows(frame: &mut Frame, area: Rect, app: &App) {
+    let form = &app.match_form;
+    let is_focused = form.focused > 0;
+
+    let (border_style, label_style) = if is_focused {
+        (theme::border_focused(), theme::accent_bold())
+    } else {
+        (theme::border(), theme::muted())
+    };
+
+    let block = Block::default()
+        .title(Span::styled(" Directives ", label_style))
+        .borders(Borders::ALL)
+        .border_style(border_style);
+
+    // Keep the focused row visible when there are more rows than space
+    let visible = area.height.saturating_sub(2) as usize;
+    let focused_row = form.focused.saturating_sub(1);
+    let offset = if is_focused && focused_row >= visible {
+        focused_row + 1 - visible
+    } else {
+        0
+    };
+
+    let lines: Vec<Line> = form
+        .rows
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+        .map(|(i, row)| {
+            if row.is_empty() && form.focused != i + 1 {
+                Line::from(Span::styled("Key Value", theme::muted()))
+            } else if form.focused == i + 1 {
+                Line::from(Span::styled(row.as_str(), theme::bold()))
+            } else {
+                Line::from(Span::raw(row.as_str()))
+            }
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+
+    if is_focused {
+        if let Some(row) = form.rows.get(focused_row) {
+            set_cursor(frame, area, (focused_row - offset) as u16, row, form.cursor_pos);
+        }
+    }
+}
+
+fn set_cursor(frame: &mut Frame, area: Rect, line: u16, value: &str, cursor_pos: usize) {
+    let prefix: String = value.chars().take(cursor_pos).collect();
+    let cursor_x = area
+        .x
+        .saturating_add(1)
+        .saturating_add(UnicodeWidthStr::width(prefix.as_str()).min(u16::MAX as usize) as u16);
+    let cursor_y = area.y + 1 + line;
+    if area.width > 1 && cursor_x < area.x.saturating_add(area.width).saturating_sub(1) {
+        frame.set_cursor_position((cursor_x, cursor_y));
+    }
+}
@@ -0,0 +1,99 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::App;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let blocks = app.config.match_blocks();
+
+    // Overlay: percentage-based width, height fits content
+    let item_count = blocks.len().max(1);
+    let height = (item_count as u16 + 5).min(frame.area().height.saturating_sub(4));
+    let area = {
+        let r = super::centered_rect(70, 80, frame.area());
+        super::centered_rect_fixed(r.width, height, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Match Blocks ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let is_empty = blocks.is_empty();
+    if is_empty {
+        frame.render_widget(
+            Paragraph::new("  No Match blocks. Press 'a' to add one.").style(theme::muted()),
+            chunks[0],
+        );
+    } else {
+        let items: Vec<ListItem> = blocks
+            .iter()
+            .map(|b| {
+                let mut spans = vec![Span::raw(" ")];
+                for (i, c) in b.criteria.iter().enumerate() {
+                    if i > 0 {
+                        spans.push(Span::raw(" "));
+                    }
+                    let keyword = if c.negated {
+                        format!("!{}", c.kind.keyword())
+                    } else {
+                        c.kind.keyword().to_string()
+                    };
+                    spans.push(Span::styled(keyword, theme::bold()));
+                    if let Some(ref arg) = c.arg {
+                        if arg.contains(char::is_whitespace) {
+                            spans.push(Span::raw(format!(" \"{}\"", arg)));
+                        } else {
+                            spans.push(Span::raw(format!(" {}", arg)));
+                        }
+                    }
+                }
+                let count = b.directive_pairs().len();
+                let label = if count == 1 {
+                    "  1 directive".to_string()
+                } else {
+                    format!("  {} directives", count)
+                };
+                spans.push(Span::styled(label, theme::muted()));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(theme::selected())
+            .highlight_symbol("  ");
+
+        frame.render_stateful_widget(list, chunks[0], &mut app.ui.match_list_state);
+    }
+
+    // Footer
+    let mut spans: Vec<Span<'_>> = Vec::new();
+    if !is_empty {
+        spans.push(Span::styled(" Enter", theme::primary_action()));
+        spans.push(Span::styled(" edit  ", theme::muted()));
+    } else {
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::styled("a", theme::accent_bold()));
+    spans.push(Span::styled(" add  ", theme::muted()));
+    if !is_empty {
+        spans.push(Span::styled("d", theme::accent_bold()));
+        spans.push(Span::styled(" delete  ", theme::muted()));
+    }
+    spans.push(Span::styled("Esc", theme::accent_bold()));
+    spans.push(Span::styled(" back", theme::muted()));
+    super::render_footer_with_status(frame, chunks[1], spans, app);
+}
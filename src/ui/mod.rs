@@ -1,16 +1,29 @@
+mod backup_list;
 mod confirm_dialog;
+mod defaults;
 mod detail_panel;
+mod directive_editor;
+mod groups;
 mod help;
 mod host_detail;
 pub mod host_form;
 mod host_list;
 mod key_detail;
+mod key_files_editor;
 mod key_list;
+mod match_form;
+mod match_list;
+mod note_editor;
 mod provider_list;
+mod saved_searches;
 mod tag_picker;
+mod template_picker;
 pub mod theme;
 mod tunnel_form;
+mod topology;
 mod tunnel_list;
+mod undo_history;
+mod write_preview;
 
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Layout, Rect};
@@ -47,6 +60,10 @@ pub fn render(frame: &mut Frame, app: &mut App) {
             host_list::render(frame, app);
             confirm_dialog::render(frame, app, &alias);
         }
+        Screen::ConfirmBulkDelete => {
+            host_list::render(frame, app);
+            confirm_dialog::render_bulk_delete(frame, app.marked.len());
+        }
         Screen::Help => {
             host_list::render(frame, app);
             help::render(frame);
@@ -70,6 +87,14 @@ pub fn render(frame: &mut Frame, app: &mut App) {
             host_list::render(frame, app);
             tag_picker::render(frame, app);
         }
+        Screen::SavedSearches => {
+            host_list::render(frame, app);
+            saved_searches::render(frame, app);
+        }
+        Screen::TemplatePicker => {
+            host_list::render(frame, app);
+            template_picker::render(frame, app);
+        }
         Screen::Providers => {
             host_list::render(frame, app);
             provider_list::render_provider_list(frame, app);
@@ -90,6 +115,39 @@ pub fn render(frame: &mut Frame, app: &mut App) {
             tunnel_list::render(frame, app, &alias);
             tunnel_form::render(frame, app);
         }
+        Screen::MatchList => {
+            host_list::render(frame, app);
+            match_list::render(frame, app);
+        }
+        Screen::MatchForm { .. } => {
+            host_list::render(frame, app);
+            match_list::render(frame, app);
+            match_form::render(frame, app);
+        }
+        Screen::Backups => {
+            host_list::render(frame, app);
+            backup_list::render(frame, app);
+        }
+        Screen::Topology => {
+            host_list::render(frame, app);
+            topology::render(frame, app);
+        }
+        Screen::Defaults => {
+            host_list::render(frame, app);
+            defaults::render(frame, app);
+        }
+        Screen::Groups => {
+            host_list::render(frame, app);
+            groups::render(frame, app);
+        }
+        Screen::UndoHistory => {
+            host_list::render(frame, app);
+            undo_history::render(frame, app);
+        }
+        Screen::ConfirmWrite => {
+            host_list::render(frame, app);
+            write_preview::render(frame, app);
+        }
         Screen::ConfirmHostKeyReset { hostname, .. } => {
             let hostname = hostname.clone();
             host_list::render(frame, app);
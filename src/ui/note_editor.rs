@@ -0,0 +1,72 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use unicode_width::UnicodeWidthStr;
+
+use super::theme;
+use crate::app::App;
+
+/// Render the host form's multi-line note editor overlay.
+pub fn render(frame: &mut Frame, app: &App) {
+    let Some(edit) = &app.note_edit else {
+        return;
+    };
+    let text_height = (edit.lines.len() as u16).clamp(6, 16);
+    // borders + text + footer
+    let height = (2 + text_height + 1).min(frame.area().height);
+    let area = {
+        let r = super::centered_rect(70, 80, frame.area());
+        super::centered_rect_fixed(r.width, height, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Notes ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+    let text_area = chunks[0];
+
+    // Keep the cursor row on screen
+    let visible = text_area.height as usize;
+    let offset = (edit.row + 1).saturating_sub(visible);
+
+    let empty = edit.lines.len() == 1 && edit.lines[0].is_empty();
+    let lines: Vec<Line> = if empty {
+        vec![Line::from(Span::styled(
+            "Runbook links, owner, maintenance window...",
+            theme::muted(),
+        ))]
+    } else {
+        edit.lines
+            .iter()
+            .skip(offset)
+            .map(|l| Line::from(Span::raw(l.as_str())))
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines), text_area);
+
+    let prefix: String = edit.lines[edit.row].chars().take(edit.col).collect();
+    let cursor_x = text_area
+        .x
+        .saturating_add(prefix.width().min(u16::MAX as usize) as u16);
+    let cursor_y = text_area.y + (edit.row - offset) as u16;
+    if cursor_x < text_area.x.saturating_add(text_area.width) {
+        frame.set_cursor_position((cursor_x, cursor_y));
+    }
+
+    let spans = vec![
+        Span::styled(" ^S", theme::primary_action()),
+        Span::styled(" save ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Enter", theme::accent_bold()),
+        Span::styled(" new line  ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" cancel", theme::muted()),
+    ];
+    super::render_footer_with_status(frame, chunks[1], spans, app);
+}
@@ -0,0 +1,86 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::{App, HostListItem};
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    if app.saved_searches.entries.is_empty() {
+        let area = super::centered_rect_fixed(56, 5, frame.area());
+        frame.render_widget(Clear, area);
+        let block = Block::default()
+            .title(Span::styled(" Saved Searches ", theme::brand()))
+            .borders(Borders::ALL)
+            .border_style(theme::accent());
+        let msg = Paragraph::new(Line::from(Span::styled(
+            "  No saved searches. Search with / and press Ctrl+S.",
+            theme::muted(),
+        )))
+        .block(block);
+        frame.render_widget(msg, area);
+        return;
+    }
+
+    // Member counts come from the virtual group rows
+    let counts: std::collections::HashMap<usize, usize> = app
+        .display_list
+        .iter()
+        .filter_map(|item| match item {
+            HostListItem::SavedSearch { index, count } => Some((*index, *count)),
+            _ => None,
+        })
+        .collect();
+
+    let height =
+        (app.saved_searches.entries.len() as u16 + 5).min(frame.area().height.saturating_sub(4));
+    let area = super::centered_rect_fixed(60, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    let items: Vec<ListItem> = app
+        .saved_searches
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(i, search)| {
+            let count = counts.get(&i).copied().unwrap_or(0);
+            let line = Line::from(vec![
+                Span::styled(format!(" {}", search.name), theme::bold()),
+                Span::styled(format!(" ({})", count), theme::muted()),
+                Span::styled(format!("  /{}", search.query), theme::muted()),
+            ]);
+            ListItem::new(line)
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(Span::styled(" Saved Searches ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let list = List::new(items)
+        .highlight_style(theme::selected())
+        .highlight_symbol("  ");
+
+    frame.render_stateful_widget(list, chunks[0], &mut app.ui.saved_search_state);
+
+    let footer = Line::from(vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" select  ", theme::muted()),
+        Span::styled("d", theme::accent_bold()),
+        Span::styled(" delete  ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ]);
+    frame.render_widget(Paragraph::new(footer), chunks[1]);
+}
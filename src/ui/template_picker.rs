@@ -0,0 +1,73 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::App;
+use crate::ssh_config::template::TemplateSource;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let rows = app.templates.len() + 1;
+    let height = (rows as u16 + 3).min(frame.area().height.saturating_sub(4));
+    let area = super::centered_rect_fixed(60, height, frame.area());
+    frame.render_widget(Clear, area);
+
+    // Summary width: 60 - borders(2) - highlight(2) - lead(1) - name(16)
+    let summary_max = 60 - 2 - 2 - 1 - 16;
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::styled(format!(" {:<16}", "Blank host"), theme::bold()),
+        Span::styled("start from scratch", theme::muted()),
+    ]))];
+    items.extend(app.templates.iter().map(|template| {
+        let entry = template.entry();
+        let mut parts: Vec<String> = Vec::new();
+        if !entry.user.is_empty() {
+            parts.push(entry.user.clone());
+        }
+        if entry.port != 22 {
+            parts.push(format!(":{}", entry.port));
+        }
+        if !entry.proxy_jump.is_empty() {
+            parts.push(format!("via {}", entry.proxy_jump));
+        }
+        for tag in &entry.tags {
+            parts.push(format!("#{}", tag));
+        }
+        if template.source == TemplateSource::File {
+            parts.push("(templates)".to_string());
+        }
+        ListItem::new(Line::from(vec![
+            Span::styled(format!(" {:<16}", template.name), theme::bold()),
+            Span::styled(super::truncate(&parts.join(" "), summary_max), theme::muted()),
+        ]))
+    }));
+
+    let block = Block::default()
+        .title(Span::styled(" New Host From ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let list = List::new(items)
+        .highlight_style(theme::selected())
+        .highlight_symbol("  ");
+
+    frame.render_stateful_widget(list, chunks[0], &mut app.ui.template_picker_state);
+
+    let footer = Line::from(vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" select  ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ]);
+    frame.render_widget(Paragraph::new(footer), chunks[1]);
+}
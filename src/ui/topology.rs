@@ -0,0 +1,74 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph};
+
+use super::theme;
+use crate::app::App;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let area = super::centered_rect(60, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(Span::styled(" Jump Hosts ", theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+    if app.topology.is_empty() {
+        frame.render_widget(
+            Paragraph::new("  No hosts yet.").style(theme::muted()),
+            chunks[0],
+        );
+    } else {
+        let width = (chunks[0].width as usize).saturating_sub(4);
+        let items: Vec<ListItem> = app
+            .topology
+            .iter()
+            .map(|node| {
+                let branch = if node.depth == 0 {
+                    " ".to_string()
+                } else {
+                    format!(" {}\u{2514} ", "  ".repeat(node.depth - 1))
+                };
+                let name_style = if node.host { theme::bold() } else { theme::muted() };
+                let mut spans = vec![
+                    Span::styled(branch.clone(), theme::muted()),
+                    Span::styled(
+                        super::truncate(&node.name, width.saturating_sub(branch.len())),
+                        name_style,
+                    ),
+                ];
+                if node.behind > 0 {
+                    spans.push(Span::styled(
+                        format!("  {} behind", node.behind),
+                        theme::muted(),
+                    ));
+                }
+                if node.cycle {
+                    spans.push(Span::styled("  loops", theme::error()));
+                } else if !node.host && app.jump_graph.is_dangling(&node.name) {
+                    spans.push(Span::styled("  not in config", theme::error()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(theme::selected())
+            .highlight_symbol("  ");
+        frame.render_stateful_widget(list, chunks[0], &mut app.ui.topology_state);
+    }
+
+    let spans = vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" select ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ];
+    super::render_footer_with_status(frame, chunks[1], spans, app);
+}
@@ -8,10 +8,6 @@ use crate::app::App;
 
 pub fn render(frame: &mut Frame, app: &mut App, alias: &str) {
     let is_active = app.active_tunnels.contains_key(alias);
-    let is_readonly = app
-        .hosts
-        .iter()
-        .any(|h| h.alias == alias && h.source_file.is_some());
 
     // Title
     let mut title_spans = vec![
@@ -46,13 +42,8 @@ pub fn render(frame: &mut Frame, app: &mut App, alias: &str) {
     .split(inner);
 
     if app.tunnel_list.is_empty() {
-        let msg = if is_readonly {
-            "  Read-only (included file)."
-        } else {
-            "  No tunnels. Press 'a' to add one."
-        };
         frame.render_widget(
-            Paragraph::new(msg).style(theme::muted()),
+            Paragraph::new("  No tunnels. Press 'a' to add one.").style(theme::muted()),
             chunks[0],
         );
     } else {
@@ -104,15 +95,13 @@ pub fn render(frame: &mut Frame, app: &mut App, alias: &str) {
         spans.push(Span::styled(" Enter", theme::primary_action()));
         spans.push(Span::styled(" start  ", theme::muted()));
     }
-    if !is_readonly {
-        spans.push(Span::styled("a", theme::accent_bold()));
-        spans.push(Span::styled(" add  ", theme::muted()));
-        if !app.tunnel_list.is_empty() {
-            spans.push(Span::styled("e", theme::accent_bold()));
-            spans.push(Span::styled(" edit  ", theme::muted()));
-            spans.push(Span::styled("d", theme::accent_bold()));
-            spans.push(Span::styled(" delete  ", theme::muted()));
-        }
+    spans.push(Span::styled("a", theme::accent_bold()));
+    spans.push(Span::styled(" add  ", theme::muted()));
+    if !app.tunnel_list.is_empty() {
+        spans.push(Span::styled("e", theme::accent_bold()));
+        spans.push(Span::styled(" edit  ", theme::muted()));
+        spans.push(Span::styled("d", theme::accent_bold()));
+        spans.push(Span::styled(" delete  ", theme::muted()));
     }
     spans.push(Span::styled("Esc", theme::accent_bold()));
     spans.push(Span::styled(" back", theme::muted()));
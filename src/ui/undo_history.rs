@@ -0,0 +1,66 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem};
+
+use super::theme;
+use crate::app::App;
+use crate::history::ConnectionHistory;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let item_count = app.journal.undo_len().max(1);
+    let height = (item_count as u16 + 3).min(frame.area().height.saturating_sub(4));
+    let area = {
+        let r = super::centered_rect(70, 80, frame.area());
+        super::centered_rect_fixed(r.width, height, frame.area())
+    };
+    frame.render_widget(Clear, area);
+
+    let title = match app.journal.redo_len() {
+        0 => " Undo History ".to_string(),
+        n => format!(" Undo History ({} to redo) ", n),
+    };
+    let block = Block::default()
+        .title(Span::styled(title, theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let label_width = (inner.width as usize).saturating_sub(16);
+    let items: Vec<ListItem> = app
+        .journal
+        .undo_entries()
+        .map(|entry| {
+            let ago = ConnectionHistory::format_time_ago(entry.at);
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!(" {:<width$}", super::truncate(&entry.label, label_width), width = label_width),
+                    theme::bold(),
+                ),
+                Span::styled(format!(" {}", ago), theme::muted()),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .highlight_style(theme::selected())
+        .highlight_symbol("  ");
+    frame.render_stateful_widget(list, chunks[0], &mut app.ui.undo_list_state);
+
+    let spans = vec![
+        Span::styled(" Enter", theme::primary_action()),
+        Span::styled(" roll back to here ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" back", theme::muted()),
+    ];
+    super::render_footer_with_status(frame, chunks[1], spans, app);
+}
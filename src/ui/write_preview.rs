@@ -0,0 +1,77 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use super::theme;
+use crate::app::App;
+
+pub fn render(frame: &mut Frame, app: &mut App) {
+    let Some(pending) = app.pending_write.as_ref() else {
+        return;
+    };
+    let area = super::centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, area);
+
+    let title = format!(" Review: {} ", super::truncate(&pending.label, 40));
+    let block = Block::default()
+        .title(Span::styled(title, theme::brand()))
+        .borders(Borders::ALL)
+        .border_style(theme::accent());
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    let width = (inner.width as usize).saturating_sub(2);
+    let lines: Vec<Line> = if pending.diff.is_empty() {
+        vec![Line::from(Span::styled(" No changes to write.", theme::muted()))]
+    } else {
+        pending
+            .diff
+            .iter()
+            .map(|line| {
+                let style = if line.starts_with("---") || line.starts_with("+++") {
+                    theme::bold()
+                } else if line.starts_with("@@") {
+                    theme::muted()
+                } else if line.starts_with('+') {
+                    theme::success()
+                } else if line.starts_with('-') {
+                    theme::error()
+                } else {
+                    theme::muted()
+                };
+                Line::from(Span::styled(format!(" {}", super::truncate(line, width)), style))
+            })
+            .collect()
+    };
+    let max_scroll = (lines.len() as u16).saturating_sub(chunks[0].height);
+    app.ui.write_preview_scroll = app.ui.write_preview_scroll.min(max_scroll);
+    frame.render_widget(
+        Paragraph::new(lines).scroll((app.ui.write_preview_scroll, 0)),
+        chunks[0],
+    );
+
+    let mut spans = vec![
+        Span::styled(" y", theme::primary_action()),
+        Span::styled(" save ", theme::muted()),
+        Span::styled("\u{2502} ", theme::muted()),
+        Span::styled("j/k", theme::accent_bold()),
+        Span::styled(" scroll  ", theme::muted()),
+        Span::styled("Esc", theme::accent_bold()),
+        Span::styled(" cancel", theme::muted()),
+    ];
+    if !app.queued_writes.is_empty() {
+        spans.push(Span::styled(
+            format!("  ({} more waiting)", app.queued_writes.len()),
+            theme::muted(),
+        ));
+    }
+    super::render_footer_with_status(frame, chunks[1], spans, app);
+}
@@ -0,0 +1,511 @@
+//! Config file watcher. Uses inotify on Linux and falls back to mtime polling
+//! elsewhere (or when inotify can't be set up). Changes are reported as
+//! `AppEvent::ConfigChanged` on the main event channel.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::event::AppEvent;
+use crate::ssh_config::model::SshConfigFile;
+
+/// How long a path has to stay quiet before its change is reported. Editors
+/// save through a temp file and a rename, which shows up as a burst of events.
+const DEBOUNCE: Duration = Duration::from_millis(150);
+/// How often the polling fallback stats the watched files.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Files and Include globs to watch for one config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchSet {
+    /// The main config file.
+    pub config: PathBuf,
+    /// Resolved Include files.
+    pub includes: Vec<PathBuf>,
+    /// Absolute Include globs. New files matching these are reported too.
+    pub globs: Vec<PathBuf>,
+}
+
+impl WatchSet {
+    pub fn for_config(config: &SshConfigFile) -> Self {
+        Self {
+            config: config.path.clone(),
+            includes: config.include_paths(),
+            globs: config.include_globs(),
+        }
+    }
+
+    fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&self.config).chain(&self.includes)
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        self.files().any(|f| f == path)
+    }
+
+    /// Whether `path` would be picked up by one of the Include globs.
+    fn matches_glob(&self, path: &Path) -> bool {
+        self.globs.iter().any(|glob| {
+            glob::Pattern::new(&glob.to_string_lossy()).is_ok_and(|p| p.matches_path(path))
+        })
+    }
+
+    /// Directories to watch: the parent of every file and every glob. Watching
+    /// the directory instead of the file survives editors replacing the file.
+    fn dirs(&self) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for path in self.files().chain(&self.globs) {
+            if let Some(parent) = path.parent() {
+                if !dirs.iter().any(|d| d == parent) {
+                    dirs.push(parent.to_path_buf());
+                }
+            }
+        }
+        dirs
+    }
+}
+
+/// Messages to the watcher thread.
+enum Message {
+    /// Replace the watched set.
+    Watch(WatchSet),
+    /// The backend has events to collect.
+    Wake,
+    /// The handle was dropped.
+    Stop,
+}
+
+/// Handle to the watcher thread. Dropping it stops the thread.
+pub struct ConfigWatcher {
+    tx: mpsc::Sender<Message>,
+}
+
+impl ConfigWatcher {
+    /// Start watching `set`, reporting changes on `events`.
+    pub fn spawn(set: WatchSet, events: mpsc::Sender<AppEvent>) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let backend = backend(tx.clone());
+        thread::spawn(move || run(set, backend, rx, events));
+        Self { tx }
+    }
+
+    /// Replace the watched set (call after a reload, when Includes may have changed).
+    pub fn watch(&self, set: WatchSet) {
+        let _ = self.tx.send(Message::Watch(set));
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        // The inotify reader holds a sender too, so the channel never
+        // disconnects on its own
+        let _ = self.tx.send(Message::Stop);
+    }
+}
+
+/// Source of changed paths: inotify or mtime polling.
+trait Backend {
+    fn watch(&mut self, set: &WatchSet);
+    /// How long the watcher thread may block before calling `changes`.
+    /// `None` blocks until a message arrives.
+    fn timeout(&self, now: Instant) -> Option<Duration>;
+    fn changes(&mut self, set: &WatchSet) -> Vec<PathBuf>;
+}
+
+fn run(
+    mut set: WatchSet,
+    mut backend: Box<dyn Backend + Send>,
+    rx: mpsc::Receiver<Message>,
+    events: mpsc::Sender<AppEvent>,
+) {
+    backend.watch(&set);
+    let mut debouncer = Debouncer::default();
+    loop {
+        let now = Instant::now();
+        // Sleep until a message arrives, the backend is due or a pending
+        // path has been quiet long enough
+        let timeout = backend.timeout(now).into_iter().chain(debouncer.timeout(now)).min();
+        let message = match timeout {
+            Some(timeout) => match rx.recv_timeout(timeout) {
+                Ok(message) => Some(message),
+                Err(mpsc::RecvTimeoutError::Timeout) => None,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            },
+            None => match rx.recv() {
+                Ok(message) => Some(message),
+                Err(_) => return,
+            },
+        };
+        match message {
+            Some(Message::Stop) => return,
+            Some(Message::Watch(next)) if next != set => {
+                set = next;
+                backend.watch(&set);
+            }
+            Some(Message::Watch(_) | Message::Wake) | None => {}
+        }
+
+        let now = Instant::now();
+        for path in backend.changes(&set) {
+            debouncer.touch(path, now);
+        }
+        for path in debouncer.ready(now) {
+            // A temp file that matched an Include glob but was renamed away
+            if !set.is_watched(&path) && !path.exists() {
+                continue;
+            }
+            if events.send(AppEvent::ConfigChanged { path }).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn backend(wake: mpsc::Sender<Message>) -> Box<dyn Backend + Send> {
+    match inotify_backend::Inotify::new(wake) {
+        Some(inotify) => Box::new(inotify),
+        None => Box::new(Poll::default()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn backend(_wake: mpsc::Sender<Message>) -> Box<dyn Backend + Send> {
+    Box::new(Poll::default())
+}
+
+/// Collapses bursts of events per path into one report.
+#[derive(Default)]
+struct Debouncer {
+    pending: Vec<(PathBuf, Instant)>,
+}
+
+impl Debouncer {
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        match self.pending.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, last)) => *last = now,
+            None => self.pending.push((path, now)),
+        }
+    }
+
+    fn ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut ready = Vec::new();
+        self.pending.retain(|(path, last)| {
+            if now.duration_since(*last) >= DEBOUNCE {
+                ready.push(path.clone());
+                false
+            } else {
+                true
+            }
+        });
+        ready
+    }
+
+    /// Time until the next pending path goes quiet, `None` when nothing is pending.
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.pending
+            .iter()
+            .map(|(_, last)| DEBOUNCE.saturating_sub(now.duration_since(*last)))
+            .min()
+    }
+}
+
+/// Fallback backend: stat every watched file and glob match on an interval.
+#[derive(Default)]
+struct Poll {
+    last_check: Option<Instant>,
+    mtimes: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Poll {
+    fn snapshot(set: &WatchSet) -> Vec<(PathBuf, Option<SystemTime>)> {
+        let mut paths: Vec<PathBuf> = set.files().cloned().collect();
+        for glob in &set.globs {
+            if let Ok(matches) = glob::glob(&glob.to_string_lossy()) {
+                for path in matches.flatten() {
+                    if !paths.contains(&path) {
+                        paths.push(path);
+                    }
+                }
+            }
+        }
+        paths
+            .into_iter()
+            .map(|path| {
+                let mtime = std::fs::metadata(&path).ok().and_then(|m| m.modified().ok());
+                (path, mtime)
+            })
+            .collect()
+    }
+}
+
+impl Backend for Poll {
+    fn watch(&mut self, set: &WatchSet) {
+        self.mtimes = Self::snapshot(set);
+        self.last_check = Some(Instant::now());
+    }
+
+    fn timeout(&self, now: Instant) -> Option<Duration> {
+        let elapsed = self.last_check.map_or(POLL_INTERVAL, |t| now.duration_since(t));
+        Some(POLL_INTERVAL.saturating_sub(elapsed))
+    }
+
+    fn changes(&mut self, set: &WatchSet) -> Vec<PathBuf> {
+        if self.last_check.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return Vec::new();
+        }
+        let current = Self::snapshot(set);
+        let mut changed: Vec<PathBuf> = current
+            .iter()
+            .filter(|entry| !self.mtimes.contains(entry))
+            .map(|(path, _)| path.clone())
+            .collect();
+        // Deleted files
+        for (path, _) in &self.mtimes {
+            if !current.iter().any(|(p, _)| p == path) && !changed.contains(path) {
+                changed.push(path.clone());
+            }
+        }
+        self.mtimes = current;
+        self.last_check = Some(Instant::now());
+        changed
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify_backend {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use inotify::{EventMask, EventOwned, WatchDescriptor, WatchMask, Watches};
+
+    use super::{Backend, Message, WatchSet};
+
+    /// Watches directories with inotify. A reader thread blocks on the
+    /// inotify fd and hands events over as they arrive, so nothing wakes
+    /// while the files are quiet.
+    pub struct Inotify {
+        watches: Watches,
+        dirs: HashMap<WatchDescriptor, PathBuf>,
+        events: mpsc::Receiver<Vec<EventOwned>>,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl Inotify {
+        pub fn new(wake: mpsc::Sender<Message>) -> Option<Self> {
+            let mut inotify = inotify::Inotify::init().ok()?;
+            let watches = inotify.watches();
+            let (tx, events) = mpsc::channel();
+            let stop = Arc::new(AtomicBool::new(false));
+            let stopped = Arc::clone(&stop);
+            thread::spawn(move || {
+                let mut buffer = vec![0; 4096];
+                while let Ok(events) = inotify.read_events_blocking(&mut buffer) {
+                    if stopped.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    let events: Vec<EventOwned> = events.map(|event| event.to_owned()).collect();
+                    if tx.send(events).is_err() || wake.send(Message::Wake).is_err() {
+                        return;
+                    }
+                }
+            });
+            Some(Self {
+                watches,
+                dirs: HashMap::new(),
+                events,
+                stop,
+            })
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            // Removing a watch queues an IN_IGNORED event, which wakes the
+            // reader so it sees the flag and exits
+            self.stop.store(true, Ordering::Relaxed);
+            for wd in self.dirs.drain().map(|(wd, _)| wd) {
+                let _ = self.watches.remove(wd);
+            }
+        }
+    }
+
+    impl Backend for Inotify {
+        fn watch(&mut self, set: &WatchSet) {
+            for wd in self.dirs.drain().map(|(wd, _)| wd) {
+                let _ = self.watches.remove(wd);
+            }
+            let mask = WatchMask::CLOSE_WRITE
+                | WatchMask::MODIFY
+                | WatchMask::CREATE
+                | WatchMask::DELETE
+                | WatchMask::MOVED_FROM
+                | WatchMask::MOVED_TO;
+            for dir in set.dirs() {
+                // Missing directories are skipped. They show up once an
+                // Include resolves and the set is refreshed.
+                if let Ok(wd) = self.watches.add(&dir, mask) {
+                    self.dirs.insert(wd, dir);
+                }
+            }
+        }
+
+        fn timeout(&self, _now: Instant) -> Option<Duration> {
+            None
+        }
+
+        fn changes(&mut self, set: &WatchSet) -> Vec<PathBuf> {
+            let mut changed = Vec::new();
+            for event in self.events.try_iter().flatten() {
+                if event.mask.contains(EventMask::Q_OVERFLOW) {
+                    // Events were dropped. Report the main config so the
+                    // app falls back to a full reload.
+                    changed.push(set.config.clone());
+                    continue;
+                }
+                // Events for watches removed by `watch` no longer have a
+                // directory and are skipped
+                let (Some(dir), Some(name)) = (self.dirs.get(&event.wd), event.name) else {
+                    continue;
+                };
+                let path = dir.join(name);
+                // Editor temp and backup files land in the same directory.
+                // Only files we read, or new files an Include glob would
+                // pick up, are relevant.
+                let relevant = set.is_watched(&path)
+                    || (event.mask.intersects(EventMask::CLOSE_WRITE | EventMask::MOVED_TO)
+                        && set.matches_glob(&path));
+                if relevant && !changed.contains(&path) {
+                    changed.push(path);
+                }
+            }
+            changed
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(dir: &Path) -> WatchSet {
+        WatchSet {
+            config: dir.join("config"),
+            includes: vec![dir.join("conf.d/work")],
+            globs: vec![dir.join("conf.d/*")],
+        }
+    }
+
+    #[test]
+    fn debouncer_reports_once_after_quiet_period() {
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+        let path = PathBuf::from("/tmp/config");
+        debouncer.touch(path.clone(), start);
+        debouncer.touch(path.clone(), start + Duration::from_millis(100));
+        assert!(debouncer.ready(start + Duration::from_millis(200)).is_empty());
+        assert_eq!(debouncer.ready(start + Duration::from_millis(250)), vec![path]);
+        assert!(debouncer.ready(start + Duration::from_secs(1)).is_empty());
+    }
+
+    #[test]
+    fn debouncer_timeout_only_while_pending() {
+        let mut debouncer = Debouncer::default();
+        let start = Instant::now();
+        assert_eq!(debouncer.timeout(start), None);
+        debouncer.touch(PathBuf::from("/tmp/a"), start);
+        debouncer.touch(PathBuf::from("/tmp/b"), start + Duration::from_millis(100));
+        assert_eq!(debouncer.timeout(start + Duration::from_millis(100)), Some(Duration::from_millis(50)));
+        assert_eq!(debouncer.timeout(start + Duration::from_secs(1)), Some(Duration::ZERO));
+        debouncer.ready(start + Duration::from_secs(1));
+        assert_eq!(debouncer.timeout(start + Duration::from_secs(1)), None);
+    }
+
+    #[test]
+    fn watch_set_dirs_and_globs() {
+        let dir = Path::new("/home/u/.ssh");
+        let set = set(dir);
+        assert_eq!(set.dirs(), vec![dir.to_path_buf(), dir.join("conf.d")]);
+        assert!(set.is_watched(&dir.join("conf.d/work")));
+        assert!(!set.is_watched(&dir.join("conf.d/.work.swp~")));
+        assert!(set.matches_glob(&dir.join("conf.d/new")));
+        assert!(!set.matches_glob(&dir.join("config~")));
+    }
+
+    #[test]
+    fn poll_backend_reports_modified_and_new_files() {
+        let dir = std::env::temp_dir().join(format!("purple_test_watch_poll_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(dir.join("config"), "Include conf.d/*\n").unwrap();
+        std::fs::write(dir.join("conf.d/work"), "Host a\n").unwrap();
+        let set = set(&dir);
+        let mut poll = Poll::default();
+        poll.watch(&set);
+        assert!(poll.changes(&set).is_empty(), "throttled until the interval passes");
+
+        // Pretend the last stat was long ago and the file was older
+        poll.last_check = None;
+        poll.mtimes[1].1 = Some(SystemTime::UNIX_EPOCH);
+        std::fs::write(dir.join("conf.d/new"), "Host b\n").unwrap();
+        let changed = poll.changes(&set);
+        assert_eq!(changed, vec![dir.join("conf.d/work"), dir.join("conf.d/new")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn inotify_reports_rename_over_watched_file() {
+        let dir = std::env::temp_dir().join(format!("purple_test_watch_inotify_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(dir.join("config"), "Include conf.d/*\n").unwrap();
+        std::fs::write(dir.join("conf.d/work"), "Host a\n").unwrap();
+        let set = set(&dir);
+        let (tx, rx) = mpsc::channel();
+        let _watcher = ConfigWatcher::spawn(set, tx);
+        // Let the thread set up its watches
+        thread::sleep(Duration::from_millis(100));
+
+        // Save the way editors do: write a temp file, then rename it over
+        std::fs::write(dir.join("conf.d/.work.tmp"), "Host b\n").unwrap();
+        std::fs::rename(dir.join("conf.d/.work.tmp"), dir.join("conf.d/work")).unwrap();
+        std::fs::write(dir.join("config~"), "backup\n").unwrap();
+
+        let mut reported = Vec::new();
+        while let Ok(AppEvent::ConfigChanged { path }) = rx.recv_timeout(Duration::from_secs(1)) {
+            reported.push(path);
+        }
+        assert_eq!(reported, vec![dir.join("conf.d/work")]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn inotify_picks_up_new_watch_set_while_idle() {
+        let dir = std::env::temp_dir().join(format!("purple_test_watch_reset_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(dir.join("config"), "Host a\n").unwrap();
+        let (tx, rx) = mpsc::channel();
+        let watcher = ConfigWatcher::spawn(WatchSet { config: dir.join("config"), ..Default::default() }, tx);
+        thread::sleep(Duration::from_millis(100));
+
+        // The thread is blocked with nothing pending. A new set must still
+        // take effect without any file activity to wake it.
+        watcher.watch(set(&dir));
+        thread::sleep(Duration::from_millis(100));
+        std::fs::write(dir.join("conf.d/work"), "Host b\n").unwrap();
+        match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(AppEvent::ConfigChanged { path }) => assert_eq!(path, dir.join("conf.d/work")),
+            _ => panic!("expected a change for the newly watched Include"),
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -2,13 +2,13 @@
 //!
 //! Tests every code path that modifies the config: serialize(), add_host(),
 //! delete_host(), delete_host_undoable(), update_host(), swap_hosts(),
-//! set_host_tags(), insert_host_at().
+//! set_host_tags().
 //!
 //! Tests marked with "BUG:" in their name demonstrate actual formatting issues.
 
 use std::path::PathBuf;
 
-use purple_ssh::ssh_config::model::{ConfigElement, HostBlock, HostEntry, SshConfigFile};
+use purple_ssh::ssh_config::model::{ConfigElement, HostEntry, SshConfigFile};
 
 /// Helper: parse a string into an SshConfigFile without touching disk.
 fn parse_str(content: &str) -> SshConfigFile {
@@ -16,6 +16,7 @@ fn parse_str(content: &str) -> SshConfigFile {
         elements: SshConfigFile::parse_content(content),
         path: PathBuf::from("/tmp/test_config"),
         crlf: content.contains("\r\n"),
+        index: Default::default(),
     }
 }
 
@@ -448,7 +449,7 @@ fn add_host_with_non_default_port() {
         hostname: "10.0.0.1".to_string(),
         user: "admin".to_string(),
         port: 2222,
-        identity_file: "~/.ssh/id_rsa".to_string(),
+        identity_files: vec!["~/.ssh/id_rsa".to_string()],
         proxy_jump: "bastion".to_string(),
         ..Default::default()
     });
@@ -732,59 +733,6 @@ fn delete_undoable_nonexistent_returns_none() {
     assert!(result.is_none());
 }
 
-// ============================================================================
-// 5. UNDO DELETE (insert_host_at)
-// ============================================================================
-
-#[test]
-fn undo_delete_restores_exact_position() {
-    let input = "\
-Host alpha
-  HostName a.com
-
-Host beta
-  HostName b.com
-
-Host gamma
-  HostName g.com
-";
-    let mut config = parse_str(input);
-    let (element, position) = config.delete_host_undoable("beta").unwrap();
-    config.insert_host_at(element, position);
-    let output = config.serialize();
-    assert_eq_visible(input, &output);
-}
-
-#[test]
-fn undo_delete_first_host() {
-    let input = "\
-Host alpha
-  HostName a.com
-
-Host beta
-  HostName b.com
-";
-    let mut config = parse_str(input);
-    let (element, position) = config.delete_host_undoable("alpha").unwrap();
-    config.insert_host_at(element, position);
-    assert_eq_visible(input, &config.serialize());
-}
-
-#[test]
-fn undo_delete_last_host() {
-    let input = "\
-Host alpha
-  HostName a.com
-
-Host beta
-  HostName b.com
-";
-    let mut config = parse_str(input);
-    let (element, position) = config.delete_host_undoable("beta").unwrap();
-    config.insert_host_at(element, position);
-    assert_eq_visible(input, &config.serialize());
-}
-
 // ============================================================================
 // 6. UPDATE/EDIT HOST
 // ============================================================================
@@ -925,7 +873,7 @@ Host myserver
             hostname: "10.0.0.1".to_string(),
             user: "newuser".to_string(),
             port: 22,
-            identity_file: "~/.ssh/id_rsa".to_string(),
+            identity_files: vec!["~/.ssh/id_rsa".to_string()],
             ..Default::default()
         },
     );
@@ -950,7 +898,7 @@ Host myserver
             hostname: "10.0.0.1".to_string(),
             user: "admin".to_string(),
             port: 22,
-            identity_file: "".to_string(),
+            identity_files: Vec::new(),
             ..Default::default()
         },
     );
@@ -1822,30 +1770,6 @@ Host alpha
     assert!(!output.contains("Host beta"));
 }
 
-#[test]
-fn add_delete_undo_sequence() {
-    let input = "\
-Host alpha
-  HostName a.com
-
-Host beta
-  HostName b.com
-";
-    let mut config = parse_str(input);
-    config.add_host(&HostEntry {
-        alias: "gamma".to_string(),
-        hostname: "g.com".to_string(),
-        port: 22,
-        ..Default::default()
-    });
-    let (element, position) = config.delete_host_undoable("beta").unwrap();
-    config.insert_host_at(element, position);
-    let output = config.serialize();
-    assert!(output.contains("Host alpha"));
-    assert!(output.contains("Host beta"));
-    assert!(output.contains("Host gamma"));
-}
-
 #[test]
 fn multiple_adds_check_spacing() {
     let mut config = parse_str("");
@@ -2033,30 +1957,6 @@ Host bhost
     assert!(output.contains("# Group A"));
 }
 
-#[test]
-fn delete_undoable_then_add_new_then_undo() {
-    let input = "\
-Host alpha
-  HostName a.com
-
-Host beta
-  HostName b.com
-";
-    let mut config = parse_str(input);
-    let (element, position) = config.delete_host_undoable("alpha").unwrap();
-    config.add_host(&HostEntry {
-        alias: "gamma".to_string(),
-        hostname: "g.com".to_string(),
-        port: 22,
-        ..Default::default()
-    });
-    config.insert_host_at(element, position);
-    let output = config.serialize();
-    assert!(output.contains("Host alpha"));
-    assert!(output.contains("Host beta"));
-    assert!(output.contains("Host gamma"));
-}
-
 // ============================================================================
 // 11. DETAILED BLANK LINE BEHAVIOR
 // ============================================================================
@@ -2168,6 +2068,7 @@ fn serialize_empty_elements_produces_just_newline() {
         elements: Vec::new(),
         path: PathBuf::from("/tmp/test"),
         crlf: false,
+        index: Default::default(),
     };
     assert_eq!(config.serialize(), "\n");
 }
@@ -2494,80 +2395,6 @@ fn crlf_preserved_in_round_trip() {
     );
 }
 
-// ============================================================================
-// 17. INSERT_HOST_AT EDGE CASES
-// ============================================================================
-
-#[test]
-fn insert_host_at_beginning() {
-    let input = "Host beta\n  HostName b.com\n";
-    let mut config = parse_str(input);
-    let new_block = HostBlock {
-        host_pattern: "alpha".to_string(),
-        raw_host_line: "Host alpha".to_string(),
-        directives: vec![purple_ssh::ssh_config::model::Directive {
-            key: "HostName".to_string(),
-            value: "a.com".to_string(),
-            raw_line: "  HostName a.com".to_string(),
-            is_non_directive: false,
-        }],
-    };
-    config.insert_host_at(ConfigElement::HostBlock(new_block), 0);
-    let output = config.serialize();
-    let alpha_pos = output.find("Host alpha").unwrap();
-    let beta_pos = output.find("Host beta").unwrap();
-    assert!(alpha_pos < beta_pos);
-}
-
-#[test]
-fn insert_host_at_beyond_length() {
-    let input = "Host alpha\n  HostName a.com\n";
-    let mut config = parse_str(input);
-    let new_block = HostBlock {
-        host_pattern: "beta".to_string(),
-        raw_host_line: "Host beta".to_string(),
-        directives: vec![purple_ssh::ssh_config::model::Directive {
-            key: "HostName".to_string(),
-            value: "b.com".to_string(),
-            raw_line: "  HostName b.com".to_string(),
-            is_non_directive: false,
-        }],
-    };
-    config.insert_host_at(ConfigElement::HostBlock(new_block), 999);
-    let output = config.serialize();
-    let alpha_pos = output.find("Host alpha").unwrap();
-    let beta_pos = output.find("Host beta").unwrap();
-    assert!(alpha_pos < beta_pos);
-}
-
-/// insert_host_at with no blank line separator creates hosts without spacing.
-/// This is expected since insert_host_at is a low-level undo primitive,
-/// but it means undo can produce outputs that differ from add_host.
-#[test]
-fn insert_host_at_no_automatic_blank_separator() {
-    let input = "Host alpha\n  HostName a.com\n";
-    let mut config = parse_str(input);
-    let new_block = HostBlock {
-        host_pattern: "beta".to_string(),
-        raw_host_line: "Host beta".to_string(),
-        directives: vec![purple_ssh::ssh_config::model::Directive {
-            key: "HostName".to_string(),
-            value: "b.com".to_string(),
-            raw_line: "  HostName b.com".to_string(),
-            is_non_directive: false,
-        }],
-    };
-    config.insert_host_at(ConfigElement::HostBlock(new_block), 1);
-    let output = config.serialize();
-    // No blank line between alpha and beta because insert_host_at doesn't add one
-    // and alpha has no trailing blank directive
-    assert!(
-        output.contains("a.com\nHost beta"),
-        "insert_host_at does not add blank separator. Got:\n{}",
-        visible(&output)
-    );
-}
-
 // ============================================================================
 // 18. STRESS: CUMULATIVE FORMATTING DRIFT
 // ============================================================================
@@ -2995,10 +2822,11 @@ Host myserver
 ";
     let config = parse_str(input);
     let entries = config.host_entries();
-    assert_eq!(entries[0].identity_file, "~/.ssh/id_ed25519",
-        "to_host_entry should capture the FIRST IdentityFile");
+    assert_eq!(entries[0].identity_files,
+        vec!["~/.ssh/id_ed25519", "~/.ssh/id_rsa", "~/.ssh/company_key"],
+        "to_host_entry should capture every IdentityFile in order");
 
-    // Edit hostname only (identity_file unchanged from to_host_entry)
+    // Edit hostname only (identity_files unchanged from to_host_entry)
     let mut config = parse_str(input);
     let mut entry = config.host_entries().remove(0);
     entry.hostname = "10.0.0.2".to_string();
@@ -3022,14 +2850,15 @@ Host myserver
         output);
 }
 
-/// Changing the primary IdentityFile updates only the first directive.
+/// Changing the primary IdentityFile rewrites the first directive in place.
+/// The second keeps its original line byte for byte.
 #[test]
 fn update_host_changes_first_identity_file_only() {
     let input = "\
 Host myserver
   HostName 10.0.0.1
   IdentityFile ~/.ssh/id_ed25519
-  IdentityFile ~/.ssh/id_rsa
+  IdentityFile    ~/.ssh/id_rsa
 ";
     let mut config = parse_str(input);
     config.update_host(
@@ -3037,7 +2866,7 @@ Host myserver
         &HostEntry {
             alias: "myserver".to_string(),
             hostname: "10.0.0.1".to_string(),
-            identity_file: "~/.ssh/new_key".to_string(),
+            identity_files: vec!["~/.ssh/new_key".to_string(), "~/.ssh/id_rsa".to_string()],
             port: 22,
             ..Default::default()
         },
@@ -3049,15 +2878,83 @@ Host myserver
     assert_eq!(id_lines.len(), 2, "Should still have 2 IdentityFile directives");
     assert!(id_lines[0].contains("~/.ssh/new_key"),
         "First IdentityFile should be updated");
-    assert!(id_lines[1].contains("~/.ssh/id_rsa"),
+    assert_eq!(id_lines[1], "  IdentityFile    ~/.ssh/id_rsa",
         "Second IdentityFile should be untouched");
 }
 
+// ============================================================================
+// 25b. QUOTED VALUES AND PATHS WITH SPACES
+// ============================================================================
+
+/// Quoted values parse unquoted and serialize exactly as written.
+#[test]
+fn roundtrip_quoted_paths_with_spaces() {
+    let input = "\
+Host \"my box\"
+  HostName 10.0.0.1
+  IdentityFile \"~/Library/Mobile Documents/keys/id_ed25519\"
+  IdentityFile '~/keys/second key'
+  CertificateFile ~/keys/with\\ escape-cert.pub
+";
+    let config = parse_str(input);
+    assert_eq_visible(input, &config.serialize());
+    let entry = &config.host_entries()[0];
+    assert_eq!(entry.alias, "my box");
+    assert_eq!(
+        entry.identity_files,
+        vec!["~/Library/Mobile Documents/keys/id_ed25519", "~/keys/second key"]
+    );
+    assert_eq!(entry.certificate_files, vec!["~/keys/with escape-cert.pub"]);
+}
+
+/// Editing another field leaves quoted lines alone, whatever quote style they use.
+#[test]
+fn update_host_keeps_quoted_lines_untouched() {
+    let input = "\
+Host \"my box\"
+  HostName 10.0.0.1
+  IdentityFile '~/keys/second key'
+  CertificateFile ~/keys/with\\ escape-cert.pub
+";
+    let mut config = parse_str(input);
+    let mut entry = config.host_entries().remove(0);
+    entry.hostname = "10.0.0.2".to_string();
+    config.update_host("my box", &entry);
+    assert_eq_visible(&input.replace("10.0.0.1", "10.0.0.2"), &config.serialize());
+}
+
+/// New and changed values with spaces are written quoted and read back unquoted.
+#[test]
+fn add_and_update_quote_values_with_spaces() {
+    let mut config = parse_str("Host web\n  HostName 10.0.0.1\n");
+    config.add_host(&HostEntry {
+        alias: "my box".to_string(),
+        hostname: "10.0.0.2".to_string(),
+        identity_files: vec!["~/keys/with space".to_string(), "~/.ssh/id_rsa".to_string()],
+        port: 22,
+        ..Default::default()
+    });
+    let output = config.serialize();
+    assert!(output.contains("Host \"my box\"\n"), "{}", output);
+    assert!(output.contains("  IdentityFile \"~/keys/with space\"\n"), "{}", output);
+    assert!(output.contains("  IdentityFile ~/.ssh/id_rsa\n"), "{}", output);
+
+    let mut config = parse_str(&output);
+    let mut entry = config.host_entries().remove(1);
+    assert_eq!(entry.alias, "my box");
+    assert_eq!(entry.identity_files[0], "~/keys/with space");
+    entry.identity_files[1] = "~/keys/say \"hi\"".to_string();
+    config.update_host("my box", &entry);
+    let output = config.serialize();
+    assert!(output.contains("  IdentityFile \"~/keys/say \\\"hi\\\"\"\n"), "{}", output);
+    assert_eq!(parse_str(&output).host_entries()[1].identity_files, entry.identity_files);
+}
+
 // ============================================================================
 // 26. MATCH BLOCKS
 // ============================================================================
 
-/// Match blocks are stored as GlobalLines and survive host deletion.
+/// Match blocks survive host deletion.
 #[test]
 fn match_block_preserved_through_host_deletion() {
     let input = "\
@@ -3108,6 +3005,75 @@ Host beta
     assert_eq_visible(input, &config.serialize());
 }
 
+#[test]
+fn match_block_complex_formatting_round_trip() {
+    let input = "\
+# Office network
+Match originalhost *.corp exec \"test -f ~/.vpn\"  # only on VPN
+\tProxyJump   bastion
+\t# comment inside match
+\tUser=deploy
+
+Match canonical all
+  ServerAliveInterval 60
+";
+    let config = parse_str(input);
+    assert_eq_visible(input, &config.serialize());
+}
+
+#[test]
+fn match_block_crlf_round_trip() {
+    let input = "Host a\r\n  HostName a.com\r\n\r\nMatch user root\r\n  IdentityFile ~/.ssh/root\r\n";
+    let config = parse_str(input);
+    assert_eq_visible(input, &config.serialize());
+}
+
+/// Editing one directive in a Match block leaves every other byte untouched.
+#[test]
+fn match_block_edit_preserves_rest_of_file() {
+    let input = "\
+Host alpha
+  HostName a.com
+
+Match host *.example.com   # wildcard
+  ForwardAgent yes
+  User deploy
+
+Host beta
+  HostName b.com
+";
+    let mut config = parse_str(input);
+    let pairs = vec![
+        ("ForwardAgent".to_string(), "yes".to_string()),
+        ("User".to_string(), "admin".to_string()),
+    ];
+    assert!(config.update_match_block(0, "host *.example.com", &pairs));
+    assert_eq_visible(&input.replace("User deploy", "User admin"), &config.serialize());
+}
+
+/// Host edits next to a Match block don't touch the Match block.
+#[test]
+fn host_edit_next_to_match_block() {
+    let input = "\
+Match all
+  ServerAliveInterval 60
+
+Host myserver
+  HostName 10.0.0.1
+";
+    let mut config = parse_str(input);
+    config.update_host(
+        "myserver",
+        &HostEntry {
+            alias: "myserver".to_string(),
+            hostname: "10.0.0.2".to_string(),
+            port: 22,
+            ..Default::default()
+        },
+    );
+    assert_eq_visible(&input.replace("10.0.0.1", "10.0.0.2"), &config.serialize());
+}
+
 /// Clearing IdentityFile removes all IdentityFile directives.
 #[test]
 fn update_host_clear_identity_file_removes_all() {
@@ -3123,7 +3089,7 @@ Host myserver
         &HostEntry {
             alias: "myserver".to_string(),
             hostname: "10.0.0.1".to_string(),
-            identity_file: "".to_string(),
+            identity_files: Vec::new(),
             port: 22,
             ..Default::default()
         },
@@ -3551,7 +3517,7 @@ fn update_host_equals_in_value_preserves_space_separator() {
     let entry = HostEntry {
         alias: "myserver".to_string(),
         hostname: "10.0.0.2".to_string(),
-        identity_file: "~/.ssh/id=staging".to_string(),
+        identity_files: vec!["~/.ssh/id=staging".to_string()],
         port: 22,
         ..Default::default()
     };
@@ -3570,7 +3536,7 @@ fn update_host_equals_separator_with_equals_in_value() {
     let entry = HostEntry {
         alias: "myserver".to_string(),
         hostname: "10.0.0.2".to_string(),
-        identity_file: "~/.ssh/id=staging".to_string(),
+        identity_files: vec!["~/.ssh/id=staging".to_string()],
         port: 22,
         ..Default::default()
     };
@@ -3619,6 +3585,7 @@ fn test_section(provider: &str, prefix: &str) -> ProviderSection {
         url: String::new(),
         verify_tls: true,
         auto_sync: true,
+        template: String::new(),
     }
 }
 